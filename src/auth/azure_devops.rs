@@ -4,7 +4,6 @@ use crate::auth::AuthSource;
 use crate::error::{Error, Result};
 use base64::Engine;
 use reqwest::Client;
-use serde::Deserialize;
 use std::env;
 use tokio::process::Command;
 use tracing::debug;
@@ -112,17 +111,23 @@ async fn get_az_cli_token() -> Option<String> {
     None
 }
 
-
 /// Test Azure DevOps authentication
 pub async fn test_azure_devops_auth(config: &AzureDevOpsAuthConfig) -> Result<String> {
     // If we have an organization, use the organization-scoped endpoint
     // Otherwise use the profile endpoint which works without organization
-    let url = if let Some(ref org) = config.organization {
-        format!("https://{}/{}/_apis/connectionData?api-version=7.1-preview", config.host, org)
-    } else {
+    let url = config.organization.as_ref().map_or_else(
         // Use profile endpoint as fallback (works at account level)
-        format!("https://app.vssps.visualstudio.com/_apis/profile/profiles/me?api-version=7.1-preview")
-    };
+        || {
+            "https://app.vssps.visualstudio.com/_apis/profile/profiles/me?api-version=7.1-preview"
+                .to_string()
+        },
+        |org| {
+            format!(
+                "https://{}/{}/_apis/connectionData?api-version=7.1-preview",
+                config.host, org
+            )
+        },
+    );
 
     let client = Client::builder()
         .timeout(std::time::Duration::from_secs(30))
@@ -0,0 +1,200 @@
+//! `ryu adopt` command - pick up an existing stack from its PRs
+
+use crate::cli::history::record_change;
+use crate::cli::style::{CHECK, Stylize, arrow, check, spinner_style};
+use anstream::println;
+use chrono::Utc;
+use indicatif::ProgressBar;
+use jj_ryu::error::{Error, Result};
+use jj_ryu::platform::{create_platform_service, parse_pr_reference, parse_repo_info};
+use jj_ryu::repo::{JjWorkspace, select_remote_with_default};
+use jj_ryu::submit::{AdoptedStack, StackSource, discover_stack};
+use jj_ryu::tracking::{
+    MetadataSnapshot, TrackedBookmark, lock_state, save_pr_cache, save_tracking,
+};
+use jj_ryu::types::{PlatformConfig, PullRequest};
+use std::path::Path;
+use std::time::Duration;
+
+/// Options for the adopt command
+#[derive(Debug, Clone, Default)]
+pub struct AdoptOptions {
+    /// Remote the stack's branches live on
+    pub remote: Option<String>,
+    /// Track only in the current workspace instead of repo-wide
+    pub workspace: bool,
+}
+
+/// Run the adopt command
+pub async fn run_adopt(path: &Path, pr: &str, options: AdoptOptions) -> Result<()> {
+    let pr_number = parse_pr_reference(pr)?;
+
+    let mut workspace = JjWorkspace::open(path)?;
+
+    let (remote_name, platform_config) = select_platform(&workspace, options.remote.as_deref())?;
+    let platform = create_platform_service(&platform_config).await?;
+    let default_branch = workspace.default_branch()?;
+
+    let stack = discover_stack(platform.as_ref(), pr_number, &default_branch).await?;
+    if stack.prs.is_empty() {
+        return Err(Error::NoStack(format!(
+            "PR #{pr_number} has no open PRs in its stack to adopt"
+        )));
+    }
+    print_adopted_stack(&stack);
+
+    let missing = fetch_stack(&mut workspace, &stack, &remote_name)?;
+    let scope = options.workspace.then(|| workspace.workspace_name());
+    let adopted = track_prs(
+        &workspace,
+        &stack.prs,
+        &remote_name,
+        &missing,
+        scope.as_deref(),
+    )?;
+
+    println!();
+    println!(
+        "{} {} bookmark{}",
+        format!("{CHECK} Adopted").success(),
+        adopted.len().accent(),
+        if adopted.len() == 1 { "" } else { "s" }
+    );
+    if let Some(top) = adopted.last() {
+        println!(
+            "{}",
+            format!("Run 'jj new {top}' to start working on top of the stack").muted()
+        );
+    }
+
+    Ok(())
+}
+
+/// Pick the remote to use and the platform it's hosted on
+pub(super) fn select_platform(
+    workspace: &JjWorkspace,
+    remote: Option<&str>,
+) -> Result<(String, PlatformConfig)> {
+    let remotes = workspace.git_remotes()?;
+    let configured_remote = workspace.configured_push_remote()?;
+    let remote_name = select_remote_with_default(&remotes, remote, configured_remote.as_deref())?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = parse_repo_info(&remote_info.url)?;
+    Ok((remote_name, platform_config))
+}
+
+/// List the PRs found for the stack
+pub(super) fn print_adopted_stack(stack: &AdoptedStack) {
+    let source = match stack.source {
+        StackSource::StackComment => "stack comment",
+        StackSource::BaseRefs => "base branches",
+    };
+    println!(
+        "Found {} open PR{} on {} (from {source}):",
+        stack.prs.len().accent(),
+        if stack.prs.len() == 1 { "" } else { "s" },
+        stack.base_branch.emphasis()
+    );
+    for pr in &stack.prs {
+        println!(
+            "  {} #{} {} {}",
+            arrow(),
+            pr.number,
+            pr.head_ref.accent(),
+            pr.title.muted()
+        );
+    }
+    for pr in &stack.skipped {
+        println!(
+            "  {}",
+            format!("#{} {} ({}, skipped)", pr.number, pr.head_ref, pr.state).muted()
+        );
+    }
+}
+
+/// Fetch the stack's branches and base, creating tracked local bookmarks
+///
+/// Returns the branches the remote doesn't have.
+pub(super) fn fetch_stack(
+    workspace: &mut JjWorkspace,
+    stack: &AdoptedStack,
+    remote_name: &str,
+) -> Result<Vec<String>> {
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(spinner_style());
+    spinner.set_message(format!("Fetching from {}...", remote_name.emphasis()));
+    spinner.enable_steady_tick(Duration::from_millis(80));
+
+    let mut fetch_names = vec![stack.base_branch.as_str()];
+    fetch_names.extend(stack.prs.iter().map(|pr| pr.head_ref.as_str()));
+    let missing = workspace.git_fetch_and_track(remote_name, &fetch_names)?;
+
+    spinner.finish_with_message(format!(
+        "{} Fetched from {}",
+        check(),
+        remote_name.emphasis()
+    ));
+    Ok(missing)
+}
+
+/// Track the bookmarks named after the PRs' head branches and cache the PRs
+///
+/// Branches in `missing` are skipped with a warning. Returns the names of the
+/// bookmarks that were tracked, in the order of `prs`.
+pub(super) fn track_prs<'a>(
+    workspace: &JjWorkspace,
+    prs: &'a [PullRequest],
+    remote_name: &str,
+    missing: &[String],
+    scope: Option<&str>,
+) -> Result<Vec<&'a str>> {
+    let workspace_root = workspace.workspace_root();
+
+    // Re-read under the lock so concurrent ryu runs don't lose updates
+    let _lock = lock_state(workspace_root)?;
+    let before = MetadataSnapshot::load(workspace_root)?;
+    let mut state = before.tracking.clone();
+    let mut pr_cache = before.pr_cache.clone();
+
+    let mut tracked = Vec::new();
+    for pr in prs {
+        let name = pr.head_ref.as_str();
+        if missing.iter().any(|m| m == name) {
+            println!(
+                "{}",
+                format!("Branch '{name}' not found on {remote_name} (PR from a fork?), skipping")
+                    .warn()
+            );
+            continue;
+        }
+        let change_id = workspace
+            .get_change_id(name)?
+            .ok_or_else(|| Error::BookmarkNotFound(name.to_string()))?;
+
+        // Replace any stale entry from an earlier track or adopt
+        state.untrack(name);
+        state.track(TrackedBookmark {
+            name: name.to_string(),
+            change_id,
+            remote: Some(remote_name.to_string()),
+            workspace: scope.map(str::to_string),
+            tracked_at: Utc::now(),
+        });
+        pr_cache.upsert(name, pr, remote_name);
+        tracked.push(name);
+    }
+
+    if tracked.is_empty() {
+        return Err(Error::NoStack(
+            "None of the PRs' branches exist on the remote".to_string(),
+        ));
+    }
+    save_tracking(workspace_root, &state)?;
+    save_pr_cache(workspace_root, &pr_cache)?;
+    record_change(workspace, before, None);
+
+    Ok(tracked)
+}
@@ -3,9 +3,10 @@
 use crate::cli::style::{self, Stylize, check, pipe, up_arrow};
 use anstream::println;
 use jj_ryu::error::Result;
-use jj_ryu::graph::build_change_graph;
+use jj_ryu::graph::{build_change_graph, build_workspace_change_graph};
 use jj_ryu::repo::JjWorkspace;
-use jj_ryu::tracking::{load_pr_cache, load_tracking};
+use jj_ryu::submit::load_journal;
+use jj_ryu::tracking::{TrackingState, load_pr_cache, load_tracking};
 use std::path::Path;
 
 /// Run the analyze command (default when no subcommand given)
@@ -16,6 +17,7 @@ pub async fn run_analyze(path: &Path) -> Result<()> {
     // Open workspace
     let workspace = JjWorkspace::open(path)?;
     let workspace_root = workspace.workspace_root().to_path_buf();
+    let workspace_name = workspace.workspace_name();
 
     // Load tracking state and PR cache
     let tracking = load_tracking(&workspace_root).unwrap_or_default();
@@ -35,6 +37,7 @@ pub async fn run_analyze(path: &Path) -> Result<()> {
             "{}",
             "Create a bookmark with: jj bookmark create <name>".muted()
         );
+        print_other_workspaces(&workspace, &tracking, &workspace_name)?;
         return Ok(());
     };
 
@@ -83,7 +86,7 @@ pub async fn run_analyze(path: &Path) -> Result<()> {
             if is_first_in_segment && !bookmark_names.is_empty() {
                 for bm in &bookmark_names {
                     let bookmark = segment.bookmarks.iter().find(|b| b.name == *bm).unwrap();
-                    let is_tracked = tracking.is_tracked(bm);
+                    let is_tracked = tracking.is_tracked_in(bm, &workspace_name);
 
                     // Tracking/sync status indicator
                     let status = if is_tracked {
@@ -107,20 +110,45 @@ pub async fn run_analyze(path: &Path) -> Result<()> {
                         String::new()
                     };
 
+                    let conflict = if bookmark.is_conflicted {
+                        format!(" {}", "(conflicted)".error())
+                    } else {
+                        String::new()
+                    };
+
                     // Dim untracked bookmark names
                     if is_tracked {
-                        println!("       [{}{}]{}", bm.accent(), pr_info.muted(), status);
+                        println!(
+                            "       [{}{}]{}{}",
+                            bm.accent(),
+                            pr_info.muted(),
+                            status,
+                            conflict
+                        );
                     } else {
-                        println!("       [{}]{}", bm.muted(), status);
+                        println!("       [{}]{}{}", bm.muted(), status, conflict);
                     }
                 }
             }
+            let conflict_flag = if change.has_conflicts {
+                format!(" {}", "(conflict)".error())
+            } else {
+                String::new()
+            };
+            let divergent_flag = if change.is_divergent {
+                format!(" {}", "(divergent)".warn())
+            } else {
+                String::new()
+            };
+
             println!(
-                "    {}  {} {} {}",
+                "    {}  {} {} {}{}{}",
                 marker,
                 change_short.muted(),
                 commit_short.muted(),
-                desc_display
+                desc_display,
+                conflict_flag,
+                divergent_flag
             );
             println!("    {}", pipe());
         }
@@ -136,7 +164,7 @@ pub async fn run_analyze(path: &Path) -> Result<()> {
         .segments
         .iter()
         .flat_map(|s| &s.bookmarks)
-        .filter(|b| tracking.is_tracked(&b.name))
+        .filter(|b| tracking.is_tracked_in(&b.name, &workspace_name))
         .count();
     let untracked_count = total_bookmarks - tracked_count;
 
@@ -192,8 +220,89 @@ pub async fn run_analyze(path: &Path) -> Result<()> {
         );
     }
 
+    // Explain states that block submission
+    let blocked = stack.segments.iter().any(|segment| {
+        segment.bookmarks.iter().any(|b| b.is_conflicted)
+            || segment
+                .changes
+                .iter()
+                .any(|c| c.has_conflicts || c.is_divergent)
+    });
+    if blocked {
+        println!();
+        println!(
+            "{} This stack has conflicted bookmarks, conflicted commits, or divergent changes.",
+            "!".warn()
+        );
+        println!(
+            "  {}",
+            "Resolve them before submitting (see 'jj bookmark set', 'jj resolve', 'jj abandon')."
+                .muted()
+        );
+    }
+
+    // Warn about a submission that stopped partway through
+    if let Ok(Some(journal)) = load_journal(&workspace_root) {
+        println!();
+        println!(
+            "{} Interrupted submission from {} ({}/{} steps done{})",
+            "!".warn(),
+            journal.started_at.format("%Y-%m-%d %H:%M"),
+            journal.completed_count(),
+            journal.total_count(),
+            journal
+                .last_error
+                .as_ref()
+                .map(|e| format!(": {e}"))
+                .unwrap_or_default()
+        );
+        println!("  Continue it with: {}", "ryu submit --resume".accent());
+    }
+
+    print_other_workspaces(&workspace, &tracking, &workspace_name)?;
+
     println!();
     println!("To submit this stack: {}", "ryu submit".accent());
 
     Ok(())
 }
+
+/// Print a one-line summary of the stack in every other workspace of the repo
+fn print_other_workspaces(
+    workspace: &JjWorkspace,
+    tracking: &TrackingState,
+    current: &str,
+) -> Result<()> {
+    let others: Vec<String> = workspace
+        .workspace_names()?
+        .into_iter()
+        .filter(|name| name != current)
+        .collect();
+    if others.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    println!("{}", "Other workspaces:".emphasis());
+    for name in others {
+        let graph = build_workspace_change_graph(workspace, &name)?;
+        let Some(stack) = graph.stack.filter(|s| !s.segments.is_empty()) else {
+            println!("  {}: {}", name.accent(), "no stack".muted());
+            continue;
+        };
+        let bookmarks: Vec<String> = stack
+            .segments
+            .iter()
+            .flat_map(|s| &s.bookmarks)
+            .map(|b| {
+                if tracking.is_tracked_in(&b.name, &name) {
+                    b.name.accent().to_string()
+                } else {
+                    b.name.muted().to_string()
+                }
+            })
+            .collect();
+        println!("  {}: {}", name.accent(), bookmarks.join(", "));
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! `ryu checkout` command - check out a PR stack for review
+
+use crate::cli::adopt::{fetch_stack, print_adopted_stack, select_platform, track_prs};
+use crate::cli::style::{CHECK, Stylize};
+use anstream::println;
+use jj_ryu::error::{Error, Result};
+use jj_ryu::platform::{create_platform_service, parse_pr_reference};
+use jj_ryu::repo::JjWorkspace;
+use jj_ryu::submit::discover_stack_below;
+use std::path::Path;
+
+/// Options for the checkout command
+#[derive(Debug, Clone, Default)]
+pub struct CheckoutOptions {
+    /// Remote the stack's branches live on
+    pub remote: Option<String>,
+    /// Track only in the current workspace instead of repo-wide
+    pub workspace: bool,
+}
+
+/// Run the checkout command
+pub async fn run_checkout(path: &Path, pr: &str, options: CheckoutOptions) -> Result<()> {
+    let pr_number = parse_pr_reference(pr)?;
+
+    let mut workspace = JjWorkspace::open(path)?;
+    let (remote_name, platform_config) = select_platform(&workspace, options.remote.as_deref())?;
+    let platform = create_platform_service(&platform_config).await?;
+    let default_branch = workspace.default_branch()?;
+
+    let stack = discover_stack_below(platform.as_ref(), pr_number, &default_branch).await?;
+    let target = stack
+        .prs
+        .last()
+        .filter(|pr| pr.number == pr_number)
+        .ok_or_else(|| Error::NoStack(format!("PR #{pr_number} is not open")))?;
+    print_adopted_stack(&stack);
+
+    let missing = fetch_stack(&mut workspace, &stack, &remote_name)?;
+    if missing.contains(&target.head_ref) {
+        return Err(Error::BookmarkNotFound(format!(
+            "{}@{remote_name}",
+            target.head_ref
+        )));
+    }
+    let scope = options.workspace.then(|| workspace.workspace_name());
+    track_prs(
+        &workspace,
+        &stack.prs,
+        &remote_name,
+        &missing,
+        scope.as_deref(),
+    )?;
+
+    let commit_id = workspace
+        .get_local_bookmark(&target.head_ref)?
+        .map(|bookmark| bookmark.commit_id)
+        .ok_or_else(|| Error::BookmarkNotFound(target.head_ref.clone()))?;
+    workspace.new_change_on(&commit_id).await?;
+
+    println!();
+    println!(
+        "{} on top of #{} {}",
+        format!("{CHECK} Working copy is now").success(),
+        target.number,
+        target.head_ref.accent()
+    );
+    println!("{}", "Run 'ryu' to see the stack".muted());
+
+    Ok(())
+}
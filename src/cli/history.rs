@@ -0,0 +1,408 @@
+//! History and undo commands - ryu metadata versioned by jj operation
+
+use crate::cli::style::{Stylize, check};
+use anstream::{eprintln, println};
+use dialoguer::Confirm;
+use jj_ryu::error::{Error, Result};
+use jj_ryu::platform::{create_platform_service, parse_repo_info};
+use jj_ryu::repo::{JjWorkspace, select_remote};
+use jj_ryu::submit::delete_stack_comment;
+use jj_ryu::tracking::{
+    HistoryEntry, MetadataSnapshot, SubmissionRecord, latest_entry_at, load_history, lock_state,
+    record_history, remove_history_entry,
+};
+use jj_ryu::types::{BaseChange, PlatformConfig, PullRequest};
+use std::collections::HashSet;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+/// Options for the history command
+#[derive(Debug, Clone, Default)]
+pub struct HistoryOptions {
+    /// Show metadata as of this jj operation instead of listing changes
+    pub at: Option<String>,
+    /// Make the metadata shown by `at` current again
+    pub restore: bool,
+}
+
+/// Record a metadata change made by the running command in ryu's history.
+///
+/// Call with the state lock held, after the change has been saved. Best effort:
+/// a failure is reported but doesn't fail the command.
+pub(super) fn record_change(
+    workspace: &JjWorkspace,
+    before: MetadataSnapshot,
+    submission: Option<SubmissionRecord>,
+) {
+    let workspace_root = workspace.workspace_root();
+    let recorded = workspace.head_operation_id().and_then(|operation| {
+        let after = MetadataSnapshot::load(workspace_root)?;
+        let mut entry = HistoryEntry::new(operation, command_line(), before, after);
+        entry.submission = submission;
+        record_history(workspace_root, &mut entry)
+    });
+    if let Err(e) = recorded {
+        eprintln!(
+            "{}",
+            format!("Warning: failed to record ryu history: {e}").warn()
+        );
+    }
+}
+
+/// The ryu invocation being run, as recorded in history
+fn command_line() -> String {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        "ryu".to_string()
+    } else {
+        format!("ryu {}", args.join(" "))
+    }
+}
+
+/// Run the history command
+pub async fn run_history(path: &Path, options: HistoryOptions) -> Result<()> {
+    let workspace = JjWorkspace::open(path)?;
+    let workspace_root = workspace.workspace_root().to_path_buf();
+
+    let Some(op) = options.at.as_deref() else {
+        print_history(&load_history(&workspace_root)?);
+        return Ok(());
+    };
+
+    let _lock = options
+        .restore
+        .then(|| lock_state(&workspace_root))
+        .transpose()?;
+    let entries = load_history(&workspace_root)?;
+    let recorded: HashSet<&str> = entries.iter().map(|e| e.operation.as_str()).collect();
+    let entry = workspace
+        .nearest_operation(op, |id| recorded.contains(id))?
+        .and_then(|id| latest_entry_at(&entries, &id));
+
+    let snapshot = entry.map(|e| e.after.clone()).unwrap_or_default();
+    match entry {
+        Some(entry) => println!(
+            "{} {} ({}, {})",
+            "ryu metadata at operation".emphasis(),
+            op.accent(),
+            format!("last changed by '{}'", entry.command).muted(),
+            entry.recorded_at.format("%Y-%m-%d %H:%M").muted()
+        ),
+        None => println!(
+            "{}",
+            format!("No ryu changes recorded at or before operation {op}").muted()
+        ),
+    }
+    print_snapshot(&snapshot);
+
+    if options.restore {
+        let before = MetadataSnapshot::load(&workspace_root)?;
+        snapshot.restore(&workspace_root)?;
+        record_change(&workspace, before, None);
+        println!();
+        println!(
+            "{} Restored ryu metadata to operation {}",
+            check(),
+            op.accent()
+        );
+    }
+
+    Ok(())
+}
+
+/// Print recorded changes, newest first
+fn print_history(entries: &[HistoryEntry]) {
+    if entries.is_empty() {
+        println!("{}", "No ryu changes recorded".muted());
+        return;
+    }
+
+    for entry in entries.iter().rev() {
+        let prs: Vec<String> = entry
+            .created_prs()
+            .iter()
+            .map(|pr| format!("#{}", pr.number))
+            .collect();
+        let opened = if prs.is_empty() {
+            String::new()
+        } else {
+            format!(", opened {}", prs.join(" "))
+        };
+        println!(
+            "{}  {}  {}",
+            entry.short_operation().accent(),
+            entry.recorded_at.format("%Y-%m-%d %H:%M:%S").muted(),
+            entry.command
+        );
+        println!(
+            "    {}",
+            format!("{} tracked{}", entry.after.tracking.bookmarks.len(), opened).muted()
+        );
+    }
+}
+
+/// Print tracked bookmarks and their cached PRs
+fn print_snapshot(snapshot: &MetadataSnapshot) {
+    println!();
+    if snapshot.tracking.bookmarks.is_empty() {
+        println!("  {}", "No bookmarks tracked".muted());
+        return;
+    }
+    for bookmark in &snapshot.tracking.bookmarks {
+        let pr = snapshot
+            .pr_cache
+            .get(&bookmark.name)
+            .map(|p| format!(" #{}", p.number))
+            .unwrap_or_default();
+        println!("  {}{}", bookmark.name.accent(), pr.muted());
+    }
+}
+
+/// Options for the undo command
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UndoOptions {
+    /// Don't ask before changing anything on the remote
+    pub yes: bool,
+    /// Also delete branches the submission pushed for the first time
+    pub delete_branches: bool,
+}
+
+/// Remote changes needed to revert a submission
+#[derive(Debug, Default)]
+struct RemoteRevert {
+    remote: String,
+    close: Vec<PullRequest>,
+    restore_bases: Vec<BaseChange>,
+    remove_comments: Vec<u64>,
+    delete_branches: Vec<String>,
+    kept_branches: Vec<String>,
+}
+
+impl RemoteRevert {
+    fn new(submission: &SubmissionRecord, delete_branches: bool) -> Self {
+        let closing: HashSet<u64> = submission.created_prs.iter().map(|pr| pr.number).collect();
+        let (delete, kept) = if delete_branches {
+            (submission.new_branches.clone(), Vec::new())
+        } else {
+            (Vec::new(), submission.new_branches.clone())
+        };
+        Self {
+            remote: submission.remote.clone(),
+            close: submission.created_prs.clone(),
+            // Closed PRs keep whatever base and comments they had
+            restore_bases: submission
+                .base_changes
+                .iter()
+                .filter(|change| !closing.contains(&change.pr_number))
+                .cloned()
+                .collect(),
+            remove_comments: submission
+                .created_comments
+                .iter()
+                .copied()
+                .filter(|number| !closing.contains(number))
+                .collect(),
+            delete_branches: delete,
+            kept_branches: kept,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.close.is_empty()
+            && self.restore_bases.is_empty()
+            && self.remove_comments.is_empty()
+            && self.delete_branches.is_empty()
+    }
+
+    fn print(&self) {
+        for pr in &self.close {
+            println!(
+                "  Close #{} {} {}",
+                pr.number,
+                pr.title,
+                pr.html_url.muted()
+            );
+        }
+        for change in &self.restore_bases {
+            println!(
+                "  Retarget #{} ({}) {} → {}",
+                change.pr_number,
+                change.bookmark.accent(),
+                change.new_base.muted(),
+                change.previous_base
+            );
+        }
+        for number in &self.remove_comments {
+            println!("  Remove stack comment from #{number}");
+        }
+        for branch in &self.delete_branches {
+            println!("  Delete {} from {}", branch.accent(), self.remote);
+        }
+    }
+}
+
+/// Run the undo command
+///
+/// Rolls back the latest recorded ryu change. For a submission this closes
+/// the PRs it opened, restores the bases it changed, removes the stack
+/// comments it added and, if asked, deletes the branches it pushed first.
+/// Then the tracking state and PR cache are restored from before it ran.
+pub async fn run_undo(path: &Path, options: UndoOptions) -> Result<()> {
+    let workspace = JjWorkspace::open(path)?;
+    let workspace_root = workspace.workspace_root().to_path_buf();
+
+    let _lock = lock_state(&workspace_root)?;
+    let entries = load_history(&workspace_root)?;
+    let Some(entry) = entries.last() else {
+        println!("{}", "Nothing to undo".muted());
+        return Ok(());
+    };
+
+    println!(
+        "{} {} {}",
+        "Undo:".emphasis(),
+        entry.command.accent(),
+        format!(
+            "({}, jj operation {})",
+            entry.recorded_at.format("%Y-%m-%d %H:%M"),
+            entry.short_operation()
+        )
+        .muted()
+    );
+
+    let revert = entry
+        .submission
+        .as_ref()
+        .map(|submission| RemoteRevert::new(submission, options.delete_branches))
+        .unwrap_or_default();
+
+    if !revert.is_empty() {
+        println!();
+        revert.print();
+        println!();
+        if !confirm_revert(options.yes)? {
+            println!("{}", "Aborted".muted());
+            return Ok(());
+        }
+
+        let config = remote_platform(&workspace, &revert.remote)?;
+        let mut failed = revert_on_platform(&config, &revert).await;
+        failed.extend(delete_branches(&workspace, &revert));
+        if !failed.is_empty() {
+            return Err(Error::Platform(format!(
+                "undo incomplete ({}); tracking state left unchanged, re-run 'ryu undo' to retry",
+                failed.join("; ")
+            )));
+        }
+    }
+
+    entry.before.restore(&workspace_root)?;
+    remove_history_entry(&workspace_root, entry)?;
+
+    println!(
+        "{} Restored tracking state from before '{}'",
+        check(),
+        entry.command
+    );
+    if !revert.kept_branches.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "Kept branches pushed by this run: {} (use 'ryu undo --delete-branches' to remove them)",
+                revert.kept_branches.join(", ")
+            )
+            .muted()
+        );
+    }
+    Ok(())
+}
+
+/// Ask before changing the remote, unless `--yes` was given
+fn confirm_revert(yes: bool) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    if !io::stdin().is_terminal() {
+        return Err(Error::InvalidArgument(
+            "Refusing to change PRs without confirmation; pass --yes".to_string(),
+        ));
+    }
+    Confirm::new()
+        .with_prompt("Revert these changes on the remote?")
+        .default(false)
+        .interact()
+        .map_err(|e| Error::Internal(format!("Failed to read confirmation: {e}")))
+}
+
+/// Platform behind a remote
+fn remote_platform(workspace: &JjWorkspace, remote: &str) -> Result<PlatformConfig> {
+    let remotes = workspace.git_remotes()?;
+    let remote_name = select_remote(&remotes, Some(remote))?;
+    let remote = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    parse_repo_info(&remote.url)
+}
+
+/// Close PRs, restore bases and remove comments; returns what failed
+async fn revert_on_platform(config: &PlatformConfig, revert: &RemoteRevert) -> Vec<String> {
+    let platform = match create_platform_service(config).await {
+        Ok(platform) => platform,
+        Err(e) => return vec![e.to_string()],
+    };
+
+    let mut failed = Vec::new();
+    for pr in &revert.close {
+        match platform.close_pr(pr.number).await {
+            Ok(()) => println!("  {} Closed #{}", check(), pr.number),
+            Err(e) => failed.push(format!("close #{}: {e}", pr.number)),
+        }
+    }
+    for change in &revert.restore_bases {
+        match platform
+            .update_pr_base(change.pr_number, &change.previous_base)
+            .await
+        {
+            Ok(_) => println!(
+                "  {} Retargeted #{} to {}",
+                check(),
+                change.pr_number,
+                change.previous_base
+            ),
+            Err(e) => failed.push(format!("retarget #{}: {e}", change.pr_number)),
+        }
+    }
+    for &number in &revert.remove_comments {
+        match delete_stack_comment(platform.as_ref(), number).await {
+            Ok(_) => println!("  {} Removed stack comment from #{number}", check()),
+            Err(e) => failed.push(format!("remove comment from #{number}: {e}")),
+        }
+    }
+    failed
+}
+
+/// Delete first-pushed branches from the remote; returns what failed
+///
+/// Branches that are already gone or were moved by someone else are skipped.
+fn delete_branches(workspace: &JjWorkspace, revert: &RemoteRevert) -> Vec<String> {
+    if revert.delete_branches.is_empty() {
+        return Vec::new();
+    }
+    let branches: Vec<&str> = revert.delete_branches.iter().map(String::as_str).collect();
+    match workspace.delete_remote_branches(&branches, &revert.remote) {
+        Ok(report) => {
+            for branch in &report.pushed {
+                println!("  {} Deleted {} from {}", check(), branch, revert.remote);
+            }
+            for (branch, reason) in &report.rejected {
+                println!(
+                    "  {}",
+                    format!("Skipped deleting {branch}: {reason}").muted()
+                );
+            }
+            Vec::new()
+        }
+        Err(e) => vec![format!("delete branches: {e}")],
+    }
+}
@@ -0,0 +1,100 @@
+//! `ryu import` command - take over stacks from other stacked-PR tools
+
+use crate::cli::adopt::{select_platform, track_prs};
+use crate::cli::style::{CHECK, Stylize, check};
+use anstream::println;
+use jj_ryu::error::Result;
+use jj_ryu::import::{
+    GHSTACK_FETCH_PATTERN, ImportSource, find_import_candidates, resolve_import_prs,
+};
+use jj_ryu::platform::create_platform_service;
+use jj_ryu::repo::JjWorkspace;
+use jj_ryu::types::PullRequest;
+use std::path::Path;
+
+/// Options for the import command
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// Remote the PRs' branches live on
+    pub remote: Option<String>,
+    /// Track only in the current workspace instead of repo-wide
+    pub workspace: bool,
+}
+
+/// Run the import command
+pub async fn run_import(path: &Path, source: &str, options: ImportOptions) -> Result<()> {
+    let source = ImportSource::parse(source)?;
+
+    let mut workspace = JjWorkspace::open(path)?;
+    let (remote_name, platform_config) = select_platform(&workspace, options.remote.as_deref())?;
+    let platform = create_platform_service(&platform_config).await?;
+
+    if source == ImportSource::Ghstack {
+        workspace.git_fetch(&remote_name, &[GHSTACK_FETCH_PATTERN])?;
+    }
+    let candidates = find_import_candidates(&workspace, source, &remote_name)?;
+    if candidates.is_empty() {
+        println!("{}", format!("No {source} PRs found in trunk()..@").muted());
+        return Ok(());
+    }
+
+    let (imported, unmatched) = resolve_import_prs(platform.as_ref(), candidates).await?;
+    for candidate in &unmatched {
+        println!(
+            "{} No open PR for {} ({}), skipping",
+            "!".warn(),
+            candidate
+                .commit_id
+                .get(..8)
+                .unwrap_or(&candidate.commit_id)
+                .muted(),
+            candidate.pr
+        );
+    }
+
+    // Never move a bookmark the user already has for something else
+    let mut kept = Vec::with_capacity(imported.len());
+    for import in imported {
+        match workspace.get_local_bookmark(&import.pr.head_ref)? {
+            Some(bookmark) if bookmark.is_conflicted || bookmark.commit_id != import.commit_id => {
+                println!(
+                    "{} Bookmark {} already points to another commit, skipping #{}",
+                    "!".warn(),
+                    import.pr.head_ref.accent(),
+                    import.pr.number
+                );
+            }
+            _ => kept.push(import),
+        }
+    }
+    let imported = kept;
+    if imported.is_empty() {
+        return Ok(());
+    }
+
+    // Fetch the PRs' branches so later pushes lease against where they are now
+    let heads: Vec<&str> = imported.iter().map(|i| i.pr.head_ref.as_str()).collect();
+    workspace.git_fetch_bookmarks(&remote_name, &heads)?;
+    let targets: Vec<(&str, &str)> = imported
+        .iter()
+        .map(|i| (i.pr.head_ref.as_str(), i.commit_id.as_str()))
+        .collect();
+    workspace.set_tracked_bookmarks(&targets, &remote_name)?;
+
+    let prs: Vec<PullRequest> = imported.into_iter().map(|i| i.pr).collect();
+    let scope = options.workspace.then(|| workspace.workspace_name());
+    track_prs(&workspace, &prs, &remote_name, &[], scope.as_deref())?;
+
+    for pr in &prs {
+        println!("  {} #{} {}", check(), pr.number, pr.head_ref.accent());
+    }
+    println!();
+    println!(
+        "{} {} PR{} from {source}",
+        format!("{CHECK} Imported").success(),
+        prs.len().accent(),
+        if prs.len() == 1 { "" } else { "s" }
+    );
+
+    Ok(())
+}
@@ -2,8 +2,12 @@
 //!
 //! Command implementations for the `ryu` binary.
 
+mod adopt;
 mod analyze;
 mod auth;
+mod checkout;
+mod history;
+mod import;
 mod progress;
 pub mod style;
 mod submit;
@@ -11,8 +15,12 @@ mod sync;
 mod track;
 mod untrack;
 
+pub use adopt::{AdoptOptions, run_adopt};
 pub use analyze::run_analyze;
 pub use auth::run_auth;
+pub use checkout::{CheckoutOptions, run_checkout};
+pub use history::{HistoryOptions, UndoOptions, run_history, run_undo};
+pub use import::{ImportOptions, run_import};
 pub use progress::CliProgress;
 pub use submit::{SubmitOptions, SubmitScope, run_submit};
 pub use sync::{SyncOptions, run_sync};
@@ -7,6 +7,7 @@ use jj_ryu::error::Error;
 use jj_ryu::submit::{Phase, ProgressCallback, PushStatus};
 use jj_ryu::types::PullRequest;
 use std::io::Write;
+use std::time::Duration;
 
 /// CLI progress callback that prints to stdout with styled output
 ///
@@ -141,4 +142,13 @@ impl ProgressCallback for CliProgress {
             println!("  {}", message.muted());
         }
     }
+
+    async fn on_retry(&self, operation: &str, attempt: u32, delay: Duration, err: &Error) {
+        let indent = if self.verbose { "  " } else { "    " };
+        eprintln!(
+            "{indent}{} Failed to {operation} ({err}); retrying in {}s (retry {attempt})",
+            "!".warn(),
+            delay.as_secs()
+        );
+    }
 }
@@ -1,20 +1,29 @@
 //! Submit command - submit a bookmark stack as PRs
 
 use crate::cli::CliProgress;
+use crate::cli::history::record_change;
 use crate::cli::style::{CHECK, Stylize, arrow, bullet, cross};
 use anstream::{eprintln, println};
 use dialoguer::Confirm;
 use jj_ryu::error::{Error, Result};
 use jj_ryu::graph::build_change_graph;
-use jj_ryu::platform::{PlatformService, create_platform_service, parse_repo_info};
-use jj_ryu::repo::{JjWorkspace, select_remote};
+use jj_ryu::platform::{PlatformService, create_platform_service_with_progress, parse_repo_info};
+use jj_ryu::repo::{JjWorkspace, select_remote_with_default};
 use jj_ryu::submit::{
-    ExecutionStep, SubmissionAnalysis, SubmissionPlan, analyze_submission, create_submission_plan,
-    execute_submission, select_bookmark_for_segment,
+    CommentPolicy, DIAGRAM_CONFIG_KEY, ExecutionOptions, ExecutionStep, JournalStatus,
+    StackPlacement, SubmissionAnalysis, SubmissionPlan, SubmissionResult, analyze_submission,
+    apply_pr_edits, check_submittable, create_submission_plan_with_cache, execute_submission,
+    fill_pr_bodies, format_pr_edits, has_prs_to_create, load_journal, load_stack_comment_template,
+    resume_submission, select_bookmark_for_segment,
 };
-use jj_ryu::tracking::{load_pr_cache, load_tracking, save_pr_cache};
-use jj_ryu::types::{ChangeGraph, NarrowedBookmarkSegment};
+use jj_ryu::tracking::{
+    MetadataSnapshot, SubmissionRecord, load_pr_cache, load_tracking, lock_state, save_pr_cache,
+};
+use jj_ryu::types::{ChangeGraph, NarrowedBookmarkSegment, PullRequest};
+use std::collections::HashMap;
+use std::io::IsTerminal;
 use std::path::Path;
+use std::sync::Arc;
 
 /// Scope of bookmark submission (mutually exclusive options)
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
@@ -63,6 +72,14 @@ pub struct SubmitOptions<'a> {
     pub select: bool,
     /// Submit all bookmarks in `trunk()`..@ (ignore tracking)
     pub all: bool,
+    /// Resume an interrupted submission from its journal
+    pub resume: bool,
+    /// Maximum number of platform requests in flight at once
+    pub concurrency: usize,
+    /// Push all bookmarks atomically (all or nothing)
+    pub atomic: bool,
+    /// Edit new PRs' titles and descriptions in the editor first
+    pub edit: bool,
 }
 
 /// Run the submit command
@@ -80,13 +97,36 @@ pub async fn run_submit(
         ));
     }
 
+    let mut execution = ExecutionOptions {
+        dry_run: options.dry_run,
+        concurrency: options.concurrency,
+        atomic: options.atomic,
+        comment_template: None,
+        placement: StackPlacement::default(),
+        comment_policy: CommentPolicy::default(),
+        comment_diagram: false,
+    };
+
+    if options.resume {
+        return run_resume(path, execution).await;
+    }
+
     // Open workspace
     let mut workspace = JjWorkspace::open(path)?;
     let workspace_root = workspace.workspace_root().to_path_buf();
+    execution.comment_template = load_stack_comment_template(&workspace)?;
+    execution.placement = StackPlacement::from_config(&workspace)?;
+    execution.comment_policy = CommentPolicy::from_config(&workspace)?;
+    execution.comment_diagram = workspace
+        .config_bool(DIAGRAM_CONFIG_KEY)?
+        .unwrap_or_default();
 
     // Load tracking state (unless --all bypasses tracking)
     let tracking = load_tracking(&workspace_root)?;
-    let tracked_names: Vec<&str> = tracking.tracked_names().into_iter().collect();
+    let tracked_names: Vec<&str> = tracking
+        .tracked_names_in(&workspace.workspace_name())
+        .into_iter()
+        .collect();
 
     // If no bookmarks tracked and not --all, error
     if tracked_names.is_empty() && !options.all {
@@ -97,7 +137,8 @@ pub async fn run_submit(
 
     // Get remotes and select one
     let remotes = workspace.git_remotes()?;
-    let remote_name = select_remote(&remotes, remote)?;
+    let configured_remote = workspace.configured_push_remote()?;
+    let remote_name = select_remote_with_default(&remotes, remote, configured_remote.as_deref())?;
 
     // Detect platform from remote URL
     let remote_info = remotes
@@ -108,7 +149,9 @@ pub async fn run_submit(
     let platform_config = parse_repo_info(&remote_info.url)?;
 
     // Create platform service
-    let platform = create_platform_service(&platform_config).await?;
+    let progress = Arc::new(CliProgress::verbose());
+    let platform =
+        create_platform_service_with_progress(&platform_config, progress.clone()).await?;
 
     // Build change graph from working copy
     let graph = build_change_graph(&workspace)?;
@@ -148,15 +191,25 @@ pub async fn run_submit(
         }
     }
 
+    check_submittable(&analysis)?;
+
     // Display what will be submitted
     print_submission_summary(&analysis, &options);
 
     // Get default branch
     let default_branch = workspace.default_branch()?;
 
-    // Create submission plan
-    let mut plan =
-        create_submission_plan(&analysis, platform.as_ref(), &remote_name, &default_branch).await?;
+    // Create submission plan, fetching cached PRs by number
+    let pr_cache = load_pr_cache(&workspace_root).unwrap_or_default();
+    let mut plan = create_submission_plan_with_cache(
+        &analysis,
+        platform.as_ref(),
+        &remote_name,
+        &default_branch,
+        &pr_cache,
+    )
+    .await?;
+    print_pr_drift(&plan);
 
     // Apply plan modifications based on options
     apply_plan_options(&mut plan, &options);
@@ -171,6 +224,13 @@ pub async fn run_submit(
         filter_plan_to_selection(&mut plan, &selected);
     }
 
+    fill_pr_bodies(&mut plan, &mut workspace, platform_config.platform).await?;
+
+    if options.edit && has_prs_to_create(&plan) && !edit_prs_to_create(&workspace, &mut plan)? {
+        println!("{}", "Aborted".muted());
+        return Ok(());
+    }
+
     // Show confirmation if requested
     if options.confirm && !options.dry_run {
         print_plan_preview(&plan);
@@ -186,62 +246,356 @@ pub async fn run_submit(
         println!();
     }
 
+    // A fresh submission replaces any interrupted one
+    if !options.dry_run {
+        if let Ok(Some(journal)) = load_journal(&workspace_root) {
+            println!(
+                "{}",
+                format!(
+                    "Discarding interrupted submission from {} ({}/{} steps done)",
+                    journal.started_at.format("%Y-%m-%d %H:%M"),
+                    journal.completed_count(),
+                    journal.total_count()
+                )
+                .muted()
+            );
+        }
+    }
+
+    let pushes: Vec<&str> = plan
+        .execution_steps
+        .iter()
+        .filter_map(|step| match step {
+            ExecutionStep::Push(bm) => Some(bm.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    guard_unpushable(&workspace, &pushes, &remote_name)?;
+
+    // Never force-push over commits someone else added to our bookmarks
+    if !options.dry_run && !guard_remote_divergence(&mut workspace, &pushes, &remote_name)? {
+        return Ok(());
+    }
+
     // Execute plan
-    let progress = CliProgress::verbose();
     let result = execute_submission(
         &plan,
         &mut workspace,
         platform.as_ref(),
-        &progress,
-        options.dry_run,
+        progress.as_ref(),
+        execution,
+    )
+    .await?;
+
+    if !options.dry_run {
+        update_pr_cache(&workspace, &plan.existing_prs, &result, &remote_name);
+        print_result(&result, analysis.segments.len());
+    }
+
+    Ok(())
+}
+
+/// Open the editor on the PRs the plan creates and apply the edits
+///
+/// Returns `false` if the user cleared the file to abort.
+fn edit_prs_to_create(workspace: &JjWorkspace, plan: &mut SubmissionPlan) -> Result<bool> {
+    let command = workspace.editor_command()?;
+    // Removed when dropped
+    let file = tempfile::Builder::new()
+        .prefix("ryu-prs-")
+        .suffix(".md")
+        .tempfile()?;
+    std::fs::write(file.path(), format_pr_edits(plan))?;
+
+    let status = std::process::Command::new(&command[0])
+        .args(&command[1..])
+        .arg(file.path())
+        .status()
+        .map_err(|e| Error::Config(format!("Failed to run editor '{}': {e}", command[0])))?;
+    if !status.success() {
+        return Err(Error::Config(format!(
+            "Editor '{}' exited with {status}",
+            command[0]
+        )));
+    }
+    apply_pr_edits(plan, &std::fs::read_to_string(file.path())?)
+}
+
+/// Resume an interrupted submission recorded in the journal
+async fn run_resume(path: &Path, mut execution: ExecutionOptions) -> Result<()> {
+    let mut workspace = JjWorkspace::open(path)?;
+    let workspace_root = workspace.workspace_root().to_path_buf();
+    execution.comment_template = load_stack_comment_template(&workspace)?;
+    execution.placement = StackPlacement::from_config(&workspace)?;
+    execution.comment_policy = CommentPolicy::from_config(&workspace)?;
+    execution.comment_diagram = workspace
+        .config_bool(DIAGRAM_CONFIG_KEY)?
+        .unwrap_or_default();
+    let dry_run = execution.dry_run;
+
+    let journal = load_journal(&workspace_root)?
+        .ok_or_else(|| Error::InvalidArgument("No interrupted submission to resume".to_string()))?;
+
+    let remote_name = journal.plan.remote.clone();
+    let remotes = workspace.git_remotes()?;
+    let remote_info = remotes
+        .iter()
+        .find(|r| r.name == remote_name)
+        .ok_or_else(|| Error::RemoteNotFound(remote_name.clone()))?;
+    let platform_config = parse_repo_info(&remote_info.url)?;
+    let progress = Arc::new(CliProgress::verbose());
+    let platform =
+        create_platform_service_with_progress(&platform_config, progress.clone()).await?;
+
+    let bookmark_count = journal.plan.segments.len();
+    let existing_prs = journal.plan.existing_prs.clone();
+
+    let pushes: Vec<&str> = journal
+        .plan
+        .execution_steps
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !journal.is_completed(*idx))
+        .filter_map(|(_, step)| match step {
+            ExecutionStep::Push(bm) => Some(bm.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    guard_unpushable(&workspace, &pushes, &remote_name)?;
+    if !dry_run && !guard_remote_divergence(&mut workspace, &pushes, &remote_name)? {
+        return Ok(());
+    }
+
+    let result = resume_submission(
+        journal,
+        &mut workspace,
+        platform.as_ref(),
+        progress.as_ref(),
+        execution,
     )
     .await?;
 
-    // Update PR cache with results
-    if !options.dry_run && result.success {
-        let mut pr_cache = load_pr_cache(&workspace_root).unwrap_or_default();
+    if !dry_run {
+        update_pr_cache(&workspace, &existing_prs, &result, &remote_name);
+        print_result(&result, bookmark_count);
+    }
+
+    Ok(())
+}
+
+/// Stop before pushing commits `jj git push` would refuse (conflicted,
+/// undescribed, or private), listing each one by bookmark
+pub(super) fn guard_unpushable(
+    workspace: &JjWorkspace,
+    bookmarks: &[&str],
+    remote: &str,
+) -> Result<()> {
+    if bookmarks.is_empty() {
+        return Ok(());
+    }
+
+    let unpushable = workspace.unpushable_commits(bookmarks, remote)?;
+    if unpushable.is_empty() {
+        return Ok(());
+    }
+
+    for commit in &unpushable {
+        eprintln!(
+            "{} {} {} {}",
+            cross(),
+            format!("{}:", commit.bookmark).accent(),
+            commit
+                .commit
+                .commit_id
+                .get(..8)
+                .unwrap_or(&commit.commit.commit_id)
+                .muted(),
+            commit.reason
+        );
+    }
+    eprintln!();
+
+    let mut names: Vec<&str> = unpushable.iter().map(|c| c.bookmark.as_str()).collect();
+    names.dedup();
+    Err(Error::UnpushableCommits(format!(
+        "{}. Resolve conflicts, describe the commits ('jj describe'), or move private commits out of the stack, then try again",
+        names.join(", ")
+    )))
+}
+
+/// Fetch the bookmarks about to be pushed and stop if a remote copy has
+/// commits we don't have
+///
+/// Offers to import those commits when running interactively. Returns
+/// `Ok(false)` if they were imported and the submission should stop.
+fn guard_remote_divergence(
+    workspace: &mut JjWorkspace,
+    bookmarks: &[&str],
+    remote: &str,
+) -> Result<bool> {
+    if bookmarks.is_empty() {
+        return Ok(true);
+    }
+
+    let diverged = workspace.check_remote_divergence(bookmarks, remote)?;
+    if diverged.is_empty() {
+        return Ok(true);
+    }
+
+    for divergence in &diverged {
+        eprintln!(
+            "{} {}@{} has commits that aren't in your local bookmark:",
+            "!".warn(),
+            divergence.bookmark.accent(),
+            divergence.remote
+        );
+        for commit in &divergence.commits {
+            eprintln!(
+                "    {} {} ({})",
+                commit
+                    .commit_id
+                    .get(..8)
+                    .unwrap_or(&commit.commit_id)
+                    .muted(),
+                commit.description_first_line,
+                commit.author_name
+            );
+        }
+    }
+    eprintln!();
+
+    let names: Vec<&str> = diverged.iter().map(|d| d.bookmark.as_str()).collect();
+    let import = std::io::stdin().is_terminal()
+        && Confirm::new()
+            .with_prompt("Import the remote commits now (submission will stop)?")
+            .default(true)
+            .interact()
+            .map_err(|e| Error::Internal(format!("Failed to read confirmation: {e}")))?;
+
+    if import {
+        workspace.git_fetch_bookmarks(remote, &names)?;
+        println!(
+            "Imported remote commits for {}. Rebase your changes onto them (see 'jj log'), then run 'ryu submit' again.",
+            names.join(", ").accent()
+        );
+        return Ok(false);
+    }
+
+    Err(Error::RemoteDiverged(format!(
+        "refusing to overwrite remote commits on {}. Run 'jj git fetch', rebase your changes onto the remote commits, then run 'ryu submit' again",
+        names.join(", ")
+    )))
+}
+
+/// Warn about cached PRs that changed on the platform since they were cached
+pub(super) fn print_pr_drift(plan: &SubmissionPlan) {
+    for drift in &plan.drift {
+        eprintln!("{} {drift}", "!".warn());
+    }
+    if !plan.drift.is_empty() {
+        eprintln!();
+    }
+}
+
+/// Update PR cache with the PRs found during planning and submission results,
+/// and record the run in ryu's history so `ryu undo` can revert it
+///
+/// A failed run leaves the cache alone but is still recorded if it changed
+/// anything on the remote.
+pub(super) fn update_pr_cache(
+    workspace: &JjWorkspace,
+    existing_prs: &HashMap<String, PullRequest>,
+    result: &SubmissionResult,
+    remote_name: &str,
+) {
+    let submission = SubmissionRecord {
+        remote: remote_name.to_string(),
+        created_prs: result.created_prs.clone(),
+        updated_prs: result.updated_prs.clone(),
+        pushed_bookmarks: result.pushed_bookmarks.clone(),
+        new_branches: result.new_branches.clone(),
+        base_changes: result.base_changes.clone(),
+        created_comments: result.created_comments.clone(),
+    };
+    if !result.success && submission.is_empty() {
+        return;
+    }
+
+    // Best effort - don't fail submit if the cache can't be locked or written
+    let workspace_root = workspace.workspace_root();
+    let Ok(_lock) = lock_state(workspace_root) else {
+        return;
+    };
+    let Ok(before) = MetadataSnapshot::load(workspace_root) else {
+        return;
+    };
+    if result.success {
+        let mut pr_cache = before.pr_cache.clone();
+        for (bookmark, pr) in existing_prs {
+            pr_cache.upsert(bookmark, pr, remote_name);
+        }
         for pr in result.created_prs.iter().chain(result.updated_prs.iter()) {
-            pr_cache.upsert(&pr.head_ref, pr, &remote_name);
+            pr_cache.upsert(&pr.head_ref, pr, remote_name);
+        }
+        if save_pr_cache(workspace_root, &pr_cache).is_err() {
+            return;
         }
-        // Best effort - don't fail submit if cache write fails
-        let _ = save_pr_cache(&workspace_root, &pr_cache);
     }
+    record_change(workspace, before, Some(submission));
+}
 
-    // Summary
-    if !options.dry_run {
-        println!();
-        if result.success {
+/// Print the submission summary
+fn print_result(result: &SubmissionResult, bookmark_count: usize) {
+    println!();
+    if result.success {
+        println!(
+            "{} {} bookmark{}",
+            format!("{CHECK} Successfully submitted").success(),
+            bookmark_count.accent(),
+            if bookmark_count == 1 { "" } else { "s" }
+        );
+
+        if !result.created_prs.is_empty() {
             println!(
-                "{} {} bookmark{}",
-                format!("{CHECK} Successfully submitted").success(),
-                analysis.segments.len().accent(),
-                if analysis.segments.len() == 1 {
+                "Created {} PR{}",
+                result.created_prs.len().accent(),
+                if result.created_prs.len() == 1 {
                     ""
                 } else {
                     "s"
                 }
             );
-
-            if !result.created_prs.is_empty() {
-                println!(
-                    "Created {} PR{}",
-                    result.created_prs.len().accent(),
-                    if result.created_prs.len() == 1 {
-                        ""
-                    } else {
-                        "s"
-                    }
-                );
-            }
+        }
+    } else {
+        eprintln!("{} Submission failed", cross());
+        for err in &result.errors {
+            eprintln!("  {}", err.error());
+        }
+        if result.applied_refs.is_empty() {
+            eprintln!("{}", "No refs were updated on the remote".muted());
         } else {
-            eprintln!("{} Submission failed", cross());
-            for err in &result.errors {
-                eprintln!("  {}", err.error());
+            eprintln!(
+                "{} {}",
+                "Refs updated on the remote:".muted(),
+                result.applied_refs.join(", ")
+            );
+        }
+        match result.journal {
+            JournalStatus::Saved => eprintln!(
+                "{}",
+                "Progress was saved; run 'ryu submit --resume' to continue".muted()
+            ),
+            JournalStatus::SaveFailed => eprintln!(
+                "{}",
+                "Progress could not be saved, so this submission can't be resumed; \
+                 run 'ryu submit' again"
+                    .warn()
+            ),
+            JournalStatus::Cleared => {
+                eprintln!("{}", "Run 'ryu submit' again to retry".muted());
             }
         }
     }
-
-    Ok(())
 }
 
 /// Build submission analysis based on options
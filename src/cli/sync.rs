@@ -2,22 +2,30 @@
 
 use crate::cli::CliProgress;
 use crate::cli::style::{CHECK, Stylize, arrow, check, spinner_style};
+use crate::cli::submit::{guard_unpushable, print_pr_drift, update_pr_cache};
 use anstream::println;
-use dialoguer::Confirm;
+use dialoguer::{Confirm, Select};
 use indicatif::ProgressBar;
 use jj_ryu::error::{Error, Result};
 use jj_ryu::graph::build_change_graph;
-use jj_ryu::platform::{create_platform_service, parse_repo_info};
-use jj_ryu::repo::{JjWorkspace, select_remote};
+use jj_ryu::platform::{create_platform_service_with_progress, parse_repo_info};
+use jj_ryu::repo::{ALL_BOOKMARKS, JjWorkspace, select_remote_with_default};
 use jj_ryu::submit::{
-    SubmissionPlan, analyze_submission, create_submission_plan, execute_submission,
+    CommentPolicy, DIAGRAM_CONFIG_KEY, ExecutionOptions, ExecutionStep, StackPlacement,
+    SubmissionPlan, analyze_submission, check_submittable, create_submission_plan_with_cache,
+    execute_submission, fill_pr_bodies, load_stack_comment_template,
 };
-use jj_ryu::tracking::load_tracking;
+use jj_ryu::tracking::{load_pr_cache, load_tracking};
+use jj_ryu::types::RemoteDivergence;
+use std::collections::BTreeSet;
+use std::io::IsTerminal;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Options for the sync command
 #[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct SyncOptions {
     /// Dry run - show what would be done without making changes
     pub dry_run: bool,
@@ -25,6 +33,10 @@ pub struct SyncOptions {
     pub confirm: bool,
     /// Sync all bookmarks in `trunk()`..@ (ignore tracking)
     pub all: bool,
+    /// Maximum number of platform requests in flight at once
+    pub concurrency: usize,
+    /// Fetch every branch from the remote instead of just the stack's
+    pub full_fetch: bool,
 }
 
 /// Run the sync command
@@ -36,7 +48,10 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions) -
 
     // Load tracking state (unless --all bypasses tracking)
     let tracking = load_tracking(&workspace_root)?;
-    let tracked_names: Vec<&str> = tracking.tracked_names().into_iter().collect();
+    let tracked_names: Vec<&str> = tracking
+        .tracked_names_in(&workspace.workspace_name())
+        .into_iter()
+        .collect();
 
     // If no bookmarks tracked and not --all, error
     if tracked_names.is_empty() && !options.all {
@@ -47,7 +62,20 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions) -
 
     // Get remotes and select one
     let remotes = workspace.git_remotes()?;
-    let remote_name = select_remote(&remotes, remote)?;
+    let configured_remote = workspace.configured_push_remote()?;
+    let remote_name = select_remote_with_default(&remotes, remote, configured_remote.as_deref())?;
+
+    // Fetch from jj's `git.fetch` remotes unless a remote was given
+    let fetch_remotes = match workspace.configured_fetch_remotes()? {
+        configured if remote.is_none() && !configured.is_empty() => configured,
+        _ => vec![remote_name.clone()],
+    };
+    if let Some(missing) = fetch_remotes
+        .iter()
+        .find(|name| !remotes.iter().any(|r| &r.name == *name))
+    {
+        return Err(Error::RemoteNotFound(missing.clone()));
+    }
 
     // Detect platform
     let remote_info = remotes
@@ -58,21 +86,57 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions) -
     let platform_config = parse_repo_info(&remote_info.url)?;
 
     // Create platform service
-    let platform = create_platform_service(&platform_config).await?;
+    let progress = Arc::new(CliProgress::compact());
+    let platform =
+        create_platform_service_with_progress(&platform_config, progress.clone()).await?;
+
+    // Catch commits a teammate pushed to our branches before the fetch
+    // folds them into the local bookmarks
+    let mut prefetched = Vec::new();
+    if !options.dry_run {
+        prefetched = stack_bookmark_names(&workspace, &tracked_names, options.all)?;
+        if !resolve_foreign_commits(&mut workspace, &prefetched, &remote_name).await? {
+            println!("{}", "Aborted".muted());
+            return Ok(());
+        }
+    }
 
     // Fetch from remote with spinner
     if !options.dry_run {
         let spinner = ProgressBar::new_spinner();
         spinner.set_style(spinner_style());
-        spinner.set_message(format!("Fetching from {}...", remote_name.emphasis()));
+        let fetch_names = fetch_remotes.join(", ");
+        spinner.set_message(format!("Fetching from {}...", fetch_names.emphasis()));
         spinner.enable_steady_tick(Duration::from_millis(80));
 
-        workspace.git_fetch(&remote_name)?;
+        let names = if options.full_fetch {
+            None
+        } else {
+            Some(stack_fetch_names(&workspace)?)
+        };
+        for fetch_remote in &fetch_remotes {
+            let patterns: Vec<String> = names.as_ref().map_or_else(
+                || vec![ALL_BOOKMARKS.to_string()],
+                |names| {
+                    // The divergence check already fetched the stack's
+                    // bookmarks from the push remote
+                    names
+                        .iter()
+                        .filter(|name| *fetch_remote != remote_name || !prefetched.contains(name))
+                        .map(|name| format!("exact:{name}"))
+                        .collect()
+                },
+            );
+            if !patterns.is_empty() {
+                let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+                workspace.git_fetch(fetch_remote, &patterns)?;
+            }
+        }
 
         spinner.finish_with_message(format!(
             "{} Fetched from {}",
             check(),
-            remote_name.emphasis()
+            fetch_names.emphasis()
         ));
     }
 
@@ -89,7 +153,6 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions) -
     }
 
     let default_branch = workspace.default_branch()?;
-    let progress = CliProgress::compact();
 
     // Analyze and plan for the single stack
     let mut analysis = analyze_submission(&graph, None)?;
@@ -106,8 +169,35 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions) -
         }
     }
 
-    let plan =
-        create_submission_plan(&analysis, platform.as_ref(), &remote_name, &default_branch).await?;
+    check_submittable(&analysis)?;
+
+    let pr_cache = load_pr_cache(&workspace_root).unwrap_or_default();
+    let comment_template = load_stack_comment_template(&workspace)?;
+    let placement = StackPlacement::from_config(&workspace)?;
+    let comment_policy = CommentPolicy::from_config(&workspace)?;
+    let comment_diagram = workspace
+        .config_bool(DIAGRAM_CONFIG_KEY)?
+        .unwrap_or_default();
+    let mut plan = create_submission_plan_with_cache(
+        &analysis,
+        platform.as_ref(),
+        &remote_name,
+        &default_branch,
+        &pr_cache,
+    )
+    .await?;
+    print_pr_drift(&plan);
+    fill_pr_bodies(&mut plan, &mut workspace, platform_config.platform).await?;
+
+    let pushes: Vec<&str> = plan
+        .execution_steps
+        .iter()
+        .filter_map(|step| match step {
+            ExecutionStep::Push(bm) => Some(bm.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    guard_unpushable(&workspace, &pushes, &remote_name)?;
 
     // Show confirmation if requested
     if options.confirm && !options.dry_run {
@@ -135,8 +225,16 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions) -
         &plan,
         &mut workspace,
         platform.as_ref(),
-        &progress,
-        options.dry_run,
+        progress.as_ref(),
+        ExecutionOptions {
+            dry_run: options.dry_run,
+            concurrency: options.concurrency,
+            atomic: false,
+            comment_template,
+            placement,
+            comment_policy,
+            comment_diagram,
+        },
     )
     .await?;
 
@@ -145,6 +243,7 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions) -
     if options.dry_run {
         println!("{}", "Dry run complete".muted());
     } else {
+        update_pr_cache(&workspace, &plan.existing_prs, &result, &remote_name);
         println!(
             "{} {} pushed, {} created, {} updated",
             format!("{CHECK} Sync complete:").success(),
@@ -157,6 +256,145 @@ pub async fn run_sync(path: &Path, remote: Option<&str>, options: SyncOptions) -
     Ok(())
 }
 
+/// Bookmarks a sync needs to fetch: trunk, every bookmark in the current
+/// stack, and the bases of their PRs
+fn stack_fetch_names(workspace: &JjWorkspace) -> Result<BTreeSet<String>> {
+    let graph = build_change_graph(workspace)?;
+    let pr_cache = load_pr_cache(workspace.workspace_root()).unwrap_or_default();
+
+    let mut names = BTreeSet::from([workspace.default_branch()?]);
+    for bookmark in graph
+        .stack
+        .iter()
+        .flat_map(|stack| &stack.segments)
+        .flat_map(|segment| &segment.bookmarks)
+    {
+        names.insert(bookmark.name.clone());
+        if let Some(base) = pr_cache.get(&bookmark.name).and_then(|pr| pr.base.clone()) {
+            names.insert(base);
+        }
+    }
+
+    Ok(names)
+}
+
+/// Bookmarks in the current stack that sync would push, root to leaf
+fn stack_bookmark_names(
+    workspace: &JjWorkspace,
+    tracked_names: &[&str],
+    all: bool,
+) -> Result<Vec<String>> {
+    let graph = build_change_graph(workspace)?;
+    Ok(graph
+        .stack
+        .iter()
+        .flat_map(|stack| &stack.segments)
+        .flat_map(|segment| &segment.bookmarks)
+        .filter(|bookmark| all || tracked_names.contains(&bookmark.name.as_str()))
+        .map(|bookmark| bookmark.name.clone())
+        .collect())
+}
+
+/// Fetch the stack's bookmarks and deal with commits others pushed to them
+///
+/// Lists the foreign commits by author and, when running interactively,
+/// offers to rebase the local stack on top of them. Returns `Ok(false)` if
+/// the user chose to abort.
+async fn resolve_foreign_commits(
+    workspace: &mut JjWorkspace,
+    bookmarks: &[String],
+    remote: &str,
+) -> Result<bool> {
+    let names: Vec<&str> = bookmarks.iter().map(String::as_str).collect();
+    if names.is_empty() {
+        return Ok(true);
+    }
+    let diverged = workspace.check_remote_divergence(&names, remote)?;
+    if diverged.is_empty() {
+        return Ok(true);
+    }
+
+    for divergence in &diverged {
+        print_foreign_commits(divergence);
+    }
+    println!();
+
+    let diverged_names: Vec<&str> = diverged.iter().map(|d| d.bookmark.as_str()).collect();
+    if !std::io::stdin().is_terminal() {
+        return Err(Error::RemoteDiverged(format!(
+            "others pushed commits to {}. Run 'ryu sync' in a terminal to rebase onto them, or rebase manually",
+            diverged_names.join(", ")
+        )));
+    }
+    let choice = Select::new()
+        .with_prompt("Others pushed to your stack")
+        .items(&["Rebase my stack on top of their commits", "Abort"])
+        .default(0)
+        .interact()
+        .map_err(|e| Error::Internal(format!("Failed to read selection: {e}")))?;
+    if choice != 0 {
+        return Ok(false);
+    }
+
+    for &name in &diverged_names {
+        workspace.rebase_onto_remote(name, remote).await?;
+        println!("{} Rebased onto {}@{remote}", check(), name.accent());
+    }
+    println!();
+
+    // Pushing a bookmark that doesn't contain its remote commits would
+    // overwrite them, however the rebase went
+    let mut missing = Vec::new();
+    for name in diverged_names {
+        if !workspace.contains_remote_bookmark(name, remote)? {
+            missing.push(name);
+        }
+    }
+    if !missing.is_empty() {
+        return Err(Error::RemoteDiverged(format!(
+            "{} still don't include the commits others pushed. Rebase them manually and run 'ryu sync' again",
+            missing.join(", ")
+        )));
+    }
+    Ok(true)
+}
+
+/// List the commits on a remote bookmark that we don't have, by author
+fn print_foreign_commits(divergence: &RemoteDivergence) {
+    println!(
+        "{} Someone else pushed to {}@{}:",
+        "!".warn(),
+        divergence.bookmark.accent(),
+        divergence.remote
+    );
+
+    let mut authors: Vec<(&str, &str)> = Vec::new();
+    for commit in &divergence.commits {
+        let author = (commit.author_name.as_str(), commit.author_email.as_str());
+        if !authors.contains(&author) {
+            authors.push(author);
+        }
+    }
+    for (name, email) in authors {
+        println!("    {name} <{email}>:");
+        for commit in divergence
+            .commits
+            .iter()
+            .filter(|c| c.author_name == name && c.author_email == email)
+        {
+            println!(
+                "      {} {}",
+                commit
+                    .commit_id
+                    .get(..8)
+                    .unwrap_or(&commit.commit_id)
+                    .muted(),
+                commit.description_first_line
+            );
+        }
+    }
+}
+
 /// Print sync preview for --confirm
 fn print_sync_preview(plan: &SubmissionPlan) {
     println!("{}:", "Sync plan".emphasis());
@@ -1,12 +1,15 @@
 //! `ryu track` command - explicit bookmark tracking
 
+use crate::cli::history::record_change;
 use crate::cli::style::{Stylize, check};
 use anyhow::Result;
 use chrono::Utc;
 use dialoguer::MultiSelect;
 use jj_ryu::graph::build_change_graph;
 use jj_ryu::repo::JjWorkspace;
-use jj_ryu::tracking::{TrackedBookmark, load_tracking, save_tracking};
+use jj_ryu::tracking::{
+    MetadataSnapshot, TrackedBookmark, load_tracking, lock_state, save_tracking,
+};
 use std::io::{self, IsTerminal};
 use std::path::Path;
 
@@ -18,6 +21,8 @@ pub struct TrackOptions {
     pub force: bool,
     /// Associate with specific remote
     pub remote: Option<String>,
+    /// Track only in the current workspace instead of repo-wide
+    pub workspace: bool,
 }
 
 /// Run the track command.
@@ -52,7 +57,7 @@ pub async fn run_track(path: &Path, bookmarks: &[String], options: TrackOptions)
     }
 
     // Load existing tracking state
-    let mut state = load_tracking(&workspace_root)?;
+    let state = load_tracking(&workspace_root)?;
 
     // Determine which bookmarks to track
     let bookmarks_to_track: Vec<&str> = if options.all {
@@ -125,6 +130,12 @@ pub async fn run_track(path: &Path, bookmarks: &[String], options: TrackOptions)
         return Ok(());
     }
 
+    // Re-read under the lock so concurrent ryu runs don't lose updates
+    let _lock = lock_state(&workspace_root)?;
+    let before = MetadataSnapshot::load(&workspace_root)?;
+    let mut state = before.tracking.clone();
+    let scope = options.workspace.then(|| workspace.workspace_name());
+
     // Track the bookmarks
     let mut tracked_names = Vec::new();
     for name in &bookmarks_to_track {
@@ -137,6 +148,7 @@ pub async fn run_track(path: &Path, bookmarks: &[String], options: TrackOptions)
             name: (*name).to_string(),
             change_id,
             remote: options.remote.clone(),
+            workspace: scope.clone(),
             tracked_at: Utc::now(),
         };
 
@@ -154,12 +166,19 @@ pub async fn run_track(path: &Path, bookmarks: &[String], options: TrackOptions)
 
     // Save state
     save_tracking(&workspace_root, &state)?;
+    if !tracked_names.is_empty() {
+        record_change(&workspace, before, None);
+    }
 
     // Print summary
+    let scope_note = scope
+        .as_ref()
+        .map(|name| format!(" in workspace {name}"))
+        .unwrap_or_default();
     if tracked_names.len() == 1 {
-        eprintln!("Tracked 1 bookmark:");
+        eprintln!("Tracked 1 bookmark{scope_note}:");
     } else {
-        eprintln!("Tracked {} bookmarks:", tracked_names.len());
+        eprintln!("Tracked {} bookmarks{scope_note}:", tracked_names.len());
     }
     for name in &tracked_names {
         eprintln!("  {} {}", check(), name.accent());
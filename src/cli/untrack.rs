@@ -1,10 +1,11 @@
 //! `ryu untrack` command - remove bookmarks from tracking
 
+use crate::cli::history::record_change;
 use crate::cli::style::{Stylize, check};
 use anyhow::Result;
 use dialoguer::MultiSelect;
 use jj_ryu::repo::JjWorkspace;
-use jj_ryu::tracking::{load_pr_cache, load_tracking, save_tracking};
+use jj_ryu::tracking::{MetadataSnapshot, load_pr_cache, load_tracking, lock_state, save_tracking};
 use std::io::{self, IsTerminal};
 use std::path::Path;
 
@@ -20,7 +21,7 @@ pub async fn run_untrack(path: &Path, bookmarks: &[String], options: UntrackOpti
     let workspace_root = workspace.workspace_root().to_path_buf();
 
     // Load existing tracking state
-    let mut state = load_tracking(&workspace_root)?;
+    let state = load_tracking(&workspace_root)?;
 
     if state.bookmarks.is_empty() {
         eprintln!("{}", "No bookmarks currently tracked".muted());
@@ -83,6 +84,11 @@ pub async fn run_untrack(path: &Path, bookmarks: &[String], options: UntrackOpti
         return Ok(());
     }
 
+    // Re-read under the lock so concurrent ryu runs don't lose updates
+    let _lock = lock_state(&workspace_root)?;
+    let before = MetadataSnapshot::load(&workspace_root)?;
+    let mut state = before.tracking.clone();
+
     // Untrack the bookmarks
     let mut untracked_names = Vec::new();
     let mut pr_notes = Vec::new();
@@ -98,6 +104,9 @@ pub async fn run_untrack(path: &Path, bookmarks: &[String], options: UntrackOpti
 
     // Save state
     save_tracking(&workspace_root, &state)?;
+    if !untracked_names.is_empty() {
+        record_change(&workspace, before, None);
+    }
 
     // Print summary
     if untracked_names.len() == 1 {
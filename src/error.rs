@@ -56,6 +56,18 @@ pub enum Error {
     #[error("git operation failed: {0}")]
     Git(String),
 
+    /// Remote bookmark has commits that a push would overwrite
+    #[error("{0}")]
+    RemoteDiverged(String),
+
+    /// Stack has conflicted bookmarks, conflicted commits, or divergent changes
+    #[error("cannot submit the stack until these are fixed:\n{0}")]
+    NotSubmittable(String),
+
+    /// Bookmark contains commits jj would refuse to push
+    #[error("refusing to push commits jj would reject:\n{0}")]
+    UnpushableCommits(String),
+
     /// Invalid configuration
     #[error("invalid configuration: {0}")]
     Config(String),
@@ -84,6 +96,17 @@ pub enum Error {
     #[error("Azure DevOps API error: {0}")]
     AzureDevOpsApi(String),
 
+    /// Platform API failure that may succeed if retried (rate limit, server error)
+    #[error("{message}")]
+    Transient {
+        /// Human-readable description
+        message: String,
+        /// Whether the platform reported a rate limit
+        rate_limited: bool,
+        /// How long the platform asked us to wait, if it said
+        retry_after: Option<std::time::Duration>,
+    },
+
     /// Platform API error (generic)
     #[error("platform error: {0}")]
     Platform(String),
@@ -20,10 +20,27 @@ use tracing::debug;
 /// - `stack: Some(...)` if there are bookmarked commits between trunk and @
 /// - `stack: None` if working copy is at trunk or no bookmarks exist
 pub fn build_change_graph(workspace: &JjWorkspace) -> Result<ChangeGraph> {
-    debug!("Building change graph from trunk to working copy...");
+    build_change_graph_to(workspace, "@")
+}
+
+/// Build a change graph for another workspace's working copy
+///
+/// Same as [`build_change_graph`], but the stack runs from trunk to the
+/// working-copy commit of `workspace_name` (which may be this workspace).
+pub fn build_workspace_change_graph(
+    workspace: &JjWorkspace,
+    workspace_name: &str,
+) -> Result<ChangeGraph> {
+    let escaped = workspace_name.replace('\\', "\\\\").replace('"', "\\\"");
+    build_change_graph_to(workspace, &format!("\"{escaped}\"@"))
+}
+
+/// Build a change graph for the stack from trunk to `head` (a revset)
+fn build_change_graph_to(workspace: &JjWorkspace, head: &str) -> Result<ChangeGraph> {
+    debug!("Building change graph from trunk to {head}...");
 
-    // Query trunk()..@ to get all commits between trunk and working copy
-    let changes = workspace.resolve_revset("trunk()..@")?;
+    // Query trunk()..head to get all commits between trunk and the working copy
+    let changes = workspace.resolve_revset(&format!("trunk()..{head}"))?;
 
     if changes.is_empty() {
         debug!("Working copy is at trunk, no stack to build");
@@ -149,6 +166,8 @@ mod tests {
             is_working_copy: false,
             authored_at: Utc::now(),
             committed_at: Utc::now(),
+            has_conflicts: false,
+            is_divergent: false,
         }
     }
 
@@ -159,6 +178,9 @@ mod tests {
             change_id: change_id.to_string(),
             has_remote: false,
             is_synced: false,
+            is_conflicted: false,
+            has_conflicts: false,
+            is_divergent: false,
         }
     }
 
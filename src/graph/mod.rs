@@ -4,4 +4,4 @@
 
 mod builder;
 
-pub use builder::build_change_graph;
+pub use builder::{build_change_graph, build_workspace_change_graph};
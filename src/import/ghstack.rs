@@ -0,0 +1,38 @@
+//! ghstack branch conventions
+//!
+//! ghstack pushes each commit as three branches, `gh/<user>/<n>/base`,
+//! `gh/<user>/<n>/head` (the PR's head) and `gh/<user>/<n>/orig` (the
+//! original commit), and adds a `Pull Request resolved: <url>` trailer.
+
+/// Bookmark pattern fetching every ghstack branch
+pub const GHSTACK_FETCH_PATTERN: &str = "glob:gh/*";
+
+/// PR head branch for a ghstack `orig` branch name
+pub fn ghstack_head_branch(orig: &str) -> Option<String> {
+    let rest = orig.strip_prefix("gh/")?.strip_suffix("/orig")?;
+    let (user, number) = rest.rsplit_once('/')?;
+    if user.is_empty() || number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("gh/{user}/{number}/head"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_branch_for_orig() {
+        assert_eq!(
+            ghstack_head_branch("gh/alice/12/orig"),
+            Some("gh/alice/12/head".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ignores_other_branches() {
+        assert_eq!(ghstack_head_branch("gh/alice/12/head"), None);
+        assert_eq!(ghstack_head_branch("gh/alice/abc/orig"), None);
+        assert_eq!(ghstack_head_branch("feature/orig"), None);
+    }
+}
@@ -0,0 +1,49 @@
+//! Graphite metadata
+//!
+//! Graphite records each branch's parent and PR as JSON in a blob referenced
+//! by `refs/branch-metadata/<branch>`; `.git/.graphite_repo_config` marks a
+//! repo Graphite was set up in.
+
+use serde::Deserialize;
+
+/// Prefix of the refs holding Graphite's per-branch metadata
+pub const GRAPHITE_METADATA_REF_PREFIX: &str = "refs/branch-metadata/";
+
+/// Graphite's repo config file, relative to the git directory
+pub const GRAPHITE_REPO_CONFIG: &str = ".graphite_repo_config";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BranchMetadata {
+    pr_info: Option<PrInfo>,
+}
+
+#[derive(Deserialize)]
+struct PrInfo {
+    number: Option<u64>,
+}
+
+/// PR number recorded in a branch's metadata blob
+pub fn graphite_pr_number(metadata: &[u8]) -> Option<u64> {
+    serde_json::from_slice::<BranchMetadata>(metadata)
+        .ok()?
+        .pr_info?
+        .number
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pr_number_from_metadata() {
+        let metadata = br#"{"parentBranchName":"main","parentBranchRevision":"abc123","prInfo":{"number":42,"base":"main","title":"Add login","state":"OPEN","isDraft":false}}"#;
+        assert_eq!(graphite_pr_number(metadata), Some(42));
+    }
+
+    #[test]
+    fn test_metadata_without_pr() {
+        assert_eq!(graphite_pr_number(br#"{"parentBranchName":"main"}"#), None);
+        assert_eq!(graphite_pr_number(b"not json"), None);
+    }
+}
@@ -0,0 +1,268 @@
+//! Import stacks managed by other stacked-PR tools
+//!
+//! Each importer walks the commits of the current stack (`trunk()..@`) and
+//! finds the PR each one already has under another tool's conventions. The
+//! caller then points a bookmark named after the PR's head branch at the
+//! commit, so ryu keeps updating the existing PR instead of opening a new one.
+
+mod ghstack;
+mod graphite;
+mod spr;
+
+pub use ghstack::{GHSTACK_FETCH_PATTERN, ghstack_head_branch};
+pub use graphite::{GRAPHITE_METADATA_REF_PREFIX, GRAPHITE_REPO_CONFIG, graphite_pr_number};
+pub use spr::{spr_branch_name, spr_commit_id};
+
+use crate::error::{Error, Result};
+use crate::platform::{PlatformService, parse_pr_reference};
+use crate::repo::JjWorkspace;
+use crate::types::{LogEntry, PrState, PullRequest};
+use std::fmt;
+
+/// Tool whose stack metadata to import
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    /// Graphite's `refs/branch-metadata/*` refs
+    Graphite,
+    /// ghstack's `gh/<user>/<n>/{head,base,orig}` branches
+    Ghstack,
+    /// spr's commit trailers
+    Spr,
+}
+
+impl ImportSource {
+    /// Parse a tool name (`graphite`, `ghstack` or `spr`)
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "graphite" | "gt" => Ok(Self::Graphite),
+            "ghstack" => Ok(Self::Ghstack),
+            "spr" => Ok(Self::Spr),
+            "git-branchless" | "branchless" => Err(Error::InvalidArgument(
+                "git-branchless keeps no PR metadata to import; its branches are already jj \
+                 bookmarks, so run 'ryu track' on them instead"
+                    .to_string(),
+            )),
+            other => Err(Error::InvalidArgument(format!(
+                "Unknown import source '{other}': expected graphite, ghstack or spr"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ImportSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Graphite => write!(f, "graphite"),
+            Self::Ghstack => write!(f, "ghstack"),
+            Self::Spr => write!(f, "spr"),
+        }
+    }
+}
+
+/// How to find the PR another tool opened for a commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrLocator {
+    /// PR number recorded by the tool
+    Number(u64),
+    /// Head branch the tool pushed the commit to
+    Branch(String),
+}
+
+impl fmt::Display for PrLocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "#{number}"),
+            Self::Branch(branch) => write!(f, "{branch}"),
+        }
+    }
+}
+
+/// A stack commit that already has a PR under another tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportCandidate {
+    /// Commit the PR is for (hex)
+    pub commit_id: String,
+    /// Where to find its PR
+    pub pr: PrLocator,
+}
+
+/// A stack commit matched to its existing PR
+#[derive(Debug, Clone)]
+pub struct ImportedPr {
+    /// Commit the bookmark should point at (hex)
+    pub commit_id: String,
+    /// The existing PR; its head branch becomes the bookmark name
+    pub pr: PullRequest,
+}
+
+/// Find the commits of the current stack that have PRs under `source`
+///
+/// Returns candidates ordered root to leaf. ghstack's `orig` branches must
+/// have been fetched from `remote` first (see [`GHSTACK_FETCH_PATTERN`]).
+pub fn find_import_candidates(
+    workspace: &JjWorkspace,
+    source: ImportSource,
+    remote: &str,
+) -> Result<Vec<ImportCandidate>> {
+    let mut commits = workspace.resolve_revset("trunk()..@")?;
+    commits.reverse();
+
+    if source == ImportSource::Graphite && !workspace.git_dir()?.join(GRAPHITE_REPO_CONFIG).exists()
+    {
+        return Err(Error::Config(format!(
+            "no {GRAPHITE_REPO_CONFIG} in the git directory; was this repo set up with Graphite?"
+        )));
+    }
+    let trunk = workspace.default_branch()?;
+
+    let mut candidates = Vec::new();
+    for commit in commits {
+        let pr = match source {
+            ImportSource::Graphite => graphite_locator(workspace, &commit)?,
+            ImportSource::Ghstack => ghstack_locator(workspace, &commit, remote)?,
+            ImportSource::Spr => spr_locator(workspace, &commit, &trunk)?,
+        };
+        if let Some(pr) = pr {
+            candidates.push(ImportCandidate {
+                commit_id: commit.commit_id,
+                pr,
+            });
+        }
+    }
+
+    Ok(candidates)
+}
+
+/// PR recorded in the Graphite metadata of a bookmark on the commit
+fn graphite_locator(workspace: &JjWorkspace, commit: &LogEntry) -> Result<Option<PrLocator>> {
+    for name in &commit.local_bookmarks {
+        let metadata = workspace.git_ref_blob(&format!("{GRAPHITE_METADATA_REF_PREFIX}{name}"))?;
+        if let Some(number) = metadata.as_deref().and_then(graphite_pr_number) {
+            return Ok(Some(PrLocator::Number(number)));
+        }
+    }
+    Ok(None)
+}
+
+/// ghstack head branch for the commit's `orig` branch, or else the PR in its
+/// trailer
+fn ghstack_locator(
+    workspace: &JjWorkspace,
+    commit: &LogEntry,
+    remote: &str,
+) -> Result<Option<PrLocator>> {
+    let suffix = format!("@{remote}");
+    if let Some(head) = commit
+        .remote_bookmarks
+        .iter()
+        .filter_map(|name| name.strip_suffix(&suffix))
+        .find_map(ghstack_head_branch)
+    {
+        return Ok(Some(PrLocator::Branch(head)));
+    }
+
+    let description = workspace.commit_description(&commit.commit_id)?;
+    Ok(pr_trailer(&description, "Pull Request resolved").map(PrLocator::Number))
+}
+
+/// PR in the commit's spr trailers
+fn spr_locator(
+    workspace: &JjWorkspace,
+    commit: &LogEntry,
+    trunk: &str,
+) -> Result<Option<PrLocator>> {
+    let description = workspace.commit_description(&commit.commit_id)?;
+    if let Some(number) = pr_trailer(&description, "Pull Request") {
+        return Ok(Some(PrLocator::Number(number)));
+    }
+    Ok(spr_commit_id(&description).map(|id| PrLocator::Branch(spr_branch_name(trunk, id))))
+}
+
+/// Look up the open PR of each candidate
+///
+/// Returns the matched PRs in candidate order, and the candidates without an
+/// open PR.
+pub async fn resolve_import_prs(
+    platform: &dyn PlatformService,
+    candidates: Vec<ImportCandidate>,
+) -> Result<(Vec<ImportedPr>, Vec<ImportCandidate>)> {
+    let mut imported = Vec::new();
+    let mut unmatched = Vec::new();
+    for candidate in candidates {
+        let pr = match &candidate.pr {
+            PrLocator::Number(number) => platform.get_pr(*number).await?,
+            PrLocator::Branch(branch) => platform.find_existing_pr(branch).await?,
+        };
+        match pr.filter(|pr| pr.state == PrState::Open) {
+            Some(pr) => imported.push(ImportedPr {
+                commit_id: candidate.commit_id,
+                pr,
+            }),
+            None => unmatched.push(candidate),
+        }
+    }
+    Ok((imported, unmatched))
+}
+
+/// Value of a `Key: value` trailer in a commit description
+///
+/// Only the last paragraph is searched, as git does; keys match
+/// case-insensitively.
+fn trailer<'a>(description: &'a str, key: &str) -> Option<&'a str> {
+    let description = description.trim_end();
+    let last_paragraph = description
+        .rfind("\n\n")
+        .map_or(description, |i| &description[i + 2..]);
+    last_paragraph.lines().rev().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim().eq_ignore_ascii_case(key).then(|| value.trim())
+    })
+}
+
+/// PR number from a trailer holding the PR's URL
+fn pr_trailer(description: &str, key: &str) -> Option<u64> {
+    trailer(description, key).and_then(|url| parse_pr_reference(url).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailer_reads_last_paragraph() {
+        let description = "Add login\n\nSome: prose here\n\ncommit-id: 1a2b3c4d\nPull Request: https://github.com/o/r/pull/7\n";
+        assert_eq!(trailer(description, "commit-id"), Some("1a2b3c4d"));
+        assert_eq!(
+            trailer(description, "pull request"),
+            Some("https://github.com/o/r/pull/7")
+        );
+        assert_eq!(trailer(description, "Some"), None);
+    }
+
+    #[test]
+    fn test_pr_trailer() {
+        let description = "Fix bug\n\nPull Request resolved: https://github.com/o/r/pull/12";
+        assert_eq!(pr_trailer(description, "Pull Request resolved"), Some(12));
+        assert_eq!(pr_trailer("Fix bug", "Pull Request resolved"), None);
+    }
+
+    #[test]
+    fn test_parse_import_source() {
+        assert_eq!(
+            ImportSource::parse("graphite").unwrap(),
+            ImportSource::Graphite
+        );
+        assert_eq!(ImportSource::parse("gt").unwrap(), ImportSource::Graphite);
+        assert_eq!(
+            ImportSource::parse("ghstack").unwrap(),
+            ImportSource::Ghstack
+        );
+        assert_eq!(ImportSource::parse("spr").unwrap(), ImportSource::Spr);
+        // Not supported on purpose: there is no PR metadata to import
+        assert!(matches!(
+            ImportSource::parse("git-branchless"),
+            Err(Error::InvalidArgument(msg)) if msg.contains("ryu track")
+        ));
+        assert!(ImportSource::parse("sapling").is_err());
+    }
+}
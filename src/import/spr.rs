@@ -0,0 +1,36 @@
+//! spr commit trailers
+//!
+//! ejoffe/spr tags each commit with a `commit-id: <hex>` trailer and pushes
+//! it to `spr/<trunk>/<commit-id>`; getcord/spr records the PR directly in a
+//! `Pull Request: <url>` trailer.
+
+use super::trailer;
+
+/// spr's commit ID from a commit description
+pub fn spr_commit_id(description: &str) -> Option<&str> {
+    trailer(description, "commit-id")
+        .filter(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Branch spr pushes a commit to
+pub fn spr_branch_name(trunk: &str, commit_id: &str) -> String {
+    format!("spr/{trunk}/{commit_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_id_trailer() {
+        let description = "Add login\n\nLonger text.\n\ncommit-id: 9f8e7d6c";
+        assert_eq!(spr_commit_id(description), Some("9f8e7d6c"));
+        assert_eq!(spr_branch_name("main", "9f8e7d6c"), "spr/main/9f8e7d6c");
+    }
+
+    #[test]
+    fn test_missing_or_invalid_commit_id() {
+        assert_eq!(spr_commit_id("Add login"), None);
+        assert_eq!(spr_commit_id("Add login\n\ncommit-id: not-hex"), None);
+    }
+}
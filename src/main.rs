@@ -4,6 +4,7 @@
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use jj_ryu::submit::DEFAULT_CONCURRENCY;
 use jj_ryu::types::Platform;
 use std::path::PathBuf;
 
@@ -72,8 +73,30 @@ enum Commands {
         /// Submit all bookmarks in `trunk()`..@ (ignore tracking)
         #[arg(long, short)]
         all: bool,
+
+        /// Resume an interrupted submission from the first incomplete step
+        #[arg(
+            long,
+            conflicts_with_all = ["bookmark", "upto", "only", "stack", "update_only", "draft", "publish", "select", "remote", "all", "edit"]
+        )]
+        resume: bool,
+
+        /// Maximum number of platform requests to run concurrently
+        #[arg(long, default_value_t = DEFAULT_CONCURRENCY)]
+        concurrency: usize,
+
+        /// Push all bookmarks atomically: either every ref updates or none does
+        #[arg(long)]
+        atomic: bool,
+
+        /// Edit the titles and descriptions of new PRs in your editor first
+        #[arg(long, short = 'e', conflicts_with_all = ["dry_run", "update_only"])]
+        edit: bool,
     },
 
+    /// Show the current stack (same as running ryu without a subcommand)
+    Status,
+
     /// Sync current stack with remote
     Sync {
         /// Dry run - show what would be done without making changes
@@ -91,6 +114,14 @@ enum Commands {
         /// Sync all bookmarks in `trunk()`..@ (ignore tracking)
         #[arg(long, short)]
         all: bool,
+
+        /// Maximum number of platform requests to run concurrently
+        #[arg(long, default_value_t = DEFAULT_CONCURRENCY)]
+        concurrency: usize,
+
+        /// Fetch every branch from the remote, not just the stack's
+        #[arg(long)]
+        full_fetch: bool,
     },
 
     /// Authentication management
@@ -115,6 +146,52 @@ enum Commands {
         /// Associate with specific remote
         #[arg(long, short)]
         remote: Option<String>,
+
+        /// Track only in this workspace (default: every workspace of the repo)
+        #[arg(long)]
+        workspace: bool,
+    },
+
+    /// Track an existing stack from one of its PRs (e.g. a teammate's)
+    Adopt {
+        /// PR number or URL; without ryu's stack comment, pass the top PR
+        pr: String,
+
+        /// Remote the stack's branches live on
+        #[arg(long, short)]
+        remote: Option<String>,
+
+        /// Track only in this workspace (default: every workspace of the repo)
+        #[arg(long)]
+        workspace: bool,
+    },
+
+    /// Check out a PR and the PRs below it, on a new change on top
+    Checkout {
+        /// PR number or URL
+        pr: String,
+
+        /// Remote the stack's branches live on
+        #[arg(long, short)]
+        remote: Option<String>,
+
+        /// Track only in this workspace (default: every workspace of the repo)
+        #[arg(long)]
+        workspace: bool,
+    },
+
+    /// Take over the current stack's PRs from Graphite, ghstack or spr
+    Import {
+        /// Tool the PRs were created with: graphite, ghstack or spr
+        source: String,
+
+        /// Remote the PRs' branches live on
+        #[arg(long, short)]
+        remote: Option<String>,
+
+        /// Track only in this workspace (default: every workspace of the repo)
+        #[arg(long)]
+        workspace: bool,
     },
 
     /// Stop tracking bookmarks
@@ -126,6 +203,28 @@ enum Commands {
         #[arg(long, short)]
         all: bool,
     },
+
+    /// Show recorded changes to ryu metadata, by jj operation
+    History {
+        /// Show tracked bookmarks and PRs as of this jj operation (e.g. @-, abc123)
+        #[arg(long)]
+        at: Option<String>,
+
+        /// Make the metadata as of --at current again
+        #[arg(long, requires = "at")]
+        restore: bool,
+    },
+
+    /// Undo the last ryu change, reverting what it did on the remote
+    Undo {
+        /// Don't ask before changing PRs on the remote
+        #[arg(long, short)]
+        yes: bool,
+
+        /// Also delete branches the undone submission pushed for the first time
+        #[arg(long)]
+        delete_branches: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -157,12 +256,13 @@ enum AuthAction {
 }
 
 #[tokio::main]
+#[allow(clippy::too_many_lines)]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
     let path = cli.path.unwrap_or_else(|| PathBuf::from("."));
 
     match cli.command {
-        None => {
+        None | Some(Commands::Status) => {
             // Default: interactive mode
             cli::run_analyze(&path).await?;
         }
@@ -179,6 +279,10 @@ async fn main() -> Result<()> {
             select,
             remote,
             all,
+            resume,
+            concurrency,
+            atomic,
+            edit,
         }) => {
             // Determine scope from mutually exclusive flags (enforced by clap arg groups)
             #[allow(clippy::option_if_let_else)]
@@ -206,6 +310,10 @@ async fn main() -> Result<()> {
                     publish,
                     select,
                     all,
+                    resume,
+                    concurrency,
+                    atomic,
+                    edit,
                 },
             )
             .await?;
@@ -215,6 +323,8 @@ async fn main() -> Result<()> {
             confirm,
             remote,
             all,
+            concurrency,
+            full_fetch,
         }) => {
             cli::run_sync(
                 &path,
@@ -223,6 +333,8 @@ async fn main() -> Result<()> {
                     dry_run,
                     confirm,
                     all,
+                    concurrency,
+                    full_fetch,
                 },
             )
             .await?;
@@ -255,12 +367,60 @@ async fn main() -> Result<()> {
             all,
             force,
             remote,
+            workspace,
         }) => {
-            cli::run_track(&path, &bookmarks, cli::TrackOptions { all, force, remote }).await?;
+            cli::run_track(
+                &path,
+                &bookmarks,
+                cli::TrackOptions {
+                    all,
+                    force,
+                    remote,
+                    workspace,
+                },
+            )
+            .await?;
+        }
+        Some(Commands::Adopt {
+            pr,
+            remote,
+            workspace,
+        }) => {
+            cli::run_adopt(&path, &pr, cli::AdoptOptions { remote, workspace }).await?;
+        }
+        Some(Commands::Checkout {
+            pr,
+            remote,
+            workspace,
+        }) => {
+            cli::run_checkout(&path, &pr, cli::CheckoutOptions { remote, workspace }).await?;
+        }
+        Some(Commands::Import {
+            source,
+            remote,
+            workspace,
+        }) => {
+            cli::run_import(&path, &source, cli::ImportOptions { remote, workspace }).await?;
         }
         Some(Commands::Untrack { bookmarks, all }) => {
             cli::run_untrack(&path, &bookmarks, cli::UntrackOptions { all }).await?;
         }
+        Some(Commands::History { at, restore }) => {
+            cli::run_history(&path, cli::HistoryOptions { at, restore }).await?;
+        }
+        Some(Commands::Undo {
+            yes,
+            delete_branches,
+        }) => {
+            cli::run_undo(
+                &path,
+                cli::UndoOptions {
+                    yes,
+                    delete_branches,
+                },
+            )
+            .await?;
+        }
     }
 
     Ok(())
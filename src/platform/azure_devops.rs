@@ -2,7 +2,8 @@
 
 use crate::error::{Error, Result};
 use crate::platform::PlatformService;
-use crate::types::{Platform, PlatformConfig, PrComment, PullRequest};
+use crate::platform::retry::CheckStatus;
+use crate::types::{Platform, PlatformConfig, PrComment, PrState, PullRequest};
 use async_trait::async_trait;
 use base64::Engine;
 use reqwest::Client;
@@ -31,6 +32,8 @@ struct PullRequestResponse {
     title: String,
     #[serde(default)]
     is_draft: bool,
+    #[serde(default)]
+    status: String,
     repository: Repository,
 }
 
@@ -68,10 +71,24 @@ impl PullRequestResponse {
             title: self.title,
             node_id: None,
             is_draft: self.is_draft,
+            // "active", "abandoned" or "completed"
+            state: match self.status.as_str() {
+                "completed" => PrState::Merged,
+                "abandoned" => PrState::Closed,
+                _ => PrState::Open,
+            },
+            comments: None,
+            checks: None,
         }
     }
 }
 
+#[derive(Deserialize)]
+struct PullRequestDescription {
+    #[serde(default)]
+    description: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct PullRequestListResponse {
     value: Vec<PullRequestResponse>,
@@ -103,6 +120,7 @@ struct CreatePrPayload {
     source_ref_name: String,
     target_ref_name: String,
     title: String,
+    description: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     is_draft: Option<bool>,
 }
@@ -176,6 +194,35 @@ impl AzureDevOpsService {
         )
     }
 
+    /// Find the thread containing a comment (comment URLs need both ids)
+    async fn comment_thread_id(&self, pr_number: u64, comment_id: u64) -> Result<u64> {
+        let threads_url = self.api_url(&format!(
+            "/git/repositories/{}/pullrequests/{}/threads",
+            urlencoding::encode(&self.config.repo),
+            pr_number
+        ));
+
+        let response: ThreadListResponse = self
+            .client
+            .get(&threads_url)
+            .header("Authorization", self.auth_header())
+            .query(&[("api-version", "7.1-preview")])
+            .send()
+            .await?
+            .check_status(Error::AzureDevOpsApi)?
+            .json()
+            .await?;
+
+        response
+            .value
+            .iter()
+            .find(|thread| thread.comments.iter().any(|c| c.id == comment_id))
+            .map(|thread| thread.id)
+            .ok_or_else(|| {
+                Error::AzureDevOpsApi(format!("comment {comment_id} not found in any thread"))
+            })
+    }
+
     fn auth_header(&self) -> String {
         let auth = format!(":{}", self.token);
         let encoded = base64::engine::general_purpose::STANDARD.encode(auth);
@@ -213,8 +260,7 @@ impl PlatformService for AzureDevOpsService {
             ])
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::AzureDevOpsApi(e.to_string()))?
+            .check_status(Error::AzureDevOpsApi)?
             .json()
             .await?;
 
@@ -232,12 +278,49 @@ impl PlatformService for AzureDevOpsService {
         Ok(result)
     }
 
+    async fn get_pr(&self, pr_number: u64) -> Result<Option<PullRequest>> {
+        debug!(pr_id = pr_number, "fetching PR");
+        let url = self.api_url(&format!(
+            "/git/repositories/{}/pullrequests/{}",
+            urlencoding::encode(&self.config.repo),
+            pr_number
+        ));
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", self.auth_header())
+            .query(&[("api-version", "7.1-preview")])
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            debug!(pr_id = pr_number, "PR not found");
+            return Ok(None);
+        }
+
+        let pr: PullRequestResponse = response.check_status(Error::AzureDevOpsApi)?.json().await?;
+
+        Ok(Some(pr.into_pull_request()))
+    }
+
     async fn create_pr_with_options(
         &self,
         head: &str,
         base: &str,
         title: &str,
         draft: bool,
+    ) -> Result<PullRequest> {
+        self.create_pr_with_body(head, base, title, "", draft).await
+    }
+
+    async fn create_pr_with_body(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
     ) -> Result<PullRequest> {
         debug!(head, base, draft, "creating PR");
         let url = self.api_url(&format!(
@@ -249,6 +332,7 @@ impl PlatformService for AzureDevOpsService {
             source_ref_name: Self::branch_ref(head),
             target_ref_name: Self::branch_ref(base),
             title: title.to_string(),
+            description: body.to_string(),
             is_draft: if draft { Some(true) } else { None },
         };
 
@@ -261,8 +345,7 @@ impl PlatformService for AzureDevOpsService {
             .json(&payload)
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::AzureDevOpsApi(e.to_string()))?
+            .check_status(Error::AzureDevOpsApi)?
             .json()
             .await?;
 
@@ -288,8 +371,7 @@ impl PlatformService for AzureDevOpsService {
             .json(&serde_json::json!({ "targetRefName": Self::branch_ref(new_base) }))
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::AzureDevOpsApi(e.to_string()))?
+            .check_status(Error::AzureDevOpsApi)?
             .json()
             .await?;
 
@@ -314,8 +396,7 @@ impl PlatformService for AzureDevOpsService {
             .json(&serde_json::json!({ "isDraft": false }))
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::AzureDevOpsApi(e.to_string()))?
+            .check_status(Error::AzureDevOpsApi)?
             .json()
             .await?;
 
@@ -323,6 +404,72 @@ impl PlatformService for AzureDevOpsService {
         Ok(pr.into_pull_request())
     }
 
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        debug!(pr_id = pr_number, "abandoning PR");
+        let url = self.api_url(&format!(
+            "/git/repositories/{}/pullrequests/{}",
+            urlencoding::encode(&self.config.repo),
+            pr_number
+        ));
+
+        self.client
+            .patch(&url)
+            .header("Authorization", self.auth_header())
+            .header("Content-Type", "application/json")
+            .query(&[("api-version", "7.1-preview")])
+            .json(&serde_json::json!({ "status": "abandoned" }))
+            .send()
+            .await?
+            .check_status(Error::AzureDevOpsApi)?;
+
+        debug!(pr_id = pr_number, "abandoned PR");
+        Ok(())
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        debug!(pr_id = pr_number, "getting PR description");
+        let url = self.api_url(&format!(
+            "/git/repositories/{}/pullrequests/{}",
+            urlencoding::encode(&self.config.repo),
+            pr_number
+        ));
+
+        let pr: PullRequestDescription = self
+            .client
+            .get(&url)
+            .header("Authorization", self.auth_header())
+            .query(&[("api-version", "7.1-preview")])
+            .send()
+            .await?
+            .check_status(Error::AzureDevOpsApi)?
+            .json()
+            .await?;
+
+        Ok(pr.description.unwrap_or_default())
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(pr_id = pr_number, "updating PR description");
+        let url = self.api_url(&format!(
+            "/git/repositories/{}/pullrequests/{}",
+            urlencoding::encode(&self.config.repo),
+            pr_number
+        ));
+
+        self.client
+            .patch(&url)
+            .header("Authorization", self.auth_header())
+            .header("Content-Type", "application/json")
+            .query(&[("api-version", "7.1-preview")])
+            .json(&serde_json::json!({ "description": body }))
+            .send()
+            .await?
+            .check_status(Error::AzureDevOpsApi)?;
+
+        debug!(pr_id = pr_number, "updated PR description");
+        Ok(())
+    }
+
     async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
         debug!(pr_id = pr_number, "listing PR comments");
         let url = self.api_url(&format!(
@@ -338,8 +485,7 @@ impl PlatformService for AzureDevOpsService {
             .query(&[("api-version", "7.1-preview")])
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::AzureDevOpsApi(e.to_string()))?
+            .check_status(Error::AzureDevOpsApi)?
             .json()
             .await?;
 
@@ -395,8 +541,7 @@ impl PlatformService for AzureDevOpsService {
             .json(&payload)
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::AzureDevOpsApi(e.to_string()))?;
+            .check_status(Error::AzureDevOpsApi)?;
 
         debug!(pr_id = pr_number, "created PR comment");
         Ok(())
@@ -406,34 +551,33 @@ impl PlatformService for AzureDevOpsService {
         debug!(pr_id = pr_number, comment_id, "updating PR comment");
 
         // Azure DevOps requires thread ID to update a comment
-        // We need to find which thread contains this comment
-        let threads_url = self.api_url(&format!(
-            "/git/repositories/{}/pullrequests/{}/threads",
+        let thread_id = self.comment_thread_id(pr_number, comment_id).await?;
+
+        let url = self.api_url(&format!(
+            "/git/repositories/{}/pullrequests/{}/threads/{}/comments/{}",
             urlencoding::encode(&self.config.repo),
-            pr_number
+            pr_number,
+            thread_id,
+            comment_id
         ));
 
-        let response: ThreadListResponse = self
-            .client
-            .get(&threads_url)
+        self.client
+            .patch(&url)
             .header("Authorization", self.auth_header())
+            .header("Content-Type", "application/json")
             .query(&[("api-version", "7.1-preview")])
+            .json(&serde_json::json!({ "content": body }))
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::AzureDevOpsApi(e.to_string()))?
-            .json()
-            .await?;
+            .check_status(Error::AzureDevOpsApi)?;
 
-        // Find the thread containing this comment
-        let thread_id = response
-            .value
-            .iter()
-            .find(|thread| thread.comments.iter().any(|c| c.id == comment_id))
-            .map(|thread| thread.id)
-            .ok_or_else(|| {
-                Error::AzureDevOpsApi(format!("comment {comment_id} not found in any thread"))
-            })?;
+        debug!(pr_id = pr_number, comment_id, "updated PR comment");
+        Ok(())
+    }
+
+    async fn delete_pr_comment(&self, pr_number: u64, comment_id: u64) -> Result<()> {
+        debug!(pr_id = pr_number, comment_id, "deleting PR comment");
+        let thread_id = self.comment_thread_id(pr_number, comment_id).await?;
 
         let url = self.api_url(&format!(
             "/git/repositories/{}/pullrequests/{}/threads/{}/comments/{}",
@@ -444,17 +588,14 @@ impl PlatformService for AzureDevOpsService {
         ));
 
         self.client
-            .patch(&url)
+            .delete(&url)
             .header("Authorization", self.auth_header())
-            .header("Content-Type", "application/json")
             .query(&[("api-version", "7.1-preview")])
-            .json(&serde_json::json!({ "content": body }))
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::AzureDevOpsApi(e.to_string()))?;
+            .check_status(Error::AzureDevOpsApi)?;
 
-        debug!(pr_id = pr_number, comment_id, "updated PR comment");
+        debug!(pr_id = pr_number, comment_id, "deleted PR comment");
         Ok(())
     }
 
@@ -23,6 +23,11 @@ static RE_AZURE_SSH: LazyLock<Regex> =
 static RE_AZURE_HTTPS: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"https?://(?:[^@]+@)?dev\.azure\.com/([^/]+)/([^/]+)/_git/(.+?)(?:\.git)?$").unwrap());
 
+/// Regex for PR/MR web URLs on GitHub, GitLab and Azure DevOps
+static RE_PR_URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^https?://.*/(?:pull|pulls|merge_requests|pullrequest)/(\d+)(?:[/?#].*)?$").unwrap()
+});
+
 /// Detect platform (GitHub or GitLab) from a remote URL
 pub fn detect_platform(url: &str) -> Option<Platform> {
     let gh_host = env::var("GH_HOST").ok();
@@ -120,6 +125,24 @@ pub fn parse_repo_info(url: &str) -> Result<PlatformConfig> {
     })
 }
 
+/// Parse a PR/MR number from user input
+///
+/// Accepts a bare number (`123`), a `#123` / `!123` reference, or the PR's web
+/// URL on any supported platform.
+pub fn parse_pr_reference(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let number = RE_PR_URL
+        .captures(input)
+        .and_then(|c| c.get(1))
+        .map_or_else(|| input.trim_start_matches(['#', '!']), |m| m.as_str());
+
+    number
+        .parse()
+        .ok()
+        .filter(|&n| n > 0)
+        .ok_or_else(|| Error::Parse(format!("not a PR number or URL: {input}")))
+}
+
 fn parse_azure_devops_url(url: &str) -> Result<PlatformConfig> {
     // Try SSH format first: git@ssh.dev.azure.com:v3/{org}/{project}/{repo}
     if let Some(caps) = RE_AZURE_SSH.captures(url) {
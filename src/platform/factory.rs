@@ -4,13 +4,40 @@
 
 use crate::auth::{get_azure_devops_auth, get_github_auth, get_gitlab_auth};
 use crate::error::Result;
-use crate::platform::{AzureDevOpsService, GitHubService, GitLabService, PlatformService};
+use crate::platform::{
+    AzureDevOpsService, GitHubService, GitLabService, PlatformService, RetryPolicy,
+    RetryingPlatformService,
+};
+use crate::submit::ProgressCallback;
 use crate::types::{Platform, PlatformConfig};
+use std::sync::Arc;
 
 /// Create a platform service from configuration
 ///
 /// Handles authentication and client construction for both GitHub and GitLab.
+/// The service retries rate-limited and transient failures with the default
+/// [`RetryPolicy`].
 pub async fn create_platform_service(config: &PlatformConfig) -> Result<Box<dyn PlatformService>> {
+    let inner = create_inner_service(config).await?;
+    Ok(Box::new(RetryingPlatformService::new(
+        inner,
+        RetryPolicy::default(),
+    )))
+}
+
+/// Create a platform service that reports retries through `progress`
+pub async fn create_platform_service_with_progress(
+    config: &PlatformConfig,
+    progress: Arc<dyn ProgressCallback>,
+) -> Result<Box<dyn PlatformService>> {
+    let inner = create_inner_service(config).await?;
+    Ok(Box::new(
+        RetryingPlatformService::new(inner, RetryPolicy::default()).with_progress(progress),
+    ))
+}
+
+/// Create the platform-specific service, without retries
+async fn create_inner_service(config: &PlatformConfig) -> Result<Box<dyn PlatformService>> {
     match config.platform {
         Platform::GitHub => {
             let auth = get_github_auth().await?;
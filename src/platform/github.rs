@@ -2,13 +2,18 @@
 
 use crate::error::{Error, Result};
 use crate::platform::PlatformService;
-use crate::types::{Platform, PlatformConfig, PrComment, PullRequest};
+use crate::platform::retry::is_transient_octocrab;
+use crate::types::{ChecksState, Platform, PlatformConfig, PrComment, PrState, PullRequest};
 use async_trait::async_trait;
+use chrono::Utc;
 use octocrab::Octocrab;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::Duration;
 use tracing::debug;
 
-// GraphQL response types for publish_pr mutation
+// GraphQL response types for publish_pr mutation and batched PR lookup
 
 #[derive(Deserialize)]
 struct GraphQlResponse<T> {
@@ -43,10 +48,173 @@ struct GraphQlPullRequest {
     title: String,
     id: String,
     is_draft: bool,
+    state: GraphQlPrState,
+    #[serde(default)]
+    head_repository_owner: Option<GraphQlRepositoryOwner>,
+    /// Only requested by the batched lookup
+    #[serde(default)]
+    comments: Option<GraphQlCommentConnection>,
+    /// Only requested by the batched lookup: the head commit
+    #[serde(default)]
+    commits: Option<GraphQlCommitConnection>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum GraphQlPrState {
+    Open,
+    Closed,
+    Merged,
+}
+
+#[derive(Deserialize)]
+struct GraphQlRepositoryOwner {
+    login: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlCommentConnection {
+    page_info: GraphQlPageInfo,
+    nodes: Vec<GraphQlComment>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlPageInfo {
+    has_next_page: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlComment {
+    /// REST comment ID, which the comment endpoints take
+    database_id: Option<u64>,
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct GraphQlCommitConnection {
+    nodes: Vec<GraphQlCommitNode>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlCommitNode {
+    commit: GraphQlCommit,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphQlCommit {
+    /// Absent when the commit has no checks or statuses
+    status_check_rollup: Option<GraphQlStatusCheckRollup>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlStatusCheckRollup {
+    state: GraphQlStatusState,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum GraphQlStatusState {
+    Expected,
+    Pending,
+    Success,
+    Failure,
+    Error,
+}
+
+#[derive(Deserialize)]
+struct RepositoryLookupData {
+    /// One aliased `pullRequests` connection per looked-up branch
+    repository: HashMap<String, PullRequestConnection>,
+}
+
+#[derive(Deserialize)]
+struct PullRequestConnection {
+    nodes: Vec<GraphQlPullRequest>,
+}
+
+/// Fields requested for every pull request in the batched lookup: enough to
+/// plan a submission and write stack comments without further requests
+const PR_FIELDS: &str = "number url baseRefName headRefName title id isDraft state \
+                         headRepositoryOwner { login } \
+                         comments(first: 100) { pageInfo { hasNextPage } nodes { databaseId body } } \
+                         commits(last: 1) { nodes { commit { statusCheckRollup { state } } } }";
+
+/// Maximum number of branches looked up in a single GraphQL query
+const PR_LOOKUP_BATCH_SIZE: usize = 50;
+
+/// Build an aliased query looking up open PRs for `count` head branches.
+///
+/// Branch names are passed as variables `$h0..$hN` (never interpolated), and
+/// each lookup is aliased `pr0..prN` so results map back to their branch.
+fn build_pr_lookup_query(count: usize) -> String {
+    let mut params = String::from("$owner: String!, $repo: String!");
+    let mut fields = String::new();
+    for i in 0..count {
+        let _ = write!(params, ", $h{i}: String!");
+        let _ = write!(
+            fields,
+            " pr{i}: pullRequests(headRefName: $h{i}, states: OPEN, first: 10) \
+             {{ nodes {{ {PR_FIELDS} }} }}"
+        );
+    }
+    format!(
+        "query FindPullRequests({params}) {{ repository(owner: $owner, name: $repo) {{{fields} }} }}"
+    )
+}
+
+/// Map a GraphQL transport error, keeping retryable failures retryable
+fn graphql_error(kind: &str, e: octocrab::Error) -> Error {
+    if is_transient_octocrab(&e) {
+        Error::Octocrab(e)
+    } else {
+        Error::GitHubApi(format!("GraphQL {kind} failed: {e}"))
+    }
+}
+
+/// Extract data from a GraphQL response, surfacing any reported errors
+fn graphql_data<T>(response: GraphQlResponse<T>) -> Result<T> {
+    if let Some(errors) = response.errors {
+        if !errors.is_empty() {
+            let messages: Vec<_> = errors.into_iter().map(|e| e.message).collect();
+            return Err(Error::GitHubApi(format!(
+                "GraphQL error: {}",
+                messages.join(", ")
+            )));
+        }
+    }
+
+    response
+        .data
+        .ok_or_else(|| Error::GitHubApi("No data in GraphQL response".to_string()))
 }
 
 impl From<GraphQlPullRequest> for PullRequest {
     fn from(pr: GraphQlPullRequest) -> Self {
+        // A partial first page would hide a stack comment further down
+        let comments = pr
+            .comments
+            .filter(|connection| !connection.page_info.has_next_page)
+            .map(|connection| {
+                connection
+                    .nodes
+                    .into_iter()
+                    .filter_map(|c| c.database_id.map(|id| PrComment { id, body: c.body }))
+                    .collect()
+            });
+        let checks = pr
+            .commits
+            .and_then(|connection| connection.nodes.into_iter().next())
+            .and_then(|node| node.commit.status_check_rollup)
+            .map(|rollup| match rollup.state {
+                GraphQlStatusState::Expected | GraphQlStatusState::Pending => ChecksState::Pending,
+                GraphQlStatusState::Success => ChecksState::Success,
+                GraphQlStatusState::Failure | GraphQlStatusState::Error => ChecksState::Failure,
+            });
+
         Self {
             number: pr.number,
             html_url: pr.url,
@@ -55,6 +223,13 @@ impl From<GraphQlPullRequest> for PullRequest {
             title: pr.title,
             node_id: Some(pr.id),
             is_draft: pr.is_draft,
+            state: match pr.state {
+                GraphQlPrState::Open => PrState::Open,
+                GraphQlPrState::Closed => PrState::Closed,
+                GraphQlPrState::Merged => PrState::Merged,
+            },
+            comments,
+            checks,
         }
     }
 }
@@ -107,6 +282,15 @@ fn pr_from_octocrab(pr: &octocrab::models::pulls::PullRequest) -> PullRequest {
         title: pr.title.as_deref().unwrap_or_default().to_string(),
         node_id: pr.node_id.clone(),
         is_draft: pr.draft.unwrap_or(false),
+        state: if pr.merged_at.is_some() {
+            PrState::Merged
+        } else if matches!(pr.state, Some(octocrab::models::IssueState::Closed)) {
+            PrState::Closed
+        } else {
+            PrState::Open
+        },
+        comments: None,
+        checks: None,
     }
 }
 
@@ -134,18 +318,95 @@ impl PlatformService for GitHubService {
         Ok(result)
     }
 
+    async fn find_existing_prs(
+        &self,
+        head_branches: &[&str],
+    ) -> Result<HashMap<String, PullRequest>> {
+        let mut result = HashMap::new();
+
+        for chunk in head_branches.chunks(PR_LOOKUP_BATCH_SIZE) {
+            debug!(count = chunk.len(), "finding existing PRs (batched)");
+
+            let mut variables = serde_json::Map::new();
+            variables.insert("owner".into(), self.config.owner.clone().into());
+            variables.insert("repo".into(), self.config.repo.clone().into());
+            for (i, head) in chunk.iter().enumerate() {
+                variables.insert(format!("h{i}"), (*head).into());
+            }
+
+            let response: GraphQlResponse<RepositoryLookupData> = self
+                .client
+                .graphql(&serde_json::json!({
+                    "query": build_pr_lookup_query(chunk.len()),
+                    "variables": variables,
+                }))
+                .await
+                .map_err(|e| graphql_error("query", e))?;
+
+            let mut data = graphql_data(response)?;
+
+            for (i, head) in chunk.iter().enumerate() {
+                let Some(connection) = data.repository.remove(&format!("pr{i}")) else {
+                    continue;
+                };
+                // Match REST `head=owner:branch` semantics: ignore same-named fork branches
+                let pr = connection.nodes.into_iter().find(|pr| {
+                    pr.head_repository_owner
+                        .as_ref()
+                        .is_none_or(|o| o.login.eq_ignore_ascii_case(&self.config.owner))
+                });
+                if let Some(pr) = pr {
+                    result.insert((*head).to_string(), pr.into());
+                }
+            }
+        }
+
+        debug!(found = result.len(), "found existing PRs");
+        Ok(result)
+    }
+
+    async fn get_pr(&self, pr_number: u64) -> Result<Option<PullRequest>> {
+        debug!(pr_number, "fetching PR");
+        let result = self
+            .client
+            .pulls(&self.config.owner, &self.config.repo)
+            .get(pr_number)
+            .await;
+
+        match result {
+            Ok(pr) => Ok(Some(pr_from_octocrab(&pr))),
+            Err(octocrab::Error::GitHub { source, .. }) if source.status_code.as_u16() == 404 => {
+                debug!("PR not found");
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
     async fn create_pr_with_options(
         &self,
         head: &str,
         base: &str,
         title: &str,
         draft: bool,
+    ) -> Result<PullRequest> {
+        self.create_pr_with_body(head, base, title, "", draft).await
+    }
+
+    async fn create_pr_with_body(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
     ) -> Result<PullRequest> {
         debug!(head, base, draft, "creating PR");
         let pr = self
             .client
             .pulls(&self.config.owner, &self.config.repo)
             .create(title, head, base)
+            .body(body)
             .draft(draft)
             .send()
             .await?;
@@ -197,6 +458,7 @@ impl PlatformService for GitHubService {
                                 title
                                 id
                                 isDraft
+                                state
                             }
                         }
                     }
@@ -206,28 +468,50 @@ impl PlatformService for GitHubService {
                 }
             }))
             .await
-            .map_err(|e| Error::GitHubApi(format!("GraphQL mutation failed: {e}")))?;
-
-        // Check for GraphQL errors
-        if let Some(errors) = response.errors {
-            if !errors.is_empty() {
-                let messages: Vec<_> = errors.into_iter().map(|e| e.message).collect();
-                return Err(Error::GitHubApi(format!(
-                    "GraphQL error: {}",
-                    messages.join(", ")
-                )));
-            }
-        }
+            .map_err(|e| graphql_error("mutation", e))?;
 
-        // Extract typed response
-        let data = response
-            .data
-            .ok_or_else(|| Error::GitHubApi("No data in GraphQL response".to_string()))?;
+        let data = graphql_data(response)?;
 
         debug!(pr_number, "published PR");
         Ok(data.mark_pull_request_ready_for_review.pull_request.into())
     }
 
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        debug!(pr_number, "closing PR");
+        self.client
+            .pulls(&self.config.owner, &self.config.repo)
+            .update(pr_number)
+            .state(octocrab::params::pulls::State::Closed)
+            .send()
+            .await?;
+
+        debug!(pr_number, "closed PR");
+        Ok(())
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        debug!(pr_number, "getting PR body");
+        let pr = self
+            .client
+            .pulls(&self.config.owner, &self.config.repo)
+            .get(pr_number)
+            .await?;
+        Ok(pr.body.unwrap_or_default())
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(pr_number, "updating PR body");
+        self.client
+            .pulls(&self.config.owner, &self.config.repo)
+            .update(pr_number)
+            .body(body)
+            .send()
+            .await?;
+
+        debug!(pr_number, "updated PR body");
+        Ok(())
+    }
+
     async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
         debug!(pr_number, "listing PR comments");
         let comments = self
@@ -269,7 +553,129 @@ impl PlatformService for GitHubService {
         Ok(())
     }
 
+    async fn delete_pr_comment(&self, _pr_number: u64, comment_id: u64) -> Result<()> {
+        debug!(comment_id, "deleting PR comment");
+        self.client
+            .issues(&self.config.owner, &self.config.repo)
+            .delete_comment(octocrab::models::CommentId(comment_id))
+            .await?;
+        debug!(comment_id, "deleted PR comment");
+        Ok(())
+    }
+
+    async fn rate_limit_reset(&self) -> Result<Option<Duration>> {
+        // Checking the rate limit doesn't count against it
+        let resources = self.client.ratelimit().get().await?.resources;
+        let now = u64::try_from(Utc::now().timestamp()).unwrap_or_default();
+        Ok([Some(resources.core), resources.graphql]
+            .into_iter()
+            .flatten()
+            .filter(|rate| rate.remaining == 0)
+            .map(|rate| Duration::from_secs(rate.reset.saturating_sub(now)))
+            .max())
+    }
+
     fn config(&self) -> &PlatformConfig {
         &self.config
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pr_lookup_query_aliases_each_branch() {
+        let query = build_pr_lookup_query(2);
+        assert!(query.contains("$owner: String!, $repo: String!, $h0: String!, $h1: String!"));
+        assert!(query.contains("pr0: pullRequests(headRefName: $h0, states: OPEN"));
+        assert!(query.contains("pr1: pullRequests(headRefName: $h1, states: OPEN"));
+        assert!(!query.contains("pr2:"));
+    }
+
+    #[test]
+    fn test_lookup_response_deserializes_aliases() {
+        let json = serde_json::json!({
+            "data": {
+                "repository": {
+                    "pr0": { "nodes": [{
+                        "number": 7,
+                        "url": "https://github.com/o/r/pull/7",
+                        "baseRefName": "main",
+                        "headRefName": "feat-a",
+                        "title": "Add feat-a",
+                        "id": "PR_7",
+                        "isDraft": true,
+                        "state": "OPEN",
+                        "headRepositoryOwner": { "login": "o" },
+                        "comments": {
+                            "pageInfo": { "hasNextPage": false },
+                            "nodes": [{ "databaseId": 70, "body": "LGTM" }]
+                        },
+                        "commits": { "nodes": [{
+                            "commit": { "statusCheckRollup": { "state": "ERROR" } }
+                        }]}
+                    }]},
+                    "pr1": { "nodes": [] }
+                }
+            }
+        });
+
+        let response: GraphQlResponse<RepositoryLookupData> = serde_json::from_value(json).unwrap();
+        let mut data = graphql_data(response).unwrap();
+
+        let pr: PullRequest = data
+            .repository
+            .remove("pr0")
+            .unwrap()
+            .nodes
+            .remove(0)
+            .into();
+        assert_eq!(pr.number, 7);
+        assert_eq!(pr.head_ref, "feat-a");
+        assert!(pr.is_draft);
+        assert_eq!(pr.state, PrState::Open);
+        let comments = pr.comments.unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].id, 70);
+        assert_eq!(pr.checks, Some(ChecksState::Failure));
+        assert!(data.repository["pr1"].nodes.is_empty());
+    }
+
+    #[test]
+    fn test_lookup_drops_partial_comment_pages() {
+        let pr: GraphQlPullRequest = serde_json::from_value(serde_json::json!({
+            "number": 8,
+            "url": "https://github.com/o/r/pull/8",
+            "baseRefName": "main",
+            "headRefName": "feat-b",
+            "title": "Add feat-b",
+            "id": "PR_8",
+            "isDraft": false,
+            "state": "OPEN",
+            "comments": {
+                "pageInfo": { "hasNextPage": true },
+                "nodes": [{ "databaseId": 80, "body": "first" }]
+            },
+            "commits": { "nodes": [{ "commit": { "statusCheckRollup": null } }] }
+        }))
+        .unwrap();
+
+        let pr = PullRequest::from(pr);
+        assert!(pr.comments.is_none());
+        assert!(pr.checks.is_none());
+    }
+
+    #[test]
+    fn test_graphql_errors_are_surfaced() {
+        let response: GraphQlResponse<RepositoryLookupData> =
+            serde_json::from_value(serde_json::json!({
+                "data": null,
+                "errors": [{ "message": "rate limited" }]
+            }))
+            .unwrap();
+
+        let err = graphql_data(response).err().unwrap();
+        assert!(err.to_string().contains("rate limited"));
+    }
+}
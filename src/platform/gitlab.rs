@@ -2,7 +2,8 @@
 
 use crate::error::{Error, Result};
 use crate::platform::PlatformService;
-use crate::types::{Platform, PlatformConfig, PrComment, PullRequest};
+use crate::platform::retry::CheckStatus;
+use crate::types::{Platform, PlatformConfig, PrComment, PrState, PullRequest};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -26,6 +27,14 @@ struct MergeRequest {
     title: String,
     #[serde(default)]
     draft: bool,
+    #[serde(default)]
+    state: String,
+}
+
+#[derive(Deserialize)]
+struct MrDescription {
+    #[serde(default)]
+    description: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -45,6 +54,14 @@ impl From<MergeRequest> for PullRequest {
             title: mr.title,
             node_id: None, // GitLab doesn't use GraphQL node IDs
             is_draft: mr.draft,
+            // "opened", "closed", "locked" or "merged"
+            state: match mr.state.as_str() {
+                "merged" => PrState::Merged,
+                "closed" => PrState::Closed,
+                _ => PrState::Open,
+            },
+            comments: None,
+            checks: None,
         }
     }
 }
@@ -54,6 +71,7 @@ struct CreateMrPayload {
     source_branch: String,
     target_branch: String,
     title: String,
+    description: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     draft: Option<bool>,
 }
@@ -117,8 +135,7 @@ impl PlatformService for GitLabService {
             .query(&[("source_branch", head_branch), ("state", "opened")])
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .check_status(Error::GitLabApi)?
             .json()
             .await?;
 
@@ -131,12 +148,48 @@ impl PlatformService for GitLabService {
         Ok(result)
     }
 
+    async fn get_pr(&self, pr_number: u64) -> Result<Option<PullRequest>> {
+        debug!(mr_iid = pr_number, "fetching MR");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        let response = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            debug!(mr_iid = pr_number, "MR not found");
+            return Ok(None);
+        }
+
+        let mr: MergeRequest = response.check_status(Error::GitLabApi)?.json().await?;
+
+        Ok(Some(mr.into()))
+    }
+
     async fn create_pr_with_options(
         &self,
         head: &str,
         base: &str,
         title: &str,
         draft: bool,
+    ) -> Result<PullRequest> {
+        self.create_pr_with_body(head, base, title, "", draft).await
+    }
+
+    async fn create_pr_with_body(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
     ) -> Result<PullRequest> {
         debug!(head, base, draft, "creating MR");
         let url = self.api_url(&format!(
@@ -148,6 +201,7 @@ impl PlatformService for GitLabService {
             source_branch: head.to_string(),
             target_branch: base.to_string(),
             title: title.to_string(),
+            description: body.to_string(),
             draft: if draft { Some(true) } else { None },
         };
 
@@ -158,8 +212,7 @@ impl PlatformService for GitLabService {
             .json(&payload)
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .check_status(Error::GitLabApi)?
             .json()
             .await?;
 
@@ -183,8 +236,7 @@ impl PlatformService for GitLabService {
             .json(&serde_json::json!({ "target_branch": new_base }))
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .check_status(Error::GitLabApi)?
             .json()
             .await?;
 
@@ -210,8 +262,7 @@ impl PlatformService for GitLabService {
             .json(&serde_json::json!({ "state_event": "ready" }))
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .check_status(Error::GitLabApi)?
             .json()
             .await?;
 
@@ -219,6 +270,67 @@ impl PlatformService for GitLabService {
         Ok(mr.into())
     }
 
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        debug!(mr_iid = pr_number, "closing MR");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        self.client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "state_event": "close" }))
+            .send()
+            .await?
+            .check_status(Error::GitLabApi)?;
+
+        debug!(mr_iid = pr_number, "closed MR");
+        Ok(())
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        debug!(mr_iid = pr_number, "getting MR description");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        let mr: MrDescription = self
+            .client
+            .get(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await?
+            .check_status(Error::GitLabApi)?
+            .json()
+            .await?;
+
+        Ok(mr.description.unwrap_or_default())
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        debug!(mr_iid = pr_number, "updating MR description");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}",
+            self.encoded_project(),
+            pr_number
+        ));
+
+        self.client
+            .put(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .json(&serde_json::json!({ "description": body }))
+            .send()
+            .await?
+            .check_status(Error::GitLabApi)?;
+
+        debug!(mr_iid = pr_number, "updated MR description");
+        Ok(())
+    }
+
     async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
         debug!(mr_iid = pr_number, "listing MR comments");
         let url = self.api_url(&format!(
@@ -233,8 +345,7 @@ impl PlatformService for GitLabService {
             .header("PRIVATE-TOKEN", &self.token)
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::GitLabApi(e.to_string()))?
+            .check_status(Error::GitLabApi)?
             .json()
             .await?;
 
@@ -268,8 +379,7 @@ impl PlatformService for GitLabService {
             .json(&serde_json::json!({ "body": body }))
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::GitLabApi(e.to_string()))?;
+            .check_status(Error::GitLabApi)?;
 
         debug!(mr_iid = pr_number, "created MR comment");
         Ok(())
@@ -290,13 +400,32 @@ impl PlatformService for GitLabService {
             .json(&serde_json::json!({ "body": body }))
             .send()
             .await?
-            .error_for_status()
-            .map_err(|e| Error::GitLabApi(e.to_string()))?;
+            .check_status(Error::GitLabApi)?;
 
         debug!(mr_iid = pr_number, comment_id, "updated MR comment");
         Ok(())
     }
 
+    async fn delete_pr_comment(&self, pr_number: u64, comment_id: u64) -> Result<()> {
+        debug!(mr_iid = pr_number, comment_id, "deleting MR comment");
+        let url = self.api_url(&format!(
+            "/projects/{}/merge_requests/{}/notes/{}",
+            self.encoded_project(),
+            pr_number,
+            comment_id
+        ));
+
+        self.client
+            .delete(&url)
+            .header("PRIVATE-TOKEN", &self.token)
+            .send()
+            .await?
+            .check_status(Error::GitLabApi)?;
+
+        debug!(mr_iid = pr_number, comment_id, "deleted MR comment");
+        Ok(())
+    }
+
     fn config(&self) -> &PlatformConfig {
         &self.config
     }
@@ -7,16 +7,20 @@ mod detection;
 mod factory;
 mod github;
 mod gitlab;
+mod retry;
 
 pub use azure_devops::AzureDevOpsService;
-pub use detection::{detect_platform, parse_repo_info};
-pub use factory::create_platform_service;
+pub use detection::{detect_platform, parse_pr_reference, parse_repo_info};
+pub use factory::{create_platform_service, create_platform_service_with_progress};
 pub use github::GitHubService;
 pub use gitlab::GitLabService;
+pub use retry::{RetryPolicy, RetryingPlatformService, retry_after_from_headers};
 
 use crate::error::Result;
 use crate::types::{PlatformConfig, PrComment, PullRequest};
 use async_trait::async_trait;
+use std::collections::HashMap;
+use std::time::Duration;
 
 /// Platform service trait for PR/MR operations
 ///
@@ -27,6 +31,32 @@ pub trait PlatformService: Send + Sync {
     /// Find an existing open PR for a head branch
     async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>>;
 
+    /// Find existing open PRs for several head branches at once.
+    ///
+    /// Returns a map from head branch to PR; branches without an open PR are
+    /// absent. The default implementation calls [`find_existing_pr`] once per
+    /// branch; platforms that can batch lookups should override it.
+    ///
+    /// [`find_existing_pr`]: Self::find_existing_pr
+    async fn find_existing_prs(
+        &self,
+        head_branches: &[&str],
+    ) -> Result<HashMap<String, PullRequest>> {
+        let mut prs = HashMap::new();
+        for head in head_branches {
+            if let Some(pr) = self.find_existing_pr(head).await? {
+                prs.insert((*head).to_string(), pr);
+            }
+        }
+        Ok(prs)
+    }
+
+    /// Fetch a PR by number, whatever its state.
+    ///
+    /// Returns `None` if no PR with that number exists (or it is not visible
+    /// to the current token).
+    async fn get_pr(&self, pr_number: u64) -> Result<Option<PullRequest>>;
+
     /// Create a new PR with default options (non-draft).
     ///
     /// This is a convenience method that delegates to [`create_pr_with_options`]
@@ -52,12 +82,46 @@ pub trait PlatformService: Send + Sync {
         draft: bool,
     ) -> Result<PullRequest>;
 
+    /// Create a new PR with a description (`body`, may be empty).
+    ///
+    /// The default implementation creates the PR with
+    /// [`create_pr_with_options`] and then sets its description, so
+    /// implementors that predate descriptions keep working. Platforms that
+    /// take a description on creation override this to do it in one request.
+    ///
+    /// [`create_pr_with_options`]: Self::create_pr_with_options
+    async fn create_pr_with_body(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        let pr = self
+            .create_pr_with_options(head, base, title, draft)
+            .await?;
+        if !body.is_empty() {
+            self.update_pr_body(pr.number, body).await?;
+        }
+        Ok(pr)
+    }
+
     /// Update the base branch of an existing PR
     async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest>;
 
     /// Publish a draft PR (convert to ready for review)
     async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest>;
 
+    /// Close a PR without merging it
+    async fn close_pr(&self, pr_number: u64) -> Result<()>;
+
+    /// Get the description (body) of a PR
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String>;
+
+    /// Replace the description (body) of a PR
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()>;
+
     /// List comments on a PR
     async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>>;
 
@@ -67,6 +131,18 @@ pub trait PlatformService: Send + Sync {
     /// Update an existing comment on a PR
     async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, body: &str) -> Result<()>;
 
+    /// Delete a comment from a PR
+    async fn delete_pr_comment(&self, pr_number: u64, comment_id: u64) -> Result<()>;
+
+    /// Time until an exhausted rate limit resets, if the platform can say
+    ///
+    /// Asked when a rate-limit error doesn't carry its own reset time (as
+    /// octocrab's errors don't). `None` if no limit is exhausted or the
+    /// platform has no way to tell.
+    async fn rate_limit_reset(&self) -> Result<Option<Duration>> {
+        Ok(None)
+    }
+
     /// Get the platform configuration
     fn config(&self) -> &PlatformConfig;
 }
@@ -0,0 +1,743 @@
+//! Retry with backoff for platform API calls
+//!
+//! [`RetryingPlatformService`] wraps any [`PlatformService`] and retries
+//! transient failures: rate limits (HTTP 429, or GitHub's 403 secondary
+//! limits), server errors and dropped connections. Waits honour `Retry-After`,
+//! `X-RateLimit-Reset` and `RateLimit-Reset` on the raw HTTP responses that
+//! carry them. octocrab's errors drop the headers, so for a GitHub rate limit
+//! the reset time is looked up with [`PlatformService::rate_limit_reset`].
+//! Without any hint (e.g. a secondary rate limit), retries back off
+//! exponentially. A limit that resets later than [`RetryPolicy::max_delay`]
+//! fails straight away, with the reset time in the error.
+//!
+//! Calls that aren't idempotent are re-checked before being retried: a failed
+//! `create_pr` first looks for a PR the failed request may have created, and
+//! a failed `create_pr_comment` looks for a matching comment.
+
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::submit::ProgressCallback;
+use crate::types::{PlatformConfig, PrComment, PullRequest};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Retry limits and backoff timing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry when the platform gives no hint
+    pub base_delay: Duration,
+    /// Longest single wait; a rate limit that resets later fails immediately
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 4,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub const fn none() -> Self {
+        Self {
+            max_retries: 0,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+        }
+    }
+
+    /// Exponential backoff before retry number `attempt` (0-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+}
+
+/// Status checking for raw platform HTTP responses
+pub trait CheckStatus: Sized {
+    /// Like `error_for_status`, but reports rate limits and server errors as
+    /// [`Error::Transient`] so they can be retried.
+    fn check_status(self, to_error: fn(String) -> Error) -> Result<Self>;
+}
+
+impl CheckStatus for reqwest::Response {
+    fn check_status(self, to_error: fn(String) -> Error) -> Result<Self> {
+        let Err(err) = self.error_for_status_ref() else {
+            return Ok(self);
+        };
+
+        let status = self.status();
+        let error = to_error(err.to_string());
+        let retry_after = retry_after_from_headers(self.headers(), Utc::now());
+        let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+            || (status == StatusCode::FORBIDDEN && retry_after.is_some());
+
+        if rate_limited || status.is_server_error() {
+            return Err(Error::Transient {
+                message: error.to_string(),
+                rate_limited,
+                retry_after,
+            });
+        }
+        Err(error)
+    }
+}
+
+/// How long the platform asked us to wait, from response headers
+///
+/// `Retry-After` wins when present. The reset headers are only used once the
+/// matching remaining-requests header has hit zero, since GitHub sends them
+/// on every response.
+pub fn retry_after_from_headers(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if let Some(value) = header("retry-after") {
+        return parse_retry_after(value, now);
+    }
+
+    [
+        ("x-ratelimit-remaining", "x-ratelimit-reset"),
+        ("ratelimit-remaining", "ratelimit-reset"),
+    ]
+    .into_iter()
+    .filter(|(remaining, _)| header(remaining).is_some_and(|v| v.trim() == "0"))
+    .find_map(|(_, reset)| header(reset).and_then(|v| parse_reset(v, now)))
+}
+
+/// Parse `Retry-After`: delay-seconds or an HTTP date
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|at| duration_until(at.with_timezone(&Utc), now))
+}
+
+/// Parse a rate-limit reset: a Unix timestamp (GitHub, GitLab) or, per the
+/// IETF draft, delta-seconds
+fn parse_reset(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let secs: i64 = value.trim().parse().ok()?;
+    if secs > 1_000_000_000 {
+        DateTime::from_timestamp(secs, 0).map(|at| duration_until(at, now))
+    } else {
+        u64::try_from(secs).ok().map(Duration::from_secs)
+    }
+}
+
+fn duration_until(at: DateTime<Utc>, now: DateTime<Utc>) -> Duration {
+    (at - now).to_std().unwrap_or(Duration::ZERO)
+}
+
+/// Whether an octocrab error is a rate limit, server error or network failure
+pub fn is_transient_octocrab(error: &octocrab::Error) -> bool {
+    match error {
+        octocrab::Error::GitHub { source, .. } => {
+            (500..600).contains(&source.status_code.as_u16()) || is_octocrab_rate_limit(error)
+        }
+        octocrab::Error::Hyper { .. } | octocrab::Error::Service { .. } => true,
+        _ => false,
+    }
+}
+
+/// Whether an octocrab error is GitHub refusing a request over a rate limit
+fn is_octocrab_rate_limit(error: &octocrab::Error) -> bool {
+    match error {
+        octocrab::Error::GitHub { source, .. } => {
+            let status = source.status_code.as_u16();
+            status == 429 || (status == 403 && source.message.to_lowercase().contains("rate limit"))
+        }
+        _ => false,
+    }
+}
+
+/// How a retryable failure should be waited out
+enum RetryHint {
+    /// No server hint; use exponential backoff
+    Backoff,
+    /// The server said when to try again
+    After(Duration),
+    /// Rate limited without a reset time; ask the platform for it
+    RateLimited,
+}
+
+/// Classify an error; `None` if retrying can't help
+fn retry_hint(error: &Error) -> Option<RetryHint> {
+    match error {
+        Error::Transient {
+            retry_after: Some(wait),
+            ..
+        } => Some(RetryHint::After(*wait)),
+        Error::Transient {
+            rate_limited: true, ..
+        } => Some(RetryHint::RateLimited),
+        Error::Transient { .. } => Some(RetryHint::Backoff),
+        Error::Http(e) if e.is_timeout() || e.is_connect() => Some(RetryHint::Backoff),
+        Error::Octocrab(e) if is_octocrab_rate_limit(e) => Some(RetryHint::RateLimited),
+        Error::Octocrab(e) if is_transient_octocrab(e) => Some(RetryHint::Backoff),
+        _ => None,
+    }
+}
+
+/// Platform service decorator that retries transient failures
+pub struct RetryingPlatformService {
+    inner: Box<dyn PlatformService>,
+    policy: RetryPolicy,
+    progress: Option<Arc<dyn ProgressCallback>>,
+}
+
+impl RetryingPlatformService {
+    /// Wrap a platform service
+    pub fn new(inner: Box<dyn PlatformService>, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            progress: None,
+        }
+    }
+
+    /// Report retries through a progress callback
+    #[must_use]
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressCallback>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Delay before retry number `attempt`, or the error to give up with
+    async fn next_delay(&self, error: Error, attempt: u32) -> Result<(Duration, Error)> {
+        if attempt >= self.policy.max_retries {
+            return Err(error);
+        }
+        let Some(hint) = retry_hint(&error) else {
+            return Err(error);
+        };
+        let wait = match hint {
+            RetryHint::After(wait) => wait,
+            RetryHint::Backoff => return Ok((self.policy.backoff(attempt), error)),
+            RetryHint::RateLimited => match self.inner.rate_limit_reset().await {
+                Ok(Some(wait)) => wait,
+                // A secondary limit, or the lookup failed: nothing to wait for
+                Ok(None) | Err(_) => return Ok((self.policy.backoff(attempt), error)),
+            },
+        };
+
+        // Waiting for a distant rate-limit reset is worse than failing now
+        if wait > self.policy.max_delay {
+            return Err(Error::Transient {
+                message: format!(
+                    "{error} (rate limit resets in {}s; try again then)",
+                    wait.as_secs()
+                ),
+                rate_limited: true,
+                retry_after: Some(wait),
+            });
+        }
+        Ok((wait, error))
+    }
+
+    async fn wait(&self, operation: &str, attempt: u32, delay: Duration, error: &Error) {
+        warn!(operation, attempt, ?delay, %error, "retrying platform request");
+        if let Some(progress) = &self.progress {
+            progress.on_retry(operation, attempt, delay, error).await;
+        }
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Run an idempotent call, retrying transient failures
+    async fn retry<T, F, Fut>(&self, operation: &str, mut call: F) -> Result<T>
+    where
+        F: FnMut() -> Fut + Send,
+        Fut: Future<Output = Result<T>> + Send,
+        T: Send,
+    {
+        let mut attempt = 0;
+        loop {
+            match call().await {
+                Ok(value) => return Ok(value),
+                Err(error) => {
+                    let (delay, error) = self.next_delay(error, attempt).await?;
+                    attempt += 1;
+                    self.wait(operation, attempt, delay, &error).await;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl PlatformService for RetryingPlatformService {
+    async fn find_existing_pr(&self, head_branch: &str) -> Result<Option<PullRequest>> {
+        self.retry("find PR", || self.inner.find_existing_pr(head_branch))
+            .await
+    }
+
+    async fn find_existing_prs(
+        &self,
+        head_branches: &[&str],
+    ) -> Result<HashMap<String, PullRequest>> {
+        self.retry("find PRs", || self.inner.find_existing_prs(head_branches))
+            .await
+    }
+
+    async fn get_pr(&self, pr_number: u64) -> Result<Option<PullRequest>> {
+        self.retry(&format!("get PR #{pr_number}"), || {
+            self.inner.get_pr(pr_number)
+        })
+        .await
+    }
+
+    async fn create_pr_with_options(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        self.create_pr_with_body(head, base, title, "", draft).await
+    }
+
+    async fn create_pr_with_body(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest> {
+        let operation = format!("create PR for {head}");
+        let mut attempt = 0;
+        loop {
+            let error = match self
+                .inner
+                .create_pr_with_body(head, base, title, body, draft)
+                .await
+            {
+                Ok(pr) => return Ok(pr),
+                Err(error) => error,
+            };
+            let (delay, error) = self.next_delay(error, attempt).await?;
+            attempt += 1;
+            self.wait(&operation, attempt, delay, &error).await;
+
+            // The failed request may have created the PR before erroring
+            if let Some(pr) = self.find_existing_pr(head).await? {
+                debug!(pr_number = pr.number, "PR exists after failed create");
+                return Ok(pr);
+            }
+        }
+    }
+
+    async fn update_pr_base(&self, pr_number: u64, new_base: &str) -> Result<PullRequest> {
+        self.retry(&format!("update base of PR #{pr_number}"), || {
+            self.inner.update_pr_base(pr_number, new_base)
+        })
+        .await
+    }
+
+    async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
+        self.retry(&format!("publish PR #{pr_number}"), || {
+            self.inner.publish_pr(pr_number)
+        })
+        .await
+    }
+
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        self.retry(&format!("close PR #{pr_number}"), || {
+            self.inner.close_pr(pr_number)
+        })
+        .await
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        self.retry(&format!("get description of PR #{pr_number}"), || {
+            self.inner.get_pr_body(pr_number)
+        })
+        .await
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        self.retry(&format!("update description of PR #{pr_number}"), || {
+            self.inner.update_pr_body(pr_number, body)
+        })
+        .await
+    }
+
+    async fn list_pr_comments(&self, pr_number: u64) -> Result<Vec<PrComment>> {
+        self.retry(&format!("list comments on PR #{pr_number}"), || {
+            self.inner.list_pr_comments(pr_number)
+        })
+        .await
+    }
+
+    async fn create_pr_comment(&self, pr_number: u64, body: &str) -> Result<()> {
+        let operation = format!("comment on PR #{pr_number}");
+        let mut attempt = 0;
+        loop {
+            let error = match self.inner.create_pr_comment(pr_number, body).await {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+            let (delay, error) = self.next_delay(error, attempt).await?;
+            attempt += 1;
+            self.wait(&operation, attempt, delay, &error).await;
+
+            // Don't post the comment twice if the failed request went through
+            let comments = self.list_pr_comments(pr_number).await?;
+            if comments.iter().any(|c| c.body.trim() == body.trim()) {
+                debug!(pr_number, "comment exists after failed create");
+                return Ok(());
+            }
+        }
+    }
+
+    async fn update_pr_comment(&self, pr_number: u64, comment_id: u64, body: &str) -> Result<()> {
+        self.retry(&format!("update comment on PR #{pr_number}"), || {
+            self.inner.update_pr_comment(pr_number, comment_id, body)
+        })
+        .await
+    }
+
+    async fn delete_pr_comment(&self, pr_number: u64, comment_id: u64) -> Result<()> {
+        self.retry(&format!("delete comment on PR #{pr_number}"), || {
+            self.inner.delete_pr_comment(pr_number, comment_id)
+        })
+        .await
+    }
+
+    async fn rate_limit_reset(&self) -> Result<Option<Duration>> {
+        self.inner.rate_limit_reset().await
+    }
+
+    fn config(&self) -> &PlatformConfig {
+        self.inner.config()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Platform, PrState};
+    use reqwest::header::HeaderValue;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn now() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    fn transient() -> Error {
+        Error::Transient {
+            message: "HTTP 502".to_string(),
+            rate_limited: false,
+            retry_after: None,
+        }
+    }
+
+    fn instant_policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::from_secs(60),
+        }
+    }
+
+    /// Platform that fails a configurable number of calls before succeeding
+    struct FlakyPlatform {
+        config: PlatformConfig,
+        failures: AtomicU32,
+        calls: AtomicU32,
+        /// Whether failed creates actually created the PR
+        created_despite_error: bool,
+        created: Mutex<Option<PullRequest>>,
+        /// What `rate_limit_reset` reports
+        rate_limit_reset: Option<Duration>,
+    }
+
+    impl FlakyPlatform {
+        fn new(failures: u32) -> Self {
+            Self {
+                config: PlatformConfig {
+                    platform: Platform::GitHub,
+                    owner: "owner".to_string(),
+                    repo: "repo".to_string(),
+                    host: None,
+                },
+                failures: AtomicU32::new(failures),
+                calls: AtomicU32::new(0),
+                created_despite_error: false,
+                created: Mutex::new(None),
+                rate_limit_reset: None,
+            }
+        }
+
+        fn fail(&self) -> Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(transient());
+            }
+            Ok(())
+        }
+    }
+
+    fn make_pr(number: u64, head: &str) -> PullRequest {
+        PullRequest {
+            number,
+            html_url: format!("https://github.com/owner/repo/pull/{number}"),
+            base_ref: "main".to_string(),
+            head_ref: head.to_string(),
+            title: "Test".to_string(),
+            node_id: None,
+            is_draft: false,
+            state: PrState::Open,
+            comments: None,
+            checks: None,
+        }
+    }
+
+    #[async_trait]
+    impl PlatformService for FlakyPlatform {
+        async fn find_existing_pr(&self, _head_branch: &str) -> Result<Option<PullRequest>> {
+            Ok(self.created.lock().unwrap().clone())
+        }
+
+        async fn get_pr(&self, pr_number: u64) -> Result<Option<PullRequest>> {
+            self.fail()?;
+            Ok(Some(make_pr(pr_number, "feat")))
+        }
+
+        async fn create_pr_with_options(
+            &self,
+            head: &str,
+            _base: &str,
+            _title: &str,
+            _draft: bool,
+        ) -> Result<PullRequest> {
+            let pr = make_pr(self.calls.load(Ordering::SeqCst).into(), head);
+            if let Err(e) = self.fail() {
+                if self.created_despite_error {
+                    *self.created.lock().unwrap() = Some(pr);
+                }
+                return Err(e);
+            }
+            Ok(pr)
+        }
+
+        async fn update_pr_base(&self, pr_number: u64, _new_base: &str) -> Result<PullRequest> {
+            Err(Error::Platform(format!("PR #{pr_number} is locked")))
+        }
+
+        async fn publish_pr(&self, pr_number: u64) -> Result<PullRequest> {
+            Ok(make_pr(pr_number, "feat"))
+        }
+
+        async fn close_pr(&self, _pr_number: u64) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_pr_body(&self, _pr_number: u64) -> Result<String> {
+            Ok(String::new())
+        }
+
+        async fn update_pr_body(&self, _pr_number: u64, _body: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn list_pr_comments(&self, _pr_number: u64) -> Result<Vec<PrComment>> {
+            Ok(vec![])
+        }
+
+        async fn create_pr_comment(&self, _pr_number: u64, _body: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn update_pr_comment(
+            &self,
+            _pr_number: u64,
+            _comment_id: u64,
+            _body: &str,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn delete_pr_comment(&self, _pr_number: u64, _comment_id: u64) -> Result<()> {
+            Ok(())
+        }
+
+        async fn rate_limit_reset(&self) -> Result<Option<Duration>> {
+            Ok(self.rate_limit_reset)
+        }
+
+        fn config(&self) -> &PlatformConfig {
+            &self.config
+        }
+    }
+
+    #[test]
+    fn test_retry_after_seconds_and_http_date() {
+        let h = headers(&[("retry-after", "30")]);
+        assert_eq!(
+            retry_after_from_headers(&h, now()),
+            Some(Duration::from_secs(30))
+        );
+
+        // `now()` is 2023-11-14 22:13:20 UTC
+        let h = headers(&[("retry-after", "Tue, 14 Nov 2023 22:13:27 GMT")]);
+        assert_eq!(
+            retry_after_from_headers(&h, now()),
+            Some(Duration::from_secs(7))
+        );
+    }
+
+    #[test]
+    fn test_reset_headers_only_used_when_exhausted() {
+        let reset = (1_700_000_000 + 42).to_string();
+
+        let h = headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", &reset),
+        ]);
+        assert_eq!(
+            retry_after_from_headers(&h, now()),
+            Some(Duration::from_secs(42))
+        );
+
+        // GitLab spelling
+        let h = headers(&[("ratelimit-remaining", "0"), ("ratelimit-reset", &reset)]);
+        assert_eq!(
+            retry_after_from_headers(&h, now()),
+            Some(Duration::from_secs(42))
+        );
+
+        let h = headers(&[
+            ("x-ratelimit-remaining", "4999"),
+            ("x-ratelimit-reset", &reset),
+        ]);
+        assert_eq!(retry_after_from_headers(&h, now()), None);
+    }
+
+    #[test]
+    fn test_backoff_is_bounded() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(8));
+        assert_eq!(policy.backoff(30), policy.max_delay);
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures() {
+        let service =
+            RetryingPlatformService::new(Box::new(FlakyPlatform::new(2)), instant_policy(3));
+        let pr = service.get_pr(5).await.unwrap().unwrap();
+        assert_eq!(pr.number, 5);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let service =
+            RetryingPlatformService::new(Box::new(FlakyPlatform::new(5)), instant_policy(2));
+        assert!(matches!(
+            service.get_pr(5).await,
+            Err(Error::Transient { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_permanent_errors() {
+        let service =
+            RetryingPlatformService::new(Box::new(FlakyPlatform::new(0)), instant_policy(3));
+        assert!(matches!(
+            service.update_pr_base(1, "main").await,
+            Err(Error::Platform(_))
+        ));
+    }
+
+    fn rate_limited(retry_after: Option<Duration>) -> Error {
+        Error::Transient {
+            message: "rate limited".to_string(),
+            rate_limited: true,
+            retry_after,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_distant_rate_limit_reset_fails_fast() {
+        let service =
+            RetryingPlatformService::new(Box::new(FlakyPlatform::new(1)), instant_policy(3));
+        let error = rate_limited(Some(Duration::from_secs(3600)));
+        match service.next_delay(error, 0).await {
+            Err(Error::Transient { message, .. }) => {
+                assert!(message.contains("resets in 3600s"), "{message}");
+            }
+            other => panic!("expected a rate-limit error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_without_hint_asks_platform() {
+        let mut flaky = FlakyPlatform::new(1);
+        flaky.rate_limit_reset = Some(Duration::from_secs(20));
+        let service = RetryingPlatformService::new(Box::new(flaky), instant_policy(3));
+
+        let (delay, _) = service.next_delay(rate_limited(None), 0).await.unwrap();
+        assert_eq!(delay, Duration::from_secs(20));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_without_any_reset_backs_off() {
+        let service =
+            RetryingPlatformService::new(Box::new(FlakyPlatform::new(1)), instant_policy(3));
+
+        let (delay, _) = service.next_delay(rate_limited(None), 0).await.unwrap();
+        assert_eq!(delay, Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_create_pr_rechecks_before_retrying() {
+        let mut flaky = FlakyPlatform::new(1);
+        flaky.created_despite_error = true;
+        let service = RetryingPlatformService::new(Box::new(flaky), instant_policy(3));
+
+        let pr = service.create_pr("feat", "main", "Add feat").await.unwrap();
+
+        // The PR created by the failed request is returned, not a second one
+        assert_eq!(pr.number, 0);
+    }
+
+    #[tokio::test]
+    async fn test_create_pr_retries_when_nothing_was_created() {
+        let service =
+            RetryingPlatformService::new(Box::new(FlakyPlatform::new(1)), instant_policy(3));
+
+        let pr = service.create_pr("feat", "main", "Add feat").await.unwrap();
+        assert_eq!(pr.number, 1);
+    }
+}
@@ -4,4 +4,4 @@
 
 mod workspace;
 
-pub use workspace::{JjWorkspace, select_remote};
+pub use workspace::{ALL_BOOKMARKS, JjWorkspace, select_remote, select_remote_with_default};
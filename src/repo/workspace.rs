@@ -1,27 +1,44 @@
 //! `JjWorkspace` - wrapper around jj-lib for repository operations
 
 use crate::error::{Error, Result};
-use crate::types::{Bookmark, GitRemote, LogEntry};
+use crate::types::{
+    Bookmark, GitRemote, LogEntry, PushReport, RemoteDivergence, UnpushableCommit, UnpushableReason,
+};
 use chrono::{DateTime, TimeZone, Utc};
-use jj_lib::backend::Timestamp;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::{Timestamp, TreeValue};
 use jj_lib::commit::Commit;
-use jj_lib::config::{ConfigLayer, ConfigSource, StackedConfig};
+use jj_lib::config::{ConfigGetResultExt, ConfigLayer, ConfigSource, StackedConfig};
+use jj_lib::fileset::{self, FilesetDiagnostics};
 use jj_lib::git::{
-    self, GitFetch, GitRefUpdate, GitSettings, RemoteCallbacks, expand_fetch_refspecs,
+    self, GitFetch, GitRefKind, GitRefUpdate, GitSettings, RemoteCallbacks, expand_fetch_refspecs,
 };
+use jj_lib::gitignore::GitIgnoreFile;
+use jj_lib::matchers::{EverythingMatcher, Matcher, NothingMatcher, PrefixMatcher};
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
-use jj_lib::op_store::{RemoteRef, RemoteRefState};
+use jj_lib::op_store::{RefTarget, RemoteRef, RemoteRefState};
+use jj_lib::op_walk;
 use jj_lib::ref_name::{RefName, RemoteName};
-use jj_lib::repo::{Repo, StoreFactories};
-use jj_lib::repo_path::RepoPathUiConverter;
+use jj_lib::repo::{MutableRepo, ReadonlyRepo, Repo, StoreFactories};
+use jj_lib::repo_path::{RepoPath, RepoPathUiConverter};
 use jj_lib::revset::{
-    self, RevsetExtensions, RevsetParseContext, RevsetWorkspaceContext, SymbolResolver,
+    self, RevsetExpression, RevsetExtensions, RevsetParseContext, RevsetWorkspaceContext,
+    SymbolResolver, UserRevsetExpression,
 };
-use jj_lib::settings::UserSettings;
+use jj_lib::rewrite::{MoveCommitsLocation, MoveCommitsTarget, RebaseOptions, move_commits};
+use jj_lib::settings::{HumanByteSize, UserSettings};
 use jj_lib::str_util::{StringExpression, StringMatcher, StringPattern};
+use jj_lib::working_copy::SnapshotOptions;
 use jj_lib::workspace::{Workspace, default_working_copy_factories};
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::Arc;
+use tokio::io::AsyncReadExt;
+
+/// Bookmark pattern matching every branch on a remote (see [`JjWorkspace::git_fetch`])
+pub const ALL_BOOKMARKS: &str = "glob:*";
 
 /// Wrapper around jj-lib workspace and repository
 pub struct JjWorkspace {
@@ -30,7 +47,10 @@ pub struct JjWorkspace {
 }
 
 /// Create `UserSettings` with defaults for read operations
-fn create_user_settings() -> Result<UserSettings> {
+///
+/// Layers the user's jj config and, given the repo path, the repo's own
+/// `config.toml` on top, so settings like `git.push` apply as they do in jj.
+fn create_user_settings(repo_path: Option<&Path>) -> Result<UserSettings> {
     let mut config = StackedConfig::with_defaults();
 
     // Add minimal user config - required by UserSettings::from_config
@@ -52,6 +72,12 @@ fn create_user_settings() -> Result<UserSettings> {
         }
     }
 
+    if let Some(repo_config) = repo_path.map(|path| path.join("config.toml")) {
+        if repo_config.exists() {
+            let _ = config.load_file(ConfigSource::Repo, &repo_config);
+        }
+    }
+
     UserSettings::from_config(config)
         .map_err(|e| Error::Config(format!("Failed to create settings: {e}")))
 }
@@ -59,7 +85,7 @@ fn create_user_settings() -> Result<UserSettings> {
 impl JjWorkspace {
     /// Open a jj workspace at the given path
     pub fn open(path: &Path) -> Result<Self> {
-        let settings = create_user_settings()?;
+        let settings = create_user_settings(None)?;
 
         let workspace = Workspace::load(
             &settings,
@@ -69,6 +95,9 @@ impl JjWorkspace {
         )
         .map_err(|e| Error::Workspace(format!("Failed to open workspace: {e}")))?;
 
+        // Now that the repo is known, pick up its config too
+        let settings = create_user_settings(Some(workspace.repo_path()))?;
+
         Ok(Self {
             workspace,
             settings,
@@ -89,44 +118,194 @@ impl JjWorkspace {
             .map_err(|e| Error::Config(format!("Invalid git settings: {e}")))
     }
 
-    /// Get all local bookmarks
-    pub fn local_bookmarks(&self) -> Result<Vec<Bookmark>> {
+    /// Remote named by jj's `git.push` setting, if set
+    pub fn configured_push_remote(&self) -> Result<Option<String>> {
+        self.settings
+            .get_string("git.push")
+            .optional()
+            .map_err(|e| Error::Config(format!("Invalid git.push setting: {e}")))
+    }
+
+    /// String value of a jj config setting, if set
+    pub fn config_string(&self, key: &'static str) -> Result<Option<String>> {
+        self.settings
+            .get_string(key)
+            .optional()
+            .map_err(|e| Error::Config(format!("Invalid {key} setting: {e}")))
+    }
+
+    /// Boolean value of a jj config setting, if set
+    pub fn config_bool(&self, key: &'static str) -> Result<Option<bool>> {
+        self.settings
+            .get_bool(key)
+            .optional()
+            .map_err(|e| Error::Config(format!("Invalid {key} setting: {e}")))
+    }
+
+    /// Remotes named by jj's `git.fetch` setting (a name or a list of names)
+    ///
+    /// Returns an empty list if the setting is unset.
+    pub fn configured_fetch_remotes(&self) -> Result<Vec<String>> {
+        let value = self
+            .settings
+            .get_value("git.fetch")
+            .optional()
+            .map_err(|e| Error::Config(format!("Invalid git.fetch setting: {e}")))?;
+        let Some(value) = value else {
+            return Ok(Vec::new());
+        };
+
+        if let Some(name) = value.as_str() {
+            return Ok(vec![name.to_string()]);
+        }
+        value
+            .as_array()
+            .and_then(|names| {
+                names
+                    .iter()
+                    .map(|name| name.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| {
+                Error::Config("git.fetch must be a remote name or a list of names".to_string())
+            })
+    }
+
+    /// Editor command and arguments, chosen the way jj chooses one
+    ///
+    /// `$JJ_EDITOR` wins, then jj's `ui.editor` setting (a command line or a
+    /// list of arguments), then `$VISUAL` and `$EDITOR`, falling back to
+    /// `nano` (`notepad` on Windows).
+    pub fn editor_command(&self) -> Result<Vec<String>> {
+        // Command lines are split like a shell would, so quoted paths with
+        // spaces stay one argument
+        let split = |command: &str, source: &str| -> Result<Vec<String>> {
+            shell_words::split(command)
+                .map_err(|e| Error::Config(format!("Invalid editor command in {source}: {e}")))
+        };
+        let from_env = |var: &str| -> Result<Option<Vec<String>>> {
+            let Ok(value) = std::env::var(var) else {
+                return Ok(None);
+            };
+            let command = split(&value, &format!("${var}"))?;
+            Ok(Some(command).filter(|command| !command.is_empty()))
+        };
+
+        if let Some(command) = from_env("JJ_EDITOR")? {
+            return Ok(command);
+        }
+
+        let value = self
+            .settings
+            .get_value("ui.editor")
+            .optional()
+            .map_err(|e| Error::Config(format!("Invalid ui.editor setting: {e}")))?;
+        if let Some(value) = value {
+            let command = match value.as_str() {
+                Some(command) => Some(split(command, "ui.editor")?),
+                None => value.as_array().and_then(|args| {
+                    args.iter()
+                        .map(|arg| arg.as_str().map(str::to_string))
+                        .collect::<Option<Vec<_>>>()
+                }),
+            };
+            return command
+                .filter(|command| !command.is_empty())
+                .ok_or_else(|| {
+                    Error::Config("ui.editor must be a command or a list of arguments".to_string())
+                });
+        }
+
+        if let Some(command) = from_env("VISUAL")? {
+            return Ok(command);
+        }
+        Ok(from_env("EDITOR")?.unwrap_or_else(|| {
+            let default = if cfg!(windows) { "notepad" } else { "nano" };
+            vec![default.to_string()]
+        }))
+    }
+
+    /// Find commits that `jj git push` would refuse to push
+    ///
+    /// Checks every commit each bookmark would add to `remote` (those not
+    /// already reachable from trunk or the remote's bookmarks) for conflicts,
+    /// an empty description, or a match against jj's `git.private-commits`.
+    pub fn unpushable_commits(
+        &self,
+        bookmarks: &[&str],
+        remote: &str,
+    ) -> Result<Vec<UnpushableCommit>> {
         let repo = self.repo()?;
-        let view = repo.view();
 
-        let mut bookmarks = Vec::new();
-        for (name, target) in view.local_bookmarks() {
-            if let Some(commit_id) = target.as_normal() {
+        let private_revset = self
+            .settings
+            .get_string("git.private-commits")
+            .optional()
+            .map_err(|e| Error::Config(format!("Invalid git.private-commits setting: {e}")))?;
+        let private: HashSet<CommitId> = match private_revset {
+            Some(expr) => self.evaluate_revset(&repo, &expr)?.into_iter().collect(),
+            None => HashSet::new(),
+        };
+
+        // ::(trunk() | remote_bookmarks(remote=exact:<remote>)), built directly
+        // so bookmark and remote names never need quoting
+        let already_pushed = self
+            .parse_revset(&repo, "trunk()")?
+            .union(&RevsetExpression::remote_bookmarks(
+                StringExpression::all(),
+                StringExpression::exact(remote),
+                None,
+            ))
+            .ancestors();
+
+        let mut unpushable = Vec::new();
+        for &bookmark in bookmarks {
+            let expr = RevsetExpression::bookmarks(StringExpression::exact(bookmark))
+                .ancestors()
+                .minus(&already_pushed);
+            for commit_id in Self::evaluate_expression(repo.as_ref(), &expr)? {
                 let commit = repo
                     .store()
-                    .get_commit(commit_id)
+                    .get_commit(&commit_id)
                     .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
 
-                // Check if bookmark has remote tracking (excluding @git pseudo-remote)
-                let name_matcher = StringPattern::exact(name.as_str()).to_matcher();
-                let remote_matcher = StringMatcher::All;
-                let has_remote = view
-                    .remote_bookmarks_matching(&name_matcher, &remote_matcher)
-                    .any(|(symbol, _)| symbol.remote.as_str() != "git");
-
-                // Check if synced with remote (excluding @git pseudo-remote)
-                let is_synced = view
-                    .remote_bookmarks_matching(&name_matcher, &remote_matcher)
-                    .filter(|(symbol, _)| symbol.remote.as_str() != "git")
-                    .any(|(_, remote_ref)| {
-                        remote_ref
-                            .target
-                            .as_normal()
-                            .is_some_and(|id| id == commit_id)
-                    });
-
-                bookmarks.push(Bookmark {
-                    name: name.as_str().to_string(),
-                    commit_id: commit_id.hex(),
-                    change_id: commit.change_id().hex(),
-                    has_remote,
-                    is_synced,
-                });
+                let mut reasons = Vec::new();
+                if commit.has_conflict() {
+                    reasons.push(UnpushableReason::Conflicted);
+                }
+                if commit.description().trim().is_empty() {
+                    reasons.push(UnpushableReason::NoDescription);
+                }
+                if private.contains(&commit_id) {
+                    reasons.push(UnpushableReason::Private);
+                }
+                if reasons.is_empty() {
+                    continue;
+                }
+
+                let entry = Self::commit_to_log_entry(repo.as_ref(), &commit);
+                unpushable.extend(reasons.into_iter().map(|reason| UnpushableCommit {
+                    bookmark: bookmark.to_string(),
+                    commit: entry.clone(),
+                    reason,
+                }));
+            }
+        }
+
+        Ok(unpushable)
+    }
+
+    /// Get all local bookmarks
+    ///
+    /// Conflicted bookmarks (several targets, e.g. after concurrent edits) are
+    /// included with `is_conflicted` set; their `commit_id` is the first target.
+    pub fn local_bookmarks(&self) -> Result<Vec<Bookmark>> {
+        let repo = self.repo()?;
+
+        let mut bookmarks = Vec::new();
+        for (name, target) in repo.view().local_bookmarks() {
+            if let Some(bookmark) = Self::bookmark_from_target(repo.as_ref(), name, target)? {
+                bookmarks.push(bookmark);
             }
         }
 
@@ -136,18 +315,24 @@ impl JjWorkspace {
     /// Get a specific local bookmark
     pub fn get_local_bookmark(&self, name: &str) -> Result<Option<Bookmark>> {
         let repo = self.repo()?;
-        let view = repo.view();
-
         let ref_name = RefName::new(name);
-        let target = view.get_local_bookmark(ref_name);
-
-        if !target.is_present() {
-            return Ok(None);
-        }
+        let target = repo.view().get_local_bookmark(ref_name);
+        Self::bookmark_from_target(repo.as_ref(), ref_name, target)
+    }
 
-        let Some(commit_id) = target.as_normal() else {
+    /// Build a `Bookmark` from a local bookmark target
+    ///
+    /// Returns `None` if the bookmark is absent (e.g. deleted).
+    fn bookmark_from_target(
+        repo: &dyn Repo,
+        name: &RefName,
+        target: &RefTarget,
+    ) -> Result<Option<Bookmark>> {
+        let view = repo.view();
+        let Some(commit_id) = target.as_normal().or_else(|| target.added_ids().next()) else {
             return Ok(None);
         };
+        let is_conflicted = target.has_conflict();
 
         let commit = repo
             .store()
@@ -155,32 +340,44 @@ impl JjWorkspace {
             .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
 
         // Check if bookmark has remote tracking (excluding @git pseudo-remote)
-        let name_matcher = StringPattern::exact(name).to_matcher();
+        let name_matcher = StringPattern::exact(name.as_str()).to_matcher();
         let remote_matcher = StringMatcher::All;
         let has_remote = view
             .remote_bookmarks_matching(&name_matcher, &remote_matcher)
             .any(|(symbol, _)| symbol.remote.as_str() != "git");
 
         // Check if synced with remote (excluding @git pseudo-remote)
-        let is_synced = view
-            .remote_bookmarks_matching(&name_matcher, &remote_matcher)
-            .filter(|(symbol, _)| symbol.remote.as_str() != "git")
-            .any(|(_, remote_ref)| {
-                remote_ref
-                    .target
-                    .as_normal()
-                    .is_some_and(|id| id == commit_id)
-            });
+        let is_synced = !is_conflicted
+            && view
+                .remote_bookmarks_matching(&name_matcher, &remote_matcher)
+                .filter(|(symbol, _)| symbol.remote.as_str() != "git")
+                .any(|(_, remote_ref)| {
+                    remote_ref
+                        .target
+                        .as_normal()
+                        .is_some_and(|id| id == commit_id)
+                });
 
         Ok(Some(Bookmark {
-            name: name.to_string(),
+            name: name.as_str().to_string(),
             commit_id: commit_id.hex(),
             change_id: commit.change_id().hex(),
             has_remote,
             is_synced,
+            is_conflicted,
+            has_conflicts: commit.has_conflict(),
+            is_divergent: Self::is_divergent(repo, &commit),
         }))
     }
 
+    /// Whether a commit's change has more than one visible commit
+    fn is_divergent(repo: &dyn Repo, commit: &Commit) -> bool {
+        repo.resolve_change_id(commit.change_id())
+            .ok()
+            .flatten()
+            .is_some_and(|commits| commits.len() > 1)
+    }
+
     /// Get a remote bookmark
     pub fn get_remote_bookmark(&self, name: &str, remote: &str) -> Result<Option<Bookmark>> {
         let repo = self.repo()?;
@@ -210,15 +407,21 @@ impl JjWorkspace {
             change_id: commit.change_id().hex(),
             has_remote: true,
             is_synced: true,
+            is_conflicted: false,
+            has_conflicts: commit.has_conflict(),
+            is_divergent: Self::is_divergent(repo.as_ref(), &commit),
         }))
     }
 
     /// Get the change ID for a bookmark.
     ///
-    /// Used for rename detection in tracking.
+    /// Used for rename detection in tracking. Returns `None` for conflicted
+    /// bookmarks and divergent changes, whose change ID is ambiguous.
     pub fn get_change_id(&self, bookmark: &str) -> Result<Option<String>> {
-        self.get_local_bookmark(bookmark)
-            .map(|opt| opt.map(|b| b.change_id))
+        self.get_local_bookmark(bookmark).map(|opt| {
+            opt.filter(|b| !b.is_conflicted && !b.is_divergent)
+                .map(|b| b.change_id)
+        })
     }
 
     /// Find the bookmark name that points to a given change ID.
@@ -227,11 +430,18 @@ impl JjWorkspace {
     /// matches its stored `change_id`, we search for what bookmark now points
     /// to that `change_id`.
     pub fn get_bookmark_for_change_id(&self, change_id: &str) -> Result<Option<String>> {
-        let bookmarks = self.local_bookmarks()?;
-        Ok(bookmarks
+        let mut candidates = self
+            .local_bookmarks()?
             .into_iter()
-            .find(|b| b.change_id == change_id)
-            .map(|b| b.name))
+            .filter(|b| b.change_id == change_id);
+
+        // Ambiguous matches (divergent change, several bookmarks) aren't renames
+        match (candidates.next(), candidates.next()) {
+            (Some(bookmark), None) if !bookmark.is_conflicted && !bookmark.is_divergent => {
+                Ok(Some(bookmark.name))
+            }
+            _ => Ok(None),
+        }
     }
 
     /// Preferred remote order for detecting default branch
@@ -286,12 +496,40 @@ impl JjWorkspace {
     pub fn resolve_revset(&self, expr: &str) -> Result<Vec<LogEntry>> {
         let repo = self.repo()?;
 
-        // Parse and evaluate the revset
+        let mut entries = Vec::new();
+        for commit_id in self.evaluate_revset(&repo, expr)? {
+            let commit = repo
+                .store()
+                .get_commit(&commit_id)
+                .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+
+            entries.push(Self::commit_to_log_entry(repo.as_ref(), &commit));
+        }
+
+        Ok(entries)
+    }
+
+    /// Evaluate a revset expression to commit IDs
+    fn evaluate_revset(
+        &self,
+        repo: &Arc<jj_lib::repo::ReadonlyRepo>,
+        expr: &str,
+    ) -> Result<Vec<CommitId>> {
+        let expression = self.parse_revset(repo, expr)?;
+        Self::evaluate_expression(repo.as_ref(), &expression)
+    }
+
+    /// Parse a revset string, with ryu's `trunk()` alias
+    fn parse_revset(
+        &self,
+        repo: &Arc<jj_lib::repo::ReadonlyRepo>,
+        expr: &str,
+    ) -> Result<Arc<UserRevsetExpression>> {
         let extensions = RevsetExtensions::default();
         let mut aliases = revset::RevsetAliasesMap::default();
 
         // Define trunk() alias - checks remote HEAD first, then falls back to jj's default
-        let trunk_alias = Self::compute_trunk_alias(&repo);
+        let trunk_alias = Self::compute_trunk_alias(repo);
         aliases
             .insert("trunk()", trunk_alias)
             .expect("trunk() alias declaration is valid");
@@ -322,36 +560,35 @@ impl JjWorkspace {
         };
 
         let mut diagnostics = revset::RevsetDiagnostics::new();
-        let expression = revset::parse(&mut diagnostics, expr, &context)
-            .map_err(|e| Error::Parse(format!("Failed to parse revset: {e}")))?;
+        revset::parse(&mut diagnostics, expr, &context)
+            .map_err(|e| Error::Parse(format!("Failed to parse revset: {e}")))
+    }
 
+    /// Evaluate a parsed revset expression to commit IDs
+    fn evaluate_expression(
+        repo: &dyn Repo,
+        expression: &Arc<UserRevsetExpression>,
+    ) -> Result<Vec<CommitId>> {
         let empty_extensions: &[Box<dyn jj_lib::revset::SymbolResolverExtension>] = &[];
-        let symbol_resolver = SymbolResolver::new(repo.as_ref(), empty_extensions);
+        let symbol_resolver = SymbolResolver::new(repo, empty_extensions);
         let resolved = expression
-            .resolve_user_expression(repo.as_ref(), &symbol_resolver)
+            .resolve_user_expression(repo, &symbol_resolver)
             .map_err(|e| Error::Revset(format!("Failed to resolve revset: {e}")))?;
 
         let revset = resolved
-            .evaluate(repo.as_ref())
+            .evaluate(repo)
             .map_err(|e| Error::Revset(format!("Failed to evaluate revset: {e}")))?;
 
-        let mut entries = Vec::new();
-        for commit_id in revset.iter() {
-            let commit_id =
-                commit_id.map_err(|e| Error::Revset(format!("Failed to iterate revset: {e}")))?;
-            let commit = repo
-                .store()
-                .get_commit(&commit_id)
-                .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
-
-            entries.push(Self::commit_to_log_entry(&repo, &commit));
-        }
-
-        Ok(entries)
+        revset
+            .iter()
+            .map(|commit_id| {
+                commit_id.map_err(|e| Error::Revset(format!("Failed to iterate revset: {e}")))
+            })
+            .collect()
     }
 
     /// Convert a jj commit to a `LogEntry`
-    fn commit_to_log_entry(repo: &Arc<jj_lib::repo::ReadonlyRepo>, commit: &Commit) -> LogEntry {
+    fn commit_to_log_entry(repo: &dyn Repo, commit: &Commit) -> LogEntry {
         let view = repo.view();
 
         // Get bookmarks pointing to this commit
@@ -395,6 +632,7 @@ impl JjWorkspace {
         LogEntry {
             commit_id: commit.id().hex(),
             change_id: commit.change_id().hex(),
+
             author_name: author.name.clone(),
             author_email: author.email.clone(),
             description_first_line,
@@ -404,6 +642,8 @@ impl JjWorkspace {
             is_working_copy,
             authored_at,
             committed_at,
+            has_conflicts: commit.has_conflict(),
+            is_divergent: Self::is_divergent(repo, commit),
         }
     }
 
@@ -440,19 +680,720 @@ impl JjWorkspace {
         Ok(remotes)
     }
 
-    /// Fetch from a git remote
-    pub fn git_fetch(&mut self, remote: &str) -> Result<()> {
+    /// Fetch bookmarks matching any of `patterns` from a git remote
+    ///
+    /// Patterns use jj's string pattern syntax: `exact:main`, `glob:feat/*`,
+    /// or a bare name (exact match). Pass [`ALL_BOOKMARKS`] to fetch every
+    /// branch on the remote.
+    pub fn git_fetch(&mut self, remote: &str, patterns: &[&str]) -> Result<()> {
         let repo = self.repo()?;
         let git_settings = self.git_settings()?;
 
+        let bookmark_expr = StringExpression::union_all(
+            patterns
+                .iter()
+                .map(|pattern| {
+                    StringPattern::parse(pattern)
+                        .map(StringExpression::pattern)
+                        .map_err(|e| {
+                            Error::InvalidArgument(format!(
+                                "Invalid bookmark pattern '{pattern}': {e}"
+                            ))
+                        })
+                })
+                .collect::<Result<_>>()?,
+        );
+
         // Start a transaction for the fetch
         let mut tx = repo.start_transaction();
+        Self::fetch_into(tx.repo_mut(), &git_settings, remote, bookmark_expr)?;
+
+        // Commit the transaction
+        tx.commit(format!("fetch from {remote}"))
+            .map_err(|e| Error::Git(format!("Failed to commit fetch: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Fetch only the given bookmarks from a git remote
+    ///
+    /// Bookmarks that don't exist on the remote are skipped.
+    pub fn git_fetch_bookmarks(&mut self, remote: &str, bookmarks: &[&str]) -> Result<()> {
+        let repo = self.repo()?;
+        let git_settings = self.git_settings()?;
+
+        let mut tx = repo.start_transaction();
+        Self::fetch_into(
+            tx.repo_mut(),
+            &git_settings,
+            remote,
+            Self::bookmarks_expression(bookmarks),
+        )?;
+
+        tx.commit(format!("fetch {} from {remote}", bookmarks.join(", ")))
+            .map_err(|e| Error::Git(format!("Failed to commit fetch: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Fetch the given bookmarks and track their remote copies
+    ///
+    /// Like `jj bookmark track`, this creates a local bookmark at the fetched
+    /// commit, or merges it into an existing local bookmark of the same name.
+    /// Returns the bookmarks that don't exist on the remote.
+    pub fn git_fetch_and_track(&mut self, remote: &str, bookmarks: &[&str]) -> Result<Vec<String>> {
+        let repo = self.repo()?;
+        let git_settings = self.git_settings()?;
+        let remote_name = RemoteName::new(remote);
+
+        let mut tx = repo.start_transaction();
+        Self::fetch_into(
+            tx.repo_mut(),
+            &git_settings,
+            remote,
+            Self::bookmarks_expression(bookmarks),
+        )?;
+
+        let mut missing = Vec::new();
+        for name in bookmarks {
+            let symbol = RefName::new(name).to_remote_symbol(remote_name);
+            let remote_ref = tx.repo_mut().get_remote_bookmark(symbol);
+            if remote_ref.is_absent() {
+                missing.push((*name).to_string());
+            } else if !remote_ref.is_tracked() {
+                tx.repo_mut()
+                    .track_remote_bookmark(symbol)
+                    .map_err(|e| Error::Git(format!("Failed to track {name}@{remote}: {e}")))?;
+            }
+        }
+
+        tx.commit(format!("track {} from {remote}", bookmarks.join(", ")))
+            .map_err(|e| Error::Git(format!("Failed to commit fetch: {e}")))?;
+
+        Ok(missing)
+    }
+
+    /// Point local bookmarks at the given commits and track their remote copies
+    ///
+    /// Takes `(bookmark, commit id)` pairs. Unlike [`Self::git_fetch_and_track`]
+    /// the local target wins: a remote bookmark pointing elsewhere is marked
+    /// tracked without being merged in, so the next push replaces it. An
+    /// existing local bookmark is never moved: if one points at another
+    /// commit, nothing is changed and an error is returned.
+    pub fn set_tracked_bookmarks(
+        &mut self,
+        bookmarks: &[(&str, &str)],
+        remote: &str,
+    ) -> Result<()> {
+        let repo = self.repo()?;
+        let remote_name = RemoteName::new(remote);
+
+        let mut tx = repo.start_transaction();
+        for &(name, commit_hex) in bookmarks {
+            let commit_id = CommitId::try_from_hex(commit_hex)
+                .ok_or_else(|| Error::Internal(format!("Invalid commit id: {commit_hex}")))?;
+            let ref_name = RefName::new(name);
+            let existing = tx.repo_mut().get_local_bookmark(ref_name);
+            if existing.is_present() && existing.as_normal() != Some(&commit_id) {
+                return Err(Error::Workspace(format!(
+                    "bookmark '{name}' already points to another commit; move or delete it first"
+                )));
+            }
+            tx.repo_mut()
+                .set_local_bookmark_target(ref_name, RefTarget::normal(commit_id));
+
+            let symbol = ref_name.to_remote_symbol(remote_name);
+            let remote_ref = tx.repo_mut().get_remote_bookmark(symbol);
+            if remote_ref.is_present() && !remote_ref.is_tracked() {
+                tx.repo_mut().set_remote_bookmark(
+                    symbol,
+                    RemoteRef {
+                        target: remote_ref.target,
+                        state: RemoteRefState::Tracked,
+                    },
+                );
+            }
+        }
+
+        let names: Vec<&str> = bookmarks.iter().map(|&(name, _)| name).collect();
+        tx.commit(format!("set bookmarks {}", names.join(", ")))
+            .map_err(|e| Error::Git(format!("Failed to commit bookmarks: {e}")))?;
+
+        Ok(())
+    }
+
+    /// Full description of a commit
+    pub fn commit_description(&self, commit_id: &str) -> Result<String> {
+        let repo = self.repo()?;
+        let id = CommitId::try_from_hex(commit_id)
+            .ok_or_else(|| Error::Internal(format!("Invalid commit id: {commit_id}")))?;
+        let commit = repo
+            .store()
+            .get_commit(&id)
+            .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+        Ok(commit.description().to_string())
+    }
+
+    /// Contents of a file as of `revision` (e.g. `trunk()`), read from the
+    /// repo rather than the working copy
+    ///
+    /// Returns `None` if the revision is empty or the path isn't a regular
+    /// file there.
+    pub async fn read_file_at(&mut self, revision: &str, path: &str) -> Result<Option<String>> {
+        let repo = self.repo()?;
+        let Some(tree) = self.tree_at(&repo, revision)? else {
+            return Ok(None);
+        };
+        let repo_path = RepoPath::from_internal_string(path)
+            .map_err(|e| Error::InvalidArgument(format!("Invalid path '{path}': {e}")))?;
+        let value = tree
+            .path_value(repo_path)
+            .map_err(|e| Error::Workspace(format!("Failed to read {path}: {e}")))?;
+        let Ok(Some(TreeValue::File { id, .. })) = value.into_resolved() else {
+            return Ok(None);
+        };
+
+        let mut reader = repo
+            .store()
+            .read_file(repo_path, &id)
+            .await
+            .map_err(|e| Error::Workspace(format!("Failed to read {path}: {e}")))?;
+        let mut contents = Vec::new();
+        reader
+            .read_to_end(&mut contents)
+            .await
+            .map_err(|e| Error::Workspace(format!("Failed to read {path}: {e}")))?;
+        Ok(Some(String::from_utf8_lossy(&contents).into_owned()))
+    }
+
+    /// Paths of the regular files under `dir` as of `revision`, sorted
+    pub fn list_files_at(&self, revision: &str, dir: &str) -> Result<Vec<String>> {
+        let repo = self.repo()?;
+        let Some(tree) = self.tree_at(&repo, revision)? else {
+            return Ok(Vec::new());
+        };
+        let repo_dir = RepoPath::from_internal_string(dir)
+            .map_err(|e| Error::InvalidArgument(format!("Invalid path '{dir}': {e}")))?;
+        let matcher = PrefixMatcher::new([repo_dir]);
+
+        let mut paths: Vec<String> = tree
+            .entries_matching(&matcher)
+            .filter(|(_, value)| {
+                value
+                    .as_ref()
+                    .is_ok_and(|v| matches!(v.as_resolved(), Some(Some(TreeValue::File { .. }))))
+            })
+            .map(|(path, _)| path.as_internal_file_string().to_string())
+            .collect();
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Tree of the first commit `revision` resolves to
+    fn tree_at(
+        &self,
+        repo: &Arc<jj_lib::repo::ReadonlyRepo>,
+        revision: &str,
+    ) -> Result<Option<MergedTree>> {
+        let Some(commit_id) = self.evaluate_revset(repo, revision)?.into_iter().next() else {
+            return Ok(None);
+        };
+        let commit = repo
+            .store()
+            .get_commit(&commit_id)
+            .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+        Ok(Some(commit.tree()))
+    }
+
+    /// Path of the backing git repository's git directory
+    pub fn git_dir(&self) -> Result<PathBuf> {
+        let repo = self.repo()?;
+        let git_repo = git::get_git_repo(repo.store())
+            .map_err(|_| Error::Git("Not a git-backed repo".to_string()))?;
+        Ok(git_repo.git_dir().to_path_buf())
+    }
+
+    /// Contents of the blob a git ref points at, if the ref exists
+    ///
+    /// Some tools keep metadata in such refs (e.g. Graphite's
+    /// `refs/branch-metadata/<branch>`).
+    pub fn git_ref_blob(&self, ref_name: &str) -> Result<Option<Vec<u8>>> {
+        let repo = self.repo()?;
+        let git_repo = git::get_git_repo(repo.store())
+            .map_err(|_| Error::Git("Not a git-backed repo".to_string()))?;
+
+        let Some(mut reference) = git_repo
+            .try_find_reference(ref_name)
+            .map_err(|e| Error::Git(format!("Failed to read {ref_name}: {e}")))?
+        else {
+            return Ok(None);
+        };
+        let blob = reference
+            .peel_to_blob()
+            .map_err(|e| Error::Git(format!("Failed to read {ref_name}: {e}")))?;
+        Ok(Some(blob.data.clone()))
+    }
+
+    /// Start a new empty change on top of `revision` and edit it
+    ///
+    /// Like `jj new`: changes on disk are first snapshotted into the change
+    /// being left, then the working copy is updated to the new change.
+    pub async fn new_change_on(&mut self, revision: &str) -> Result<()> {
+        let old_repo = self.snapshot_working_copy().await?;
+        let parent_id = self
+            .evaluate_revset(&old_repo, revision)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Revset(format!("Revision '{revision}' not found")))?;
+        let parent = old_repo
+            .store()
+            .get_commit(&parent_id)
+            .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+
+        let mut tx = old_repo.start_transaction();
+        let commit = tx
+            .repo_mut()
+            .new_commit(vec![parent_id], parent.tree())
+            .write()
+            .map_err(|e| Error::Workspace(format!("Failed to create commit: {e}")))?;
+        tx.repo_mut()
+            .edit(self.workspace.workspace_name().to_owned(), &commit)
+            .map_err(|e| Error::Workspace(format!("Failed to edit new commit: {e}")))?;
+        // Rebases children of the abandoned working-copy commit, if any
+        tx.repo_mut()
+            .rebase_descendants()
+            .map_err(|e| Error::Workspace(format!("Failed to rebase descendants: {e}")))?;
+        let repo = tx
+            .commit("new empty commit")
+            .map_err(|e| Error::Workspace(format!("Failed to commit transaction: {e}")))?;
+
+        self.update_working_copy(&old_repo, &repo)
+    }
+
+    /// Snapshot changes on disk into this workspace's working-copy commit
+    ///
+    /// What every jj command does before touching the repo, so edits made
+    /// since the last jj command aren't lost. Returns the repo after the
+    /// snapshot.
+    async fn snapshot_working_copy(&mut self) -> Result<Arc<ReadonlyRepo>> {
+        let repo = self.repo()?;
+        let Some(wc_commit_id) = repo
+            .view()
+            .get_wc_commit_id(self.workspace.workspace_name())
+            .cloned()
+        else {
+            return Ok(repo);
+        };
+        let wc_commit = repo
+            .store()
+            .get_commit(&wc_commit_id)
+            .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+
+        let base_ignores = self.base_ignores(&repo)?;
+        let auto_track = self.auto_track_matcher()?;
+        let max_new_file_size = self
+            .settings
+            .get_value_with("snapshot.max-new-file-size", HumanByteSize::try_from)
+            .optional()
+            .map_err(|e| Error::Config(format!("Invalid snapshot.max-new-file-size: {e}")))?
+            .map_or(1024 * 1024, |size| size.0);
+        let options = SnapshotOptions {
+            base_ignores,
+            progress: None,
+            start_tracking_matcher: auto_track.as_ref(),
+            force_tracking_matcher: &NothingMatcher,
+            max_new_file_size,
+        };
+
+        let mut locked = self
+            .workspace
+            .start_working_copy_mutation()
+            .map_err(|e| Error::Workspace(format!("Failed to lock the working copy: {e}")))?;
+        if locked.locked_wc().old_tree().tree_ids() != wc_commit.tree_ids() {
+            return Err(Error::Workspace(
+                "The working copy is stale; run 'jj workspace update-stale' first".to_string(),
+            ));
+        }
+        let (tree, _) =
+            locked.locked_wc().snapshot(&options).await.map_err(|e| {
+                Error::Workspace(format!("Failed to snapshot the working copy: {e}"))
+            })?;
+
+        let repo = if tree.tree_ids() == wc_commit.tree_ids() {
+            repo
+        } else {
+            let mut tx = repo.start_transaction();
+            tx.set_is_snapshot(true);
+            tx.repo_mut()
+                .rewrite_commit(&wc_commit)
+                .set_tree(tree)
+                .write()
+                .map_err(|e| {
+                    Error::Workspace(format!("Failed to snapshot the working copy: {e}"))
+                })?;
+            tx.repo_mut()
+                .rebase_descendants()
+                .map_err(|e| Error::Workspace(format!("Failed to rebase descendants: {e}")))?;
+            tx.commit("snapshot working copy")
+                .map_err(|e| Error::Workspace(format!("Failed to commit transaction: {e}")))?
+        };
+        locked
+            .finish(repo.op_id().clone())
+            .map_err(|e| Error::Workspace(format!("Failed to save the working copy state: {e}")))?;
+        Ok(repo)
+    }
+
+    /// Ignore files that apply outside the tree: git's excludes file and
+    /// `info/exclude`, as jj reads them
+    fn base_ignores(&self, repo: &ReadonlyRepo) -> Result<Arc<GitIgnoreFile>> {
+        let excludes_file = git::get_git_repo(repo.store())
+            .ok()
+            .and_then(|git_repo| {
+                let path = git_repo.config_snapshot().string("core.excludesFile")?;
+                let path = path.to_string();
+                Some(match path.strip_prefix("~/").zip(dirs::home_dir()) {
+                    Some((rest, home)) => home.join(rest),
+                    None => PathBuf::from(path),
+                })
+            })
+            .or_else(|| {
+                std::env::var_os("XDG_CONFIG_HOME")
+                    .map(PathBuf::from)
+                    .or_else(|| dirs::home_dir().map(|home| home.join(".config")))
+                    .map(|config| config.join("git").join("ignore"))
+            });
+
+        let mut ignores = GitIgnoreFile::empty();
+        for file in excludes_file.into_iter().chain(
+            self.git_dir()
+                .ok()
+                .map(|dir| dir.join("info").join("exclude")),
+        ) {
+            ignores = ignores
+                .chain_with_file("", file)
+                .map_err(|e| Error::Workspace(format!("Failed to read ignore file: {e}")))?;
+        }
+        Ok(ignores)
+    }
+
+    /// Matcher for new files to start tracking, from jj's `snapshot.auto-track`
+    fn auto_track_matcher(&self) -> Result<Box<dyn Matcher>> {
+        let Some(fileset) = self
+            .settings
+            .get_string("snapshot.auto-track")
+            .optional()
+            .map_err(|e| Error::Config(format!("Invalid snapshot.auto-track setting: {e}")))?
+        else {
+            return Ok(Box::new(EverythingMatcher));
+        };
+        let workspace_root = self.workspace.workspace_root().to_path_buf();
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: workspace_root.clone(),
+            base: workspace_root,
+        };
+        let expression = fileset::parse(&mut FilesetDiagnostics::new(), &fileset, &path_converter)
+            .map_err(|e| Error::Config(format!("Invalid snapshot.auto-track setting: {e}")))?;
+        Ok(expression.to_matcher())
+    }
+
+    /// Update the files on disk after an operation moved this workspace's
+    /// working-copy commit from where it was in `old_repo`
+    fn update_working_copy(&mut self, old_repo: &ReadonlyRepo, repo: &ReadonlyRepo) -> Result<()> {
+        let name = self.workspace.workspace_name();
+        let Some(new_id) = repo.view().get_wc_commit_id(name) else {
+            return Ok(());
+        };
+        let old_id = old_repo.view().get_wc_commit_id(name);
+        if old_id == Some(new_id) {
+            return Ok(());
+        }
+
+        let get_commit = |repo: &ReadonlyRepo, id| {
+            repo.store()
+                .get_commit(id)
+                .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))
+        };
+        let new_commit = get_commit(repo, new_id)?;
+        let old_tree = old_id
+            .map(|id| get_commit(old_repo, id))
+            .transpose()?
+            .map(|commit| commit.tree());
+        self.workspace
+            .check_out(repo.op_id().clone(), old_tree.as_ref(), &new_commit)
+            .map_err(|e| Error::Workspace(format!("Failed to update the working copy: {e}")))?;
+        Ok(())
+    }
+
+    /// Rebase our commits onto commits someone else pushed to `bookmark`
+    ///
+    /// Picks up the remote commits [`Self::check_remote_divergence`] fetched,
+    /// without fetching again, then moves every commit of the bookmark that
+    /// the remote doesn't have (including ones amended since the last push)
+    /// onto them, together with their descendants, like `jj rebase -s`.
+    /// Commits of trunk and of the bookmarks below it in the stack stay put.
+    /// Our local bookmark follows its commits, so the next push is a
+    /// fast-forward; if it still wouldn't contain the remote commits
+    /// afterwards, nothing is recorded and `RemoteDiverged` is returned.
+    pub async fn rebase_onto_remote(&mut self, bookmark: &str, remote: &str) -> Result<()> {
+        let old_repo = self.snapshot_working_copy().await?;
+        let git_settings = self.git_settings()?;
+        let name = RefName::new(bookmark);
+        let remote_name = RemoteName::new(remote);
+        let symbol = name.to_remote_symbol(remote_name);
+
+        let old_remote = old_repo
+            .view()
+            .get_remote_bookmark(symbol)
+            .target
+            .as_normal()
+            .cloned()
+            .ok_or_else(|| {
+                Error::RemoteDiverged(format!(
+                    "{bookmark}@{remote} was never fetched before, so there is nothing to rebase; fetch it and rebase manually"
+                ))
+            })?;
+        let local = old_repo
+            .view()
+            .get_local_bookmark(name)
+            .as_normal()
+            .cloned()
+            .ok_or_else(|| Error::BookmarkNotFound(bookmark.to_string()))?;
+        // Built before the import so the trunk() alias isn't resolved against it
+        let trunk = self.parse_revset(&old_repo, "trunk()")?;
+
+        let mut tx = old_repo.start_transaction();
+        git::import_some_refs(tx.repo_mut(), &git_settings, |kind, imported| {
+            kind == GitRefKind::Bookmark && imported == symbol
+        })
+        .map_err(|e| Error::Git(format!("Failed to import {bookmark}@{remote}: {e}")))?;
+        let new_remote = tx
+            .repo_mut()
+            .get_remote_bookmark(symbol)
+            .target
+            .as_normal()
+            .cloned()
+            .ok_or_else(|| Error::BookmarkNotFound(format!("{bookmark}@{remote}")))?;
+
+        // The import conflicts a bookmark that moved on both sides; keep ours
+        // so it follows our commits through the rebase, unless the remote
+        // already contains them
+        if local != old_remote {
+            let target = if Self::is_ancestor(tx.repo_mut(), &local, &new_remote)? {
+                new_remote.clone()
+            } else {
+                local.clone()
+            };
+            tx.repo_mut()
+                .set_local_bookmark_target(name, RefTarget::normal(target));
+        }
+
+        // roots(::local ~ ::(new_remote | trunk() | bookmarks below this one))
+        let below = RevsetExpression::bookmarks(StringExpression::all())
+            .minus(&RevsetExpression::commit(local.clone()).descendants());
+        let keep = RevsetExpression::commit(new_remote.clone())
+            .union(&trunk)
+            .union(&below)
+            .ancestors();
+        let roots_expr = RevsetExpression::commit(local)
+            .ancestors()
+            .minus(&keep)
+            .roots();
+        let roots = Self::evaluate_expression(tx.repo_mut(), &roots_expr)?;
+        if !roots.is_empty() {
+            let location = MoveCommitsLocation {
+                new_parent_ids: vec![new_remote.clone()],
+                new_child_ids: vec![],
+                target: MoveCommitsTarget::Roots(roots),
+            };
+            move_commits(tx.repo_mut(), &location, &RebaseOptions::default())
+                .map_err(|e| Error::Workspace(format!("Failed to rebase: {e}")))?;
+        }
+        tx.repo_mut()
+            .rebase_descendants()
+            .map_err(|e| Error::Workspace(format!("Failed to rebase descendants: {e}")))?;
+
+        let rebased = tx.repo_mut().get_local_bookmark(name).as_normal().cloned();
+        let contains_remote = match &rebased {
+            Some(id) => Self::is_ancestor(tx.repo_mut(), &new_remote, id)?,
+            None => false,
+        };
+        if !contains_remote {
+            return Err(Error::RemoteDiverged(format!(
+                "could not rebase {bookmark} onto {bookmark}@{remote}; rebase it manually"
+            )));
+        }
+
+        let repo = tx
+            .commit(format!("rebase onto {bookmark}@{remote}"))
+            .map_err(|e| Error::Workspace(format!("Failed to commit transaction: {e}")))?;
+        self.update_working_copy(&old_repo, &repo)
+    }
+
+    /// Whether local `bookmark` contains everything on `bookmark@remote`,
+    /// so pushing it is a fast-forward
+    ///
+    /// A bookmark that was never pushed trivially does.
+    pub fn contains_remote_bookmark(&self, bookmark: &str, remote: &str) -> Result<bool> {
+        let repo = self.repo()?;
+        let name = RefName::new(bookmark);
+        let symbol = name.to_remote_symbol(RemoteName::new(remote));
+        let Some(remote_id) = repo.view().get_remote_bookmark(symbol).target.as_normal() else {
+            return Ok(repo.view().get_remote_bookmark(symbol).target.is_absent());
+        };
+        let Some(local_id) = repo.view().get_local_bookmark(name).as_normal() else {
+            return Ok(false);
+        };
+        Self::is_ancestor(repo.as_ref(), remote_id, local_id)
+    }
+
+    /// Whether `ancestor` is `descendant` or one of its ancestors
+    fn is_ancestor(repo: &dyn Repo, ancestor: &CommitId, descendant: &CommitId) -> Result<bool> {
+        repo.index()
+            .is_ancestor(ancestor, descendant)
+            .map_err(|e| Error::Workspace(format!("Failed to query index: {e}")))
+    }
+
+    /// Check whether pushing would overwrite commits someone else pushed
+    ///
+    /// Fetches the given bookmarks and reports each one whose remote copy has
+    /// moved to commits that weren't in this repo before the fetch (e.g. a
+    /// reviewer's "suggested change" commit). When nothing diverged, the fetch
+    /// is recorded so later pushes lease against up-to-date remote refs;
+    /// otherwise no jj operation is recorded, so jj's remote bookmarks keep
+    /// their old targets. The git remote-tracking refs were still updated by
+    /// the fetch, though, and jj picks them up on its next git import.
+    pub fn check_remote_divergence(
+        &mut self,
+        bookmarks: &[&str],
+        remote: &str,
+    ) -> Result<Vec<RemoteDivergence>> {
+        let repo = self.repo()?;
+        let git_settings = self.git_settings()?;
+        let remote_name = RemoteName::new(remote);
+
+        // Everything reachable from these heads is already known locally
+        let known_heads: Vec<CommitId> = repo.view().heads().iter().cloned().collect();
+        let before: Vec<Option<CommitId>> = bookmarks
+            .iter()
+            .map(|name| {
+                let symbol = RefName::new(name).to_remote_symbol(remote_name);
+                repo.view()
+                    .get_remote_bookmark(symbol)
+                    .target
+                    .as_normal()
+                    .cloned()
+            })
+            .collect();
+
+        let mut tx = repo.start_transaction();
+        Self::fetch_into(
+            tx.repo_mut(),
+            &git_settings,
+            remote,
+            Self::bookmarks_expression(bookmarks),
+        )?;
+
+        let mut diverged = Vec::new();
+        for (name, old_target) in bookmarks.iter().zip(before) {
+            let symbol = RefName::new(name).to_remote_symbol(remote_name);
+            let new_target = tx
+                .repo_mut()
+                .view()
+                .get_remote_bookmark(symbol)
+                .target
+                .as_normal()
+                .cloned();
+
+            let Some(new_target) = new_target else {
+                continue;
+            };
+            if old_target.as_ref() == Some(&new_target) {
+                continue;
+            }
 
-        let mut fetch = GitFetch::new(tx.repo_mut(), &git_settings)
+            let commits = Self::unknown_commits(tx.repo_mut(), &new_target, &known_heads)?;
+            if !commits.is_empty() {
+                diverged.push(RemoteDivergence {
+                    bookmark: (*name).to_string(),
+                    remote: remote.to_string(),
+                    commits,
+                });
+            }
+        }
+
+        if diverged.is_empty() && tx.repo_mut().has_changes() {
+            tx.commit(format!("fetch {} from {remote}", bookmarks.join(", ")))
+                .map_err(|e| Error::Git(format!("Failed to commit fetch: {e}")))?;
+        }
+
+        Ok(diverged)
+    }
+
+    /// Maximum number of unknown commits listed per diverged bookmark
+    const MAX_UNKNOWN_COMMITS: usize = 20;
+
+    /// Commits reachable from `head` that aren't ancestors of any known head, newest first
+    fn unknown_commits(
+        repo: &MutableRepo,
+        head: &CommitId,
+        known_heads: &[CommitId],
+    ) -> Result<Vec<LogEntry>> {
+        let index = repo.index();
+        let is_known = |id: &CommitId| -> Result<bool> {
+            for known in known_heads {
+                if index
+                    .is_ancestor(id, known)
+                    .map_err(|e| Error::Workspace(format!("Failed to query index: {e}")))?
+                {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        };
+
+        let mut entries = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        let mut pending = vec![head.clone()];
+        while let Some(id) = pending.pop() {
+            if entries.len() >= Self::MAX_UNKNOWN_COMMITS {
+                break;
+            }
+            if !seen.insert(id.clone()) || is_known(&id)? {
+                continue;
+            }
+            let commit = repo
+                .store()
+                .get_commit(&id)
+                .map_err(|e| Error::Workspace(format!("Failed to get commit: {e}")))?;
+            pending.extend(commit.parent_ids().iter().rev().cloned());
+            entries.push(Self::commit_to_log_entry(repo, &commit));
+        }
+
+        Ok(entries)
+    }
+
+    /// String expression matching exactly the given bookmark names
+    fn bookmarks_expression(bookmarks: &[&str]) -> StringExpression {
+        StringExpression::union_all(
+            bookmarks
+                .iter()
+                .map(|name| StringExpression::exact(*name))
+                .collect(),
+        )
+    }
+
+    /// Fetch bookmarks matching `bookmark_expr` and import them into `mut_repo`
+    fn fetch_into(
+        mut_repo: &mut MutableRepo,
+        git_settings: &GitSettings,
+        remote: &str,
+        bookmark_expr: StringExpression,
+    ) -> Result<()> {
+        let mut fetch = GitFetch::new(mut_repo, git_settings)
             .map_err(|e| Error::Git(format!("Failed to create fetch: {e}")))?;
 
         let remote_name = RemoteName::new(remote);
-        let refspecs = expand_fetch_refspecs(remote_name, StringExpression::all())
+        let refspecs = expand_fetch_refspecs(remote_name, bookmark_expr)
             .map_err(|e| Error::Git(format!("Failed to expand refspecs: {e}")))?;
         fetch
             .fetch(
@@ -469,34 +1410,74 @@ impl JjWorkspace {
             .import_refs()
             .map_err(|e| Error::Git(format!("Failed to import refs: {e}")))?;
 
-        // Commit the transaction
-        tx.commit(format!("fetch from {remote}"))
-            .map_err(|e| Error::Git(format!("Failed to commit fetch: {e}")))?;
-
         Ok(())
     }
 
     /// Push a bookmark to a remote
     pub fn git_push(&mut self, bookmark: &str, remote: &str) -> Result<()> {
+        let report = self.git_push_bookmarks(&[bookmark], remote)?;
+        if let Some((_, reason)) = report.rejected.first() {
+            return Err(Error::Git(format!(
+                "Failed to push: {bookmark} rejected: {reason}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Push several bookmarks to a remote in a single `git push`
+    ///
+    /// Every bookmark is leased against its remote-tracking ref. Bookmarks the
+    /// remote accepted get their remote-tracking refs updated in one jj
+    /// operation; rejected ones are listed in the report rather than failing
+    /// the whole call.
+    ///
+    /// Unlike `jj git push`, this doesn't refuse unpushable commits; check
+    /// [`Self::unpushable_commits`] before starting a submission.
+    pub fn git_push_bookmarks(&mut self, bookmarks: &[&str], remote: &str) -> Result<PushReport> {
+        self.push_bookmarks(bookmarks, remote, false)
+    }
+
+    /// Push several bookmarks to a remote with `git push --atomic`
+    ///
+    /// Either every ref is updated or none is. If the remote doesn't support
+    /// atomic pushes, this falls back to a regular batched push and reports
+    /// `atomic: false`.
+    pub fn git_push_bookmarks_atomic(
+        &mut self,
+        bookmarks: &[&str],
+        remote: &str,
+    ) -> Result<PushReport> {
+        self.push_bookmarks(bookmarks, remote, true)
+    }
+
+    fn push_bookmarks(&self, bookmarks: &[&str], remote: &str, atomic: bool) -> Result<PushReport> {
         let repo = self.repo()?;
         let git_settings = self.git_settings()?;
-
-        // Get the local bookmark target
         let view = repo.view();
-        let ref_name = RefName::new(bookmark);
-        let target = view.get_local_bookmark(ref_name);
+        let remote_name = RemoteName::new(remote);
 
-        if !target.is_present() {
-            return Err(Error::BookmarkNotFound(bookmark.to_string()));
-        }
+        let mut updates = Vec::with_capacity(bookmarks.len());
+        let mut targets = Vec::with_capacity(bookmarks.len());
+        for &bookmark in bookmarks {
+            // Get the local bookmark target
+            let ref_name = RefName::new(bookmark);
+            let target = view.get_local_bookmark(ref_name);
 
-        let new_target = target.as_normal().cloned();
+            if !target.is_present() {
+                return Err(Error::BookmarkNotFound(bookmark.to_string()));
+            }
 
-        // Get expected current target from remote tracking
-        let remote_name = RemoteName::new(remote);
-        let remote_symbol = ref_name.to_remote_symbol(remote_name);
-        let remote_ref = view.get_remote_bookmark(remote_symbol);
-        let expected_current_target = remote_ref.target.as_normal().cloned();
+            // Get expected current target from remote tracking
+            let remote_symbol = ref_name.to_remote_symbol(remote_name);
+            let remote_ref = view.get_remote_bookmark(remote_symbol);
+
+            updates.push(GitRefUpdate {
+                qualified_name: format!("refs/heads/{bookmark}").into(),
+                expected_current_target: remote_ref.target.as_normal().cloned(),
+                new_target: target.as_normal().cloned(),
+            });
+            targets.push((bookmark, target.clone()));
+        }
 
         // Start a transaction first - needed for export_refs
         let mut tx = repo.start_transaction();
@@ -506,45 +1487,156 @@ impl JjWorkspace {
         let export_stats = git::export_refs(tx.repo_mut())
             .map_err(|e| Error::Git(format!("Failed to export refs: {e}")))?;
 
-        // Check if our bookmark failed to export
-        if export_stats
+        // Check if any of our bookmarks failed to export
+        if let Some((symbol, _)) = export_stats
             .failed_bookmarks
             .iter()
-            .any(|(symbol, _)| symbol.name.as_str() == bookmark)
+            .find(|(symbol, _)| bookmarks.contains(&symbol.name.as_str()))
         {
             return Err(Error::Git(format!(
-                "Failed to export bookmark '{bookmark}' to git"
+                "Failed to export bookmark '{}' to git",
+                symbol.name.as_str()
             )));
         }
 
-        // Build the update for pushing
-        let update = GitRefUpdate {
-            qualified_name: format!("refs/heads/{bookmark}").into(),
-            expected_current_target,
-            new_target,
+        let mut report = PushReport::default();
+        let atomic_rejections = if atomic {
+            let git_backend = git::get_git_backend(repo.store())
+                .map_err(|e| Error::Git(format!("Failed to access git backend: {e}")))?;
+            atomic_push(
+                git_backend.git_repo_path(),
+                &git_settings.executable_path,
+                remote,
+                &updates,
+            )?
+        } else {
+            None
         };
 
-        git::push_updates(
+        let rejections: RefRejections = if let Some(rejected) = atomic_rejections {
+            report.atomic = true;
+            rejected
+        } else {
+            let stats = git::push_updates(
+                tx.repo_mut().base_repo().as_ref(),
+                &git_settings,
+                remote_name,
+                &updates,
+                RemoteCallbacks::default(),
+            )
+            .map_err(|e| Error::Git(format!("Failed to push: {e}")))?;
+            stats
+                .rejected
+                .into_iter()
+                .chain(stats.remote_rejected)
+                .map(|(name, reason)| (name.as_str().to_string(), reason))
+                .collect()
+        };
+
+        for (bookmark, target) in targets {
+            let qualified_name = format!("refs/heads/{bookmark}");
+            let rejection = rejections.iter().find(|(name, _)| *name == qualified_name);
+            if let Some((_, reason)) = rejection {
+                report.rejected.push((
+                    bookmark.to_string(),
+                    reason
+                        .clone()
+                        .unwrap_or_else(|| "rejected by remote".to_string()),
+                ));
+                continue;
+            }
+
+            // Update the remote tracking ref to match what we just pushed
+            // This ensures the bookmark shows as "synced" after push
+            let remote_symbol = RefName::new(bookmark).to_remote_symbol(remote_name);
+            let remote_ref = RemoteRef {
+                target,
+                state: RemoteRefState::Tracked,
+            };
+            tx.repo_mut().set_remote_bookmark(remote_symbol, remote_ref);
+            report.pushed.push(bookmark.to_string());
+        }
+
+        if !report.pushed.is_empty() {
+            tx.commit(format!("push {} to {remote}", report.pushed.join(", ")))
+                .map_err(|e| Error::Git(format!("Failed to commit push: {e}")))?;
+        }
+
+        Ok(report)
+    }
+
+    /// Delete branches from a remote
+    ///
+    /// Each deletion is leased on the remote-tracking ref, so a branch someone
+    /// else has moved since our last fetch is rejected rather than deleted.
+    /// Branches jj doesn't know on the remote are reported as rejected. Local
+    /// bookmarks are left alone.
+    pub fn delete_remote_branches(&self, branches: &[&str], remote: &str) -> Result<PushReport> {
+        let repo = self.repo()?;
+        let git_settings = self.git_settings()?;
+        let view = repo.view();
+        let remote_name = RemoteName::new(remote);
+
+        let mut report = PushReport::default();
+        let mut updates = Vec::with_capacity(branches.len());
+        for &branch in branches {
+            let remote_symbol = RefName::new(branch).to_remote_symbol(remote_name);
+            match view.get_remote_bookmark(remote_symbol).target.as_normal() {
+                Some(current) => updates.push(GitRefUpdate {
+                    qualified_name: format!("refs/heads/{branch}").into(),
+                    expected_current_target: Some(current.clone()),
+                    new_target: None,
+                }),
+                None => report
+                    .rejected
+                    .push((branch.to_string(), format!("not found on {remote}"))),
+            }
+        }
+        if updates.is_empty() {
+            return Ok(report);
+        }
+
+        let mut tx = repo.start_transaction();
+        let stats = git::push_updates(
             tx.repo_mut().base_repo().as_ref(),
             &git_settings,
             remote_name,
-            &[update],
+            &updates,
             RemoteCallbacks::default(),
         )
         .map_err(|e| Error::Git(format!("Failed to push: {e}")))?;
+        let rejections: RefRejections = stats
+            .rejected
+            .into_iter()
+            .chain(stats.remote_rejected)
+            .map(|(name, reason)| (name.as_str().to_string(), reason))
+            .collect();
 
-        // Update the remote tracking ref to match what we just pushed
-        // This ensures the bookmark shows as "synced" after push
-        let remote_ref = RemoteRef {
-            target: target.clone(),
-            state: RemoteRefState::Tracked,
-        };
-        tx.repo_mut().set_remote_bookmark(remote_symbol, remote_ref);
+        for update in &updates {
+            let qualified_name = update.qualified_name.as_str();
+            let branch = qualified_name.trim_start_matches("refs/heads/");
+            if let Some((_, reason)) = rejections.iter().find(|(name, _)| name == qualified_name) {
+                report.rejected.push((
+                    branch.to_string(),
+                    reason
+                        .clone()
+                        .unwrap_or_else(|| "rejected by remote".to_string()),
+                ));
+                continue;
+            }
 
-        tx.commit(format!("push {bookmark} to {remote}"))
-            .map_err(|e| Error::Git(format!("Failed to commit push: {e}")))?;
+            let remote_symbol = RefName::new(branch).to_remote_symbol(remote_name);
+            tx.repo_mut()
+                .set_remote_bookmark(remote_symbol, RemoteRef::absent());
+            report.pushed.push(branch.to_string());
+        }
 
-        Ok(())
+        if !report.pushed.is_empty() {
+            tx.commit(format!("delete {} from {remote}", report.pushed.join(", ")))
+                .map_err(|e| Error::Git(format!("Failed to commit push: {e}")))?;
+        }
+
+        Ok(report)
     }
 
     /// Get the default branch name by checking remote HEAD first, then common names
@@ -575,6 +1667,129 @@ impl JjWorkspace {
     pub fn workspace_root(&self) -> &Path {
         self.workspace.workspace_root()
     }
+
+    /// Get the name of this workspace (e.g. "default")
+    pub fn workspace_name(&self) -> String {
+        self.workspace.workspace_name().as_str().to_string()
+    }
+
+    /// Get the names of all workspaces of the repo, sorted
+    ///
+    /// These are the workspaces with a working-copy commit in the current view.
+    pub fn workspace_names(&self) -> Result<Vec<String>> {
+        let repo = self.repo()?;
+        let mut names: Vec<String> = repo
+            .view()
+            .wc_commit_ids()
+            .keys()
+            .map(|name| name.as_str().to_string())
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Get the id (hex) of the jj operation the repo is currently at
+    pub fn head_operation_id(&self) -> Result<String> {
+        Ok(self.repo()?.operation().id().hex())
+    }
+
+    /// Find the closest operation at or before `op_str` that satisfies `is_wanted`
+    ///
+    /// `op_str` is any jj operation expression (`@`, `@-`, an id prefix, ...).
+    /// Ancestors are walked newest first; returns the matching operation id (hex).
+    pub fn nearest_operation(
+        &self,
+        op_str: &str,
+        is_wanted: impl Fn(&str) -> bool,
+    ) -> Result<Option<String>> {
+        let repo = self.repo()?;
+        let op = op_walk::resolve_op_with_repo(&repo, op_str)
+            .map_err(|e| Error::InvalidArgument(format!("Invalid operation '{op_str}': {e}")))?;
+
+        for ancestor in op_walk::walk_ancestors(&[op]) {
+            let ancestor = ancestor
+                .map_err(|e| Error::Workspace(format!("Failed to read operation log: {e}")))?;
+            let id = ancestor.id().hex();
+            if is_wanted(&id) {
+                return Ok(Some(id));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Rejected refs from a push, with the reason reported by git (if any)
+type RefRejections = Vec<(String, Option<String>)>;
+
+/// Push ref updates with `git push --atomic`, leasing each ref
+///
+/// Returns the rejected refs (all of them when the remote refuses the push),
+/// or `None` if the remote doesn't support atomic pushes.
+fn atomic_push(
+    git_dir: &Path,
+    git_executable: &Path,
+    remote: &str,
+    updates: &[GitRefUpdate],
+) -> Result<Option<RefRejections>> {
+    let mut command = Command::new(git_executable);
+    command
+        .args(["-c", "core.fsmonitor=false"])
+        .arg("--git-dir")
+        .arg(git_dir)
+        .env("LC_ALL", "C")
+        .args(["push", "--atomic", "--porcelain", "--no-verify"]);
+    for update in updates {
+        let expected = update
+            .expected_current_target
+            .as_ref()
+            .map(CommitId::hex)
+            .unwrap_or_default();
+        command.arg(format!(
+            "--force-with-lease={}:{expected}",
+            update.qualified_name.as_str()
+        ));
+    }
+    command.args(["--", remote]);
+    for update in updates {
+        let source = update
+            .new_target
+            .as_ref()
+            .map(CommitId::hex)
+            .unwrap_or_default();
+        command.arg(format!("{source}:{}", update.qualified_name.as_str()));
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| Error::Git(format!("Failed to run git push: {e}")))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("does not support --atomic") {
+        return Ok(None);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !stdout.starts_with("To ") {
+        return Err(Error::Git(format!("Failed to push: {}", stderr.trim())));
+    }
+    Ok(Some(parse_porcelain_rejections(&stdout)))
+}
+
+/// Collect the rejected refs from `git push --porcelain` output
+fn parse_porcelain_rejections(stdout: &str) -> RefRejections {
+    stdout
+        .lines()
+        .skip(1)
+        .take_while(|line| *line != "Done")
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let (flag, refspec, summary) = (fields.next()?, fields.next()?, fields.next());
+            if flag != "!" {
+                return None;
+            }
+            let (_, reference) = refspec.split_once(':')?;
+            Some((reference.to_string(), summary.map(str::to_string)))
+        })
+        .collect()
 }
 
 /// Select a remote from a list of available remotes
@@ -583,11 +1798,23 @@ impl JjWorkspace {
 /// - If only one remote exists, use it
 /// - If multiple remotes exist, prefer "origin", else use first
 pub fn select_remote(remotes: &[GitRemote], specified: Option<&str>) -> Result<String> {
+    select_remote_with_default(remotes, specified, None)
+}
+
+/// Select a remote, falling back to a configured default (e.g. jj's `git.push`)
+///
+/// An explicitly specified remote wins over the configured one; either must
+/// exist. Without both, prefers the only remote, then "origin", then the first.
+pub fn select_remote_with_default(
+    remotes: &[GitRemote],
+    specified: Option<&str>,
+    configured: Option<&str>,
+) -> Result<String> {
     if remotes.is_empty() {
         return Err(Error::NoSupportedRemotes);
     }
 
-    if let Some(name) = specified {
+    if let Some(name) = specified.or(configured) {
         if !remotes.iter().any(|r| r.name == name) {
             return Err(Error::RemoteNotFound(name.to_string()));
         }
@@ -629,7 +1856,253 @@ mod tests {
     #[test]
     fn test_create_user_settings() {
         // Should not panic even without user config
-        let settings = create_user_settings();
+        let settings = create_user_settings(None);
         assert!(settings.is_ok());
     }
+
+    #[test]
+    fn test_parse_porcelain_rejections() {
+        let stdout = "To github.com:user/repo.git\n\
+                      !\t1234abcd:refs/heads/feat-a\t[rejected] (stale info)\n\
+                      !\t5678abcd:refs/heads/feat-b\t[remote rejected] (atomic push failed)\n\
+                      *\t9abcdef0:refs/heads/feat-c\t[new branch]\n\
+                      Done\n";
+
+        let rejected = parse_porcelain_rejections(stdout);
+        assert_eq!(
+            rejected,
+            vec![
+                (
+                    "refs/heads/feat-a".to_string(),
+                    Some("[rejected] (stale info)".to_string())
+                ),
+                (
+                    "refs/heads/feat-b".to_string(),
+                    Some("[remote rejected] (atomic push failed)".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_change_on_snapshots_and_checks_out() {
+        let temp = tempfile::tempdir().unwrap();
+        let settings = create_user_settings(None).unwrap();
+        Workspace::init_internal_git(&settings, temp.path()).unwrap();
+        let mut workspace = JjWorkspace::open(temp.path()).unwrap();
+
+        std::fs::write(temp.path().join("file.txt"), "edited\n").unwrap();
+        workspace.new_change_on("@").await.unwrap();
+
+        let repo = workspace.repo().unwrap();
+        let parent = workspace.tree_at(&repo, "@-").unwrap().unwrap();
+        assert!(
+            parent
+                .path_value(RepoPath::from_internal_string("file.txt").unwrap())
+                .unwrap()
+                .is_present(),
+            "the edit should be snapshotted into the old change"
+        );
+        assert_eq!(workspace.resolve_revset("@ & empty()").unwrap().len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("file.txt")).unwrap(),
+            "edited\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rebase_onto_remote_uses_fetched_git_ref() {
+        use jj_lib::ref_name::GitRefName;
+
+        let temp = tempfile::tempdir().unwrap();
+        let settings = create_user_settings(None).unwrap();
+        let (_, repo) = Workspace::init_internal_git(&settings, temp.path()).unwrap();
+
+        // feat@origin at `pushed`; we added `ours` on top, someone else `theirs`
+        let mut tx = repo.start_transaction();
+        let write = |tx: &mut jj_lib::transaction::Transaction, parent: CommitId, text: &str| {
+            let tree = tx.repo().store().empty_merged_tree();
+            tx.repo_mut()
+                .new_commit(vec![parent], tree)
+                .set_description(text)
+                .write()
+                .unwrap()
+        };
+        let root = repo.store().root_commit_id().clone();
+        let pushed = write(&mut tx, root, "pushed");
+        let ours = write(&mut tx, pushed.id().clone(), "ours");
+        let theirs = write(&mut tx, pushed.id().clone(), "theirs");
+        let name = RefName::new("feat");
+        let symbol = name.to_remote_symbol(RemoteName::new("origin"));
+        tx.repo_mut()
+            .set_local_bookmark_target(name, RefTarget::normal(ours.id().clone()));
+        tx.repo_mut().set_remote_bookmark(
+            symbol,
+            RemoteRef {
+                target: RefTarget::normal(pushed.id().clone()),
+                state: RemoteRefState::Tracked,
+            },
+        );
+        tx.repo_mut().set_git_ref_target(
+            GitRefName::new("refs/remotes/origin/feat"),
+            RefTarget::normal(pushed.id().clone()),
+        );
+        let repo = tx.commit("set up").unwrap();
+
+        // What the divergence check's fetch leaves behind
+        let git_repo = git::get_git_repo(repo.store()).unwrap();
+        git_repo
+            .reference(
+                "refs/remotes/origin/feat",
+                gix::ObjectId::from_bytes_or_panic(theirs.id().as_bytes()),
+                gix::refs::transaction::PreviousValue::Any,
+                "fetch",
+            )
+            .unwrap();
+
+        let mut workspace = JjWorkspace::open(temp.path()).unwrap();
+        workspace.new_change_on("feat").await.unwrap();
+        workspace
+            .rebase_onto_remote("feat", "origin")
+            .await
+            .unwrap();
+
+        let remote = workspace
+            .get_remote_bookmark("feat", "origin")
+            .unwrap()
+            .unwrap();
+        assert_eq!(remote.commit_id, theirs.id().hex());
+        let local = workspace.get_local_bookmark("feat").unwrap().unwrap();
+        assert!(!local.is_conflicted);
+        let parents = workspace.resolve_revset("feat-").unwrap();
+        assert_eq!(parents[0].commit_id, theirs.id().hex());
+        assert_eq!(
+            workspace.resolve_revset("@-").unwrap()[0].commit_id,
+            local.commit_id
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rebase_onto_remote_after_amending_pushed_commit() {
+        use jj_lib::ref_name::GitRefName;
+
+        let temp = tempfile::tempdir().unwrap();
+        let settings = create_user_settings(None).unwrap();
+        let (_, repo) = Workspace::init_internal_git(&settings, temp.path()).unwrap();
+
+        // feat and feat@origin at `pushed`
+        let mut tx = repo.start_transaction();
+        let tree = repo.store().empty_merged_tree();
+        let pushed = tx
+            .repo_mut()
+            .new_commit(vec![repo.store().root_commit_id().clone()], tree.clone())
+            .set_description("pushed")
+            .write()
+            .unwrap();
+        let name = RefName::new("feat");
+        let symbol = name.to_remote_symbol(RemoteName::new("origin"));
+        tx.repo_mut()
+            .set_local_bookmark_target(name, RefTarget::normal(pushed.id().clone()));
+        tx.repo_mut().set_remote_bookmark(
+            symbol,
+            RemoteRef {
+                target: RefTarget::normal(pushed.id().clone()),
+                state: RemoteRefState::Tracked,
+            },
+        );
+        tx.repo_mut().set_git_ref_target(
+            GitRefName::new("refs/remotes/origin/feat"),
+            RefTarget::normal(pushed.id().clone()),
+        );
+        let repo = tx.commit("set up").unwrap();
+
+        // We amend `pushed`, which hides it and moves feat along
+        let mut tx = repo.start_transaction();
+        tx.repo_mut()
+            .rewrite_commit(&pushed)
+            .set_description("pushed, amended")
+            .write()
+            .unwrap();
+        tx.repo_mut().rebase_descendants().unwrap();
+        let repo = tx.commit("amend").unwrap();
+
+        // Someone else pushed `theirs` on top of `pushed`; it only exists in git
+        let mut tx = repo.start_transaction();
+        let theirs = tx
+            .repo_mut()
+            .new_commit(vec![pushed.id().clone()], tree)
+            .set_description("theirs")
+            .write()
+            .unwrap();
+        drop(tx);
+        let git_repo = git::get_git_repo(repo.store()).unwrap();
+        git_repo
+            .reference(
+                "refs/remotes/origin/feat",
+                gix::ObjectId::from_bytes_or_panic(theirs.id().as_bytes()),
+                gix::refs::transaction::PreviousValue::Any,
+                "fetch",
+            )
+            .unwrap();
+
+        let mut workspace = JjWorkspace::open(temp.path()).unwrap();
+        workspace
+            .rebase_onto_remote("feat", "origin")
+            .await
+            .unwrap();
+
+        assert!(
+            workspace
+                .contains_remote_bookmark("feat", "origin")
+                .unwrap()
+        );
+        let local = workspace.resolve_revset("feat").unwrap();
+        assert_eq!(local[0].description_first_line, "pushed, amended");
+        let parents = workspace.resolve_revset("feat-").unwrap();
+        assert_eq!(parents[0].commit_id, theirs.id().hex());
+    }
+
+    #[test]
+    fn test_set_tracked_bookmarks_keeps_existing_bookmark() {
+        let temp = tempfile::tempdir().unwrap();
+        let settings = create_user_settings(None).unwrap();
+        let (_, repo) = Workspace::init_internal_git(&settings, temp.path()).unwrap();
+
+        let mut tx = repo.start_transaction();
+        let tree = repo.store().empty_merged_tree();
+        let root = repo.store().root_commit_id().clone();
+        let first = tx
+            .repo_mut()
+            .new_commit(vec![root.clone()], tree.clone())
+            .set_description("first")
+            .write()
+            .unwrap();
+        let second = tx
+            .repo_mut()
+            .new_commit(vec![root], tree)
+            .set_description("second")
+            .write()
+            .unwrap();
+        tx.repo_mut()
+            .set_local_bookmark_target(RefName::new("feat"), RefTarget::normal(first.id().clone()));
+        tx.commit("set up").unwrap();
+
+        let mut workspace = JjWorkspace::open(temp.path()).unwrap();
+        let first_hex = first.id().hex();
+        let second_hex = second.id().hex();
+        assert!(
+            workspace
+                .set_tracked_bookmarks(&[("feat", &second_hex), ("other", &second_hex)], "origin")
+                .is_err()
+        );
+        let feat = workspace.get_local_bookmark("feat").unwrap().unwrap();
+        assert_eq!(feat.commit_id, first_hex);
+        assert!(workspace.get_local_bookmark("other").unwrap().is_none());
+
+        // Already pointing at the commit is fine
+        workspace
+            .set_tracked_bookmarks(&[("feat", &first_hex), ("other", &second_hex)], "origin")
+            .unwrap();
+        assert!(workspace.get_local_bookmark("other").unwrap().is_some());
+    }
 }
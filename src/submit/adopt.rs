@@ -0,0 +1,164 @@
+//! Stack reconstruction from existing PRs
+//!
+//! Used to pick up a stack that was submitted from another machine or by a
+//! teammate: the layout comes from the stack data ryu embeds in its comments,
+//! or failing that from following each PR's base branch down to trunk.
+
+use crate::error::{Error, Result};
+use crate::platform::PlatformService;
+use crate::submit::execute::{StackCommentData, parse_stack_comment_data};
+use crate::types::{PrState, PullRequest};
+use std::collections::HashSet;
+
+/// Where the layout of an adopted stack came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackSource {
+    /// Stack data embedded in a ryu comment or description section
+    StackComment,
+    /// Inferred by following base branches
+    BaseRefs,
+}
+
+/// A stack reconstructed from the platform
+#[derive(Debug, Clone)]
+pub struct AdoptedStack {
+    /// Branch the bottom open PR targets
+    pub base_branch: String,
+    /// Open PRs, ordered root to leaf
+    pub prs: Vec<PullRequest>,
+    /// PRs listed in the stack data that have since been merged or closed
+    pub skipped: Vec<PullRequest>,
+    /// How the layout was determined
+    pub source: StackSource,
+}
+
+/// Reconstruct the stack a PR belongs to
+///
+/// Prefers the stack data ryu embeds in its comments (or description
+/// section), which covers the whole stack from any of its PRs. Without it,
+/// the chain is inferred by following base branches from `pr_number` down to
+/// `trunk`, so only PRs below the given one are found - pass the top PR.
+pub async fn discover_stack(
+    platform: &dyn PlatformService,
+    pr_number: u64,
+    trunk: &str,
+) -> Result<AdoptedStack> {
+    let pr = fetch_pr(platform, pr_number).await?;
+
+    if let Some(data) = embedded_stack_data(platform, pr_number).await? {
+        if data.stack.iter().any(|item| item.pr_number == pr_number) {
+            return from_stack_data(platform, &data, pr).await;
+        }
+    }
+
+    from_base_refs(platform, pr, trunk).await
+}
+
+/// Reconstruct the part of a stack below (and including) a PR
+///
+/// Follows base branches from `pr_number` down to `trunk`, ignoring any
+/// embedded stack data, so PRs stacked on top of it are left out.
+pub async fn discover_stack_below(
+    platform: &dyn PlatformService,
+    pr_number: u64,
+    trunk: &str,
+) -> Result<AdoptedStack> {
+    let pr = fetch_pr(platform, pr_number).await?;
+
+    from_base_refs(platform, pr, trunk).await
+}
+
+async fn fetch_pr(platform: &dyn PlatformService, pr_number: u64) -> Result<PullRequest> {
+    platform
+        .get_pr(pr_number)
+        .await?
+        .ok_or_else(|| Error::Platform(format!("PR #{pr_number} not found")))
+}
+
+/// Stack data from the PR's ryu comment, or else its description
+async fn embedded_stack_data(
+    platform: &dyn PlatformService,
+    pr_number: u64,
+) -> Result<Option<StackCommentData>> {
+    let comments = platform.list_pr_comments(pr_number).await?;
+    if let Some(data) = comments
+        .iter()
+        .find_map(|comment| parse_stack_comment_data(&comment.body))
+    {
+        return Ok(Some(data));
+    }
+
+    let body = platform.get_pr_body(pr_number).await?;
+    Ok(parse_stack_comment_data(&body))
+}
+
+/// Re-fetch every PR listed in the stack data for its current state
+async fn from_stack_data(
+    platform: &dyn PlatformService,
+    data: &StackCommentData,
+    target: PullRequest,
+) -> Result<AdoptedStack> {
+    let mut prs = Vec::new();
+    let mut skipped = Vec::new();
+    for item in &data.stack {
+        let pr = if item.pr_number == target.number {
+            Some(target.clone())
+        } else {
+            platform.get_pr(item.pr_number).await?
+        };
+        match pr {
+            Some(pr) if pr.state == PrState::Open => prs.push(pr),
+            Some(pr) => skipped.push(pr),
+            // Deleted or inaccessible - nothing to adopt
+            None => {}
+        }
+    }
+
+    let base_branch = prs
+        .first()
+        .map_or_else(|| data.base_branch.clone(), |pr| pr.base_ref.clone());
+
+    Ok(AdoptedStack {
+        base_branch,
+        prs,
+        skipped,
+        source: StackSource::StackComment,
+    })
+}
+
+/// Follow base branches down from `top` until trunk or a branch without an
+/// open PR
+async fn from_base_refs(
+    platform: &dyn PlatformService,
+    top: PullRequest,
+    trunk: &str,
+) -> Result<AdoptedStack> {
+    let mut seen = HashSet::from([top.head_ref.clone()]);
+    let mut chain = vec![top];
+
+    loop {
+        let base = &chain[chain.len() - 1].base_ref;
+        if base == trunk {
+            break;
+        }
+        match platform.find_existing_pr(base).await? {
+            Some(parent) if seen.insert(parent.head_ref.clone()) => chain.push(parent),
+            _ => break,
+        }
+    }
+
+    chain.reverse();
+    let (prs, skipped) = chain
+        .into_iter()
+        .partition::<Vec<_>, _>(|pr| pr.state == PrState::Open);
+    let base_branch = prs
+        .first()
+        .map_or_else(|| trunk.to_string(), |pr| pr.base_ref.clone());
+
+    Ok(AdoptedStack {
+        base_branch,
+        prs,
+        skipped,
+        source: StackSource::BaseRefs,
+    })
+}
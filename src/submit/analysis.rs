@@ -184,6 +184,42 @@ pub fn generate_pr_title(
     }
 }
 
+/// Refuse to submit conflicted bookmarks, conflicted commits, or divergent changes
+///
+/// Every problem in the analyzed segments is listed with how to fix it.
+pub fn check_submittable(analysis: &SubmissionAnalysis) -> Result<()> {
+    let mut problems = Vec::new();
+
+    for segment in &analysis.segments {
+        let name = &segment.bookmark.name;
+        if segment.bookmark.is_conflicted {
+            problems.push(format!(
+                "{name}: bookmark is conflicted (it points at several commits); pick one with 'jj bookmark set {name} -r <revision>'"
+            ));
+        }
+        for change in &segment.changes {
+            let commit = change.commit_id.get(..8).unwrap_or(&change.commit_id);
+            if change.has_conflicts {
+                problems.push(format!(
+                    "{name}: commit {commit} has conflicts; resolve them with 'jj resolve' or by editing the files"
+                ));
+            }
+            if change.is_divergent {
+                let change_id = change.change_id.get(..8).unwrap_or(&change.change_id);
+                problems.push(format!(
+                    "{name}: change {change_id} is divergent (several commits share it); abandon the unwanted copy with 'jj abandon <commit>'"
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::NotSubmittable(problems.join("\n")))
+    }
+}
+
 /// Create narrowed segments from resolved bookmarks and analysis
 ///
 /// This bridges CLI bookmark selection with submission planning.
@@ -221,6 +257,9 @@ mod tests {
             change_id: format!("{name}_change"),
             has_remote: false,
             is_synced: false,
+            is_conflicted: false,
+            has_conflicts: false,
+            is_divergent: false,
         }
     }
 
@@ -237,6 +276,8 @@ mod tests {
             is_working_copy: false,
             authored_at: Utc::now(),
             committed_at: Utc::now(),
+            has_conflicts: false,
+            is_divergent: false,
         }
     }
 
@@ -333,6 +374,59 @@ mod tests {
         assert!(matches!(result, Err(Error::BookmarkNotFound(_))));
     }
 
+    fn make_analysis(segments: Vec<NarrowedBookmarkSegment>) -> SubmissionAnalysis {
+        SubmissionAnalysis {
+            target_bookmark: segments
+                .last()
+                .map(|s| s.bookmark.name.clone())
+                .unwrap_or_default(),
+            segments,
+        }
+    }
+
+    #[test]
+    fn test_check_submittable_accepts_clean_stack() {
+        let analysis = make_analysis(vec![NarrowedBookmarkSegment {
+            bookmark: make_bookmark("feat-a"),
+            changes: vec![make_log_entry("First change", &["feat-a"])],
+        }]);
+        assert!(check_submittable(&analysis).is_ok());
+    }
+
+    #[test]
+    fn test_check_submittable_lists_every_problem() {
+        let mut conflicted = make_bookmark("feat-a");
+        conflicted.is_conflicted = true;
+        let mut conflict_commit = make_log_entry("Second change", &["feat-b"]);
+        conflict_commit.has_conflicts = true;
+        let mut divergent = make_log_entry("Third change", &["feat-c"]);
+        divergent.is_divergent = true;
+
+        let analysis = make_analysis(vec![
+            NarrowedBookmarkSegment {
+                bookmark: conflicted,
+                changes: vec![make_log_entry("First change", &["feat-a"])],
+            },
+            NarrowedBookmarkSegment {
+                bookmark: make_bookmark("feat-b"),
+                changes: vec![conflict_commit],
+            },
+            NarrowedBookmarkSegment {
+                bookmark: make_bookmark("feat-c"),
+                changes: vec![divergent],
+            },
+        ]);
+
+        let Err(Error::NotSubmittable(msg)) = check_submittable(&analysis) else {
+            panic!("expected NotSubmittable");
+        };
+        let lines: Vec<&str> = msg.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("feat-a: bookmark is conflicted"));
+        assert!(lines[1].starts_with("feat-b: commit Second c has conflicts"));
+        assert!(lines[2].starts_with("feat-c: change Third ch is divergent"));
+    }
+
     #[test]
     fn test_get_base_branch_first() {
         let segments = vec![NarrowedBookmarkSegment {
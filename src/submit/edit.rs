@@ -0,0 +1,241 @@
+//! Editing the PRs a plan will create
+//!
+//! Like `jj describe`, but for the title and description of every PR in the
+//! plan at once: the PRs are written to a single file, one section per
+//! bookmark, and the edited file is read back into the plan's `PrToCreate`
+//! steps.
+
+use crate::error::{Error, Result};
+use crate::submit::plan::{ExecutionStep, SubmissionPlan};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// Prefix of lines ignored when the file is read back
+pub const EDIT_COMMENT_PREFIX: &str = "RYU:";
+
+/// Header starting each PR's section, followed by the bookmark name
+const SECTION_HEADER: &str = "RYU: bookmark ";
+
+/// Whether the plan creates any PRs
+pub fn has_prs_to_create(plan: &SubmissionPlan) -> bool {
+    plan.execution_steps
+        .iter()
+        .any(|step| matches!(step, ExecutionStep::CreatePr(_)))
+}
+
+/// The file listing every PR the plan creates, for the user to edit
+pub fn format_pr_edits(plan: &SubmissionPlan) -> String {
+    let mut out = String::from(
+        "RYU: Edit the PRs about to be created. In each section, the first line\n\
+         RYU: is the PR title and the rest is its description.\n\
+         RYU: Lines starting with \"RYU:\" are ignored; keep the \"RYU: bookmark\"\n\
+         RYU: lines so ryu can tell the PRs apart. Removing a section keeps that\n\
+         RYU: PR as generated. Clearing the whole file aborts.\n",
+    );
+
+    for step in &plan.execution_steps {
+        let ExecutionStep::CreatePr(create) = step else {
+            continue;
+        };
+        let draft = if create.draft { ", draft" } else { "" };
+        let _ = write!(
+            out,
+            "\n{SECTION_HEADER}{} (into {}{draft})\n{}\n",
+            create.bookmark.name, create.base_branch, create.title
+        );
+        if !create.body.is_empty() {
+            let _ = writeln!(out, "\n{}", create.body);
+        }
+    }
+
+    out
+}
+
+/// Read an edited file back into the plan's PR titles and descriptions
+///
+/// Returns `false` if everything but comments was removed, meaning the user
+/// wants to abort.
+pub fn apply_pr_edits(plan: &mut SubmissionPlan, edited: &str) -> Result<bool> {
+    let sections = parse_sections(edited)?;
+    if sections.iter().all(|(_, text)| text.is_empty()) {
+        return Ok(false);
+    }
+
+    let mut seen = HashSet::new();
+    for (name, text) in sections {
+        if !seen.insert(name) {
+            return Err(Error::InvalidArgument(format!(
+                "Bookmark '{name}' appears more than once in the edited PRs"
+            )));
+        }
+        let create = plan
+            .execution_steps
+            .iter_mut()
+            .find_map(|step| match step {
+                ExecutionStep::CreatePr(create) if create.bookmark.name == name => Some(create),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!("No PR is being created for bookmark '{name}'"))
+            })?;
+
+        let (title, body) = text.split_once('\n').unwrap_or((&text, ""));
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(Error::InvalidArgument(format!(
+                "PR for '{name}' has an empty title"
+            )));
+        }
+        create.title = title.to_string();
+        create.body = body.trim().to_string();
+    }
+
+    Ok(true)
+}
+
+/// Split the edited file into (bookmark, trimmed text) sections
+fn parse_sections(edited: &str) -> Result<Vec<(&str, String)>> {
+    let mut sections: Vec<(&str, Vec<&str>)> = Vec::new();
+    for line in edited.lines() {
+        if let Some(header) = line.strip_prefix(SECTION_HEADER) {
+            let name = header.split_whitespace().next().ok_or_else(|| {
+                Error::InvalidArgument("'RYU: bookmark' line without a bookmark name".to_string())
+            })?;
+            sections.push((name, Vec::new()));
+        } else if line.starts_with(EDIT_COMMENT_PREFIX) {
+            // Instructions, or notes the user added
+        } else if let Some((_, lines)) = sections.last_mut() {
+            lines.push(line);
+        } else if !line.trim().is_empty() {
+            return Err(Error::InvalidArgument(format!(
+                "Text outside any PR section in the edited PRs: {line}"
+            )));
+        }
+    }
+
+    Ok(sections
+        .into_iter()
+        .map(|(name, lines)| (name, lines.join("\n").trim().to_string()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submit::plan::PrToCreate;
+    use crate::types::Bookmark;
+    use std::collections::HashMap;
+
+    fn make_create(name: &str, base: &str, title: &str, body: &str) -> ExecutionStep {
+        ExecutionStep::CreatePr(PrToCreate {
+            bookmark: Bookmark {
+                name: name.to_string(),
+                commit_id: format!("{name}_commit"),
+                change_id: format!("{name}_change"),
+                has_remote: false,
+                is_synced: false,
+                is_conflicted: false,
+                has_conflicts: false,
+                is_divergent: false,
+            },
+            base_branch: base.to_string(),
+            title: title.to_string(),
+            body: body.to_string(),
+            draft: false,
+        })
+    }
+
+    fn make_plan() -> SubmissionPlan {
+        SubmissionPlan {
+            segments: vec![],
+            constraints: vec![],
+            execution_steps: vec![
+                make_create("feat-a", "main", "Add feat-a", "Body A"),
+                make_create("feat-b", "feat-a", "Add feat-b", ""),
+            ],
+            existing_prs: HashMap::new(),
+            remote: "origin".to_string(),
+            default_branch: "main".to_string(),
+            drift: vec![],
+        }
+    }
+
+    fn created(plan: &SubmissionPlan) -> Vec<(&str, &str, &str)> {
+        plan.execution_steps
+            .iter()
+            .filter_map(|step| match step {
+                ExecutionStep::CreatePr(c) => {
+                    Some((c.bookmark.name.as_str(), c.title.as_str(), c.body.as_str()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_unedited_file_round_trips() {
+        let mut plan = make_plan();
+        let file = format_pr_edits(&plan);
+        assert!(file.contains("RYU: bookmark feat-b (into feat-a)\nAdd feat-b\n"));
+
+        assert!(apply_pr_edits(&mut plan, &file).unwrap());
+        assert_eq!(
+            created(&plan),
+            vec![
+                ("feat-a", "Add feat-a", "Body A"),
+                ("feat-b", "Add feat-b", "")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edits_update_title_and_body() {
+        let mut plan = make_plan();
+        let edited = "RYU: bookmark feat-b (into feat-a)\nBetter title\n\nNew body\nline two\n";
+
+        assert!(apply_pr_edits(&mut plan, edited).unwrap());
+        assert_eq!(
+            created(&plan),
+            vec![
+                ("feat-a", "Add feat-a", "Body A"),
+                ("feat-b", "Better title", "New body\nline two")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_file_aborts() {
+        let mut plan = make_plan();
+        assert!(!apply_pr_edits(&mut plan, "RYU: nothing here\n\n").unwrap());
+        assert!(!apply_pr_edits(&mut plan, "RYU: bookmark feat-a\n\n").unwrap());
+    }
+
+    #[test]
+    fn test_empty_title_rejected() {
+        let mut plan = make_plan();
+        let edited = "RYU: bookmark feat-a\nTitle\n\nRYU: bookmark feat-b\n\n";
+        assert!(matches!(
+            apply_pr_edits(&mut plan, edited),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_or_duplicate_bookmark_rejected() {
+        let mut plan = make_plan();
+        assert!(apply_pr_edits(&mut plan, "RYU: bookmark feat-z\nTitle\n").is_err());
+        assert!(
+            apply_pr_edits(
+                &mut plan,
+                "RYU: bookmark feat-a\nOne\nRYU: bookmark feat-a\nTwo\n"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_text_outside_sections_rejected() {
+        let mut plan = make_plan();
+        assert!(apply_pr_edits(&mut plan, "stray\nRYU: bookmark feat-a\nTitle\n").is_err());
+    }
+}
@@ -5,13 +5,17 @@
 use crate::error::{Error, Result};
 use crate::platform::PlatformService;
 use crate::repo::JjWorkspace;
-use crate::submit::plan::{PrBaseUpdate, PrToCreate};
+use crate::submit::journal::{SubmissionJournal, clear_journal, save_journal};
+use crate::submit::plan::{PrBaseUpdate, PrToCreate, batched_execution_layers};
+use crate::submit::template::StackCommentTemplate;
 use crate::submit::{ExecutionStep, Phase, ProgressCallback, PushStatus, SubmissionPlan};
-use crate::types::{Bookmark, PullRequest};
+use crate::types::{BaseChange, Bookmark, PrComment, PrState, PullRequest};
 use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt::Write;
+use std::path::Path;
 
 /// Result of submission execution
 #[derive(Debug, Clone, Default)]
@@ -24,8 +28,32 @@ pub struct SubmissionResult {
     pub updated_prs: Vec<PullRequest>,
     /// Bookmarks that were pushed
     pub pushed_bookmarks: Vec<String>,
+    /// Pushed bookmarks that didn't exist on the remote before
+    pub new_branches: Vec<String>,
+    /// PR bases that were changed, with their previous value
+    pub base_changes: Vec<BaseChange>,
+    /// PRs that got a new stack comment (rather than an updated one)
+    pub created_comments: Vec<u64>,
+    /// Git refs the remote applied (e.g. `refs/heads/feat-a`)
+    pub applied_refs: Vec<String>,
     /// Errors encountered (non-fatal)
     pub errors: Vec<String>,
+    /// Warnings that didn't affect the outcome (e.g. atomic push unavailable)
+    pub warnings: Vec<String>,
+    /// Whether the journal holds this run's progress for `--resume`
+    pub journal: JournalStatus,
+}
+
+/// State of the submission journal when execution stopped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JournalStatus {
+    /// Nothing left to resume (dry run, or every step completed)
+    #[default]
+    Cleared,
+    /// Progress is saved and `ryu submit --resume` can continue
+    Saved,
+    /// Writing the journal failed, so the run can't be resumed
+    SaveFailed,
 }
 
 impl SubmissionResult {
@@ -47,6 +75,26 @@ impl SubmissionResult {
     pub fn soft_fail(&mut self, error: String) {
         self.errors.push(error);
     }
+
+    /// Record a pushed bookmark and the ref it updated
+    fn record_push(&mut self, bookmark: &Bookmark) {
+        if !bookmark.has_remote {
+            self.new_branches.push(bookmark.name.clone());
+        }
+        self.pushed_bookmarks.push(bookmark.name.clone());
+        self.applied_refs
+            .push(format!("refs/heads/{}", bookmark.name));
+    }
+
+    /// Record a PR base change so it can be reverted later
+    fn record_base_change(&mut self, update: &PrBaseUpdate) {
+        self.base_changes.push(BaseChange {
+            pr_number: update.pr.number,
+            bookmark: update.bookmark.name.clone(),
+            previous_base: update.current_base.clone(),
+            new_base: update.expected_base.clone(),
+        });
+    }
 }
 
 /// Outcome of executing a single step
@@ -60,6 +108,136 @@ pub enum StepOutcome {
     SoftError(String),
 }
 
+/// Default number of platform requests run concurrently
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Options controlling how a plan is executed
+#[derive(Debug, Clone)]
+pub struct ExecutionOptions {
+    /// Report what would be done without making changes
+    pub dry_run: bool,
+    /// Maximum number of independent steps (and stack comments) in flight at once
+    pub concurrency: usize,
+    /// Push all bookmarks with `git push --atomic` (all or nothing)
+    pub atomic: bool,
+    /// Template for the visible part of stack comments (built-in layout if `None`)
+    pub comment_template: Option<StackCommentTemplate>,
+    /// Where stack navigation goes on each PR
+    pub placement: StackPlacement,
+    /// Which stacks get stack navigation at all
+    pub comment_policy: CommentPolicy,
+    /// Draw the stack as a Mermaid diagram in the built-in layout
+    pub comment_diagram: bool,
+}
+
+impl Default for ExecutionOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            concurrency: DEFAULT_CONCURRENCY,
+            atomic: false,
+            comment_template: None,
+            placement: StackPlacement::default(),
+            comment_policy: CommentPolicy::default(),
+            comment_diagram: false,
+        }
+    }
+}
+
+/// jj config key choosing where stack navigation goes
+pub const PLACEMENT_CONFIG_KEY: &str = "ryu.stack-comment.placement";
+
+/// Where stack navigation is written on each PR
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StackPlacement {
+    /// A ryu comment on the PR
+    #[default]
+    Comment,
+    /// A managed section of the PR description
+    Body,
+    /// Both a comment and a section of the description
+    Both,
+}
+
+impl StackPlacement {
+    /// Parse a placement name (`comment`, `body` or `both`)
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "comment" => Ok(Self::Comment),
+            "body" => Ok(Self::Body),
+            "both" => Ok(Self::Both),
+            other => Err(Error::Config(format!(
+                "Invalid {PLACEMENT_CONFIG_KEY} '{other}': expected comment, body or both"
+            ))),
+        }
+    }
+
+    /// Placement selected by `ryu.stack-comment.placement` (comment if unset)
+    pub fn from_config(workspace: &JjWorkspace) -> Result<Self> {
+        workspace
+            .config_string(PLACEMENT_CONFIG_KEY)?
+            .map_or(Ok(Self::default()), |value| Self::parse(&value))
+    }
+
+    /// Whether navigation goes in a comment
+    pub const fn in_comment(self) -> bool {
+        matches!(self, Self::Comment | Self::Both)
+    }
+
+    /// Whether navigation goes in the PR description
+    pub const fn in_body(self) -> bool {
+        matches!(self, Self::Body | Self::Both)
+    }
+}
+
+/// jj config key turning on the Mermaid stack diagram
+pub const DIAGRAM_CONFIG_KEY: &str = "ryu.stack-comment.diagram";
+
+/// jj config key choosing which stacks get stack navigation
+pub const POLICY_CONFIG_KEY: &str = "ryu.stack-comment.policy";
+
+/// Which stacks get stack navigation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommentPolicy {
+    /// Every stack, including single-PR ones
+    #[default]
+    Always,
+    /// Only stacks of two or more PRs
+    MultiOnly,
+    /// No stack; existing navigation is removed
+    Never,
+}
+
+impl CommentPolicy {
+    /// Parse a policy name (`always`, `multi-only` or `never`)
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "always" => Ok(Self::Always),
+            "multi-only" => Ok(Self::MultiOnly),
+            "never" => Ok(Self::Never),
+            other => Err(Error::Config(format!(
+                "Invalid {POLICY_CONFIG_KEY} '{other}': expected always, multi-only or never"
+            ))),
+        }
+    }
+
+    /// Policy selected by `ryu.stack-comment.policy` (always if unset)
+    pub fn from_config(workspace: &JjWorkspace) -> Result<Self> {
+        workspace
+            .config_string(POLICY_CONFIG_KEY)?
+            .map_or(Ok(Self::default()), |value| Self::parse(&value))
+    }
+
+    /// Whether a stack of `len` PRs gets navigation
+    pub const fn wants_navigation(self, len: usize) -> bool {
+        match self {
+            Self::Always => true,
+            Self::MultiOnly => len > 1,
+            Self::Never => false,
+        }
+    }
+}
+
 /// Stack comment data embedded in PR comments
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct StackCommentData {
@@ -82,6 +260,9 @@ pub struct StackItem {
     pub pr_number: u64,
     /// PR title
     pub pr_title: String,
+    /// PR state (open, closed, merged)
+    #[serde(default)]
+    pub pr_state: PrState,
 }
 
 /// Prefix for stack comment data
@@ -91,16 +272,56 @@ const COMMENT_DATA_PREFIX_OLD: &str = "<!--- JJ-STACK_INFO: ";
 pub const COMMENT_DATA_POSTFIX: &str = " --->";
 /// Marker for the current PR in stack comments
 pub const STACK_COMMENT_THIS_PR: &str = "👈";
+/// Start of the ryu-managed section of a PR description
+pub const STACK_SECTION_START: &str =
+    "<!-- ryu:stack:start - edits inside this section are overwritten -->";
+/// End of the ryu-managed section of a PR description
+pub const STACK_SECTION_END: &str = "<!-- ryu:stack:end -->";
 
 // =============================================================================
 // Step Execution Functions (testable in isolation)
 // =============================================================================
 
-/// Execute a push step
-pub fn execute_push(workspace: &mut JjWorkspace, bookmark: &Bookmark, remote: &str) -> StepOutcome {
-    match workspace.git_push(&bookmark.name, remote) {
-        Ok(()) => StepOutcome::Success(None),
-        Err(e) => StepOutcome::FatalError(format!("Failed to push {}: {e}", bookmark.name)),
+/// Execute push steps as a single batched push
+///
+/// With `atomic`, the push uses `git push --atomic` where the remote supports
+/// it. Returns one outcome per bookmark, in order, and whether the push was
+/// applied atomically.
+pub fn execute_pushes(
+    workspace: &mut JjWorkspace,
+    bookmarks: &[&Bookmark],
+    remote: &str,
+    atomic: bool,
+) -> (Vec<StepOutcome>, bool) {
+    let names: Vec<&str> = bookmarks.iter().map(|bm| bm.name.as_str()).collect();
+    let report = if atomic {
+        workspace.git_push_bookmarks_atomic(&names, remote)
+    } else {
+        workspace.git_push_bookmarks(&names, remote)
+    };
+    match report {
+        Ok(report) => {
+            let outcomes = names
+                .iter()
+                .map(|name| {
+                    report
+                        .rejected
+                        .iter()
+                        .find(|(rejected, _)| rejected == name)
+                        .map_or(StepOutcome::Success(None), |(_, reason)| {
+                            StepOutcome::FatalError(format!("Failed to push {name}: {reason}"))
+                        })
+                })
+                .collect();
+            (outcomes, report.atomic)
+        }
+        Err(e) => {
+            let outcomes = names
+                .iter()
+                .map(|name| StepOutcome::FatalError(format!("Failed to push {name}: {e}")))
+                .collect();
+            (outcomes, false)
+        }
     }
 }
 
@@ -124,10 +345,11 @@ pub async fn execute_update_base(
 /// Execute a create PR step
 pub async fn execute_create_pr(platform: &dyn PlatformService, create: &PrToCreate) -> StepOutcome {
     match platform
-        .create_pr_with_options(
+        .create_pr_with_body(
             &create.bookmark.name,
             &create.base_branch,
             &create.title,
+            &create.body,
             create.draft,
         )
         .await
@@ -160,118 +382,464 @@ pub async fn execute_publish_pr(platform: &dyn PlatformService, pr: &PullRequest
 /// 3. Create new PRs
 /// 4. Publish draft PRs
 /// 5. Add/update stack comments
+///
+/// Steps run in dependency layers (see [`batched_execution_layers`]): the
+/// pushes of a layer go out in a single `git push`, and platform steps within
+/// a layer run concurrently, up to `options.concurrency`.
+/// Progress is recorded in an on-disk journal so an interrupted run can be
+/// continued with [`resume_submission`].
 pub async fn execute_submission(
     plan: &SubmissionPlan,
     workspace: &mut JjWorkspace,
     platform: &dyn PlatformService,
     progress: &dyn ProgressCallback,
-    dry_run: bool,
+    options: ExecutionOptions,
 ) -> Result<SubmissionResult> {
-    let mut result = SubmissionResult::new();
-
-    if dry_run {
+    if options.dry_run {
         progress
             .on_message("Dry run - no changes will be made")
             .await;
         report_dry_run(plan, progress).await;
-        return Ok(result);
+        return Ok(SubmissionResult::new());
+    }
+
+    let journal = SubmissionJournal::new(plan.clone());
+    run_journaled(journal, workspace, platform, progress, options).await
+}
+
+/// Resume an interrupted submission from its journal
+///
+/// Steps recorded as completed are skipped; execution continues from the
+/// first incomplete step, then (re)writes stack comments for the whole stack.
+pub async fn resume_submission(
+    journal: SubmissionJournal,
+    workspace: &mut JjWorkspace,
+    platform: &dyn PlatformService,
+    progress: &dyn ProgressCallback,
+    options: ExecutionOptions,
+) -> Result<SubmissionResult> {
+    progress
+        .on_message(&format!(
+            "Resuming submission started {} ({}/{} steps already done)",
+            journal.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            journal.completed_count(),
+            journal.total_count()
+        ))
+        .await;
+
+    if options.dry_run {
+        progress.on_message("Would execute:").await;
+        for (idx, step) in journal.plan.execution_steps.iter().enumerate() {
+            if !journal.is_completed(idx) {
+                let msg = format_step_for_dry_run(step, &journal.plan.remote);
+                progress.on_message(&msg).await;
+            }
+        }
+        return Ok(SubmissionResult::new());
+    }
+
+    run_journaled(journal, workspace, platform, progress, options).await
+}
+
+/// Persist journal progress (best effort - never fails the submission)
+///
+/// Returns the resulting journal status.
+fn persist_journal(workspace_root: &Path, journal: &SubmissionJournal) -> JournalStatus {
+    match save_journal(workspace_root, journal) {
+        Ok(()) => JournalStatus::Saved,
+        Err(e) => {
+            tracing::warn!("Failed to write submission journal: {e}");
+            JournalStatus::SaveFailed
+        }
+    }
+}
+
+/// Execute the incomplete steps of a journaled plan
+#[allow(clippy::too_many_lines)]
+async fn run_journaled(
+    mut journal: SubmissionJournal,
+    workspace: &mut JjWorkspace,
+    platform: &dyn PlatformService,
+    progress: &dyn ProgressCallback,
+    options: ExecutionOptions,
+) -> Result<SubmissionResult> {
+    let workspace_root = workspace.workspace_root().to_path_buf();
+    let plan = journal.plan.clone();
+    let layers = batched_execution_layers(&plan.execution_steps, &plan.constraints)?;
+    let concurrency = options.concurrency.max(1);
+    let mut result = SubmissionResult::new();
+
+    if options.atomic {
+        let push_batches = layers
+            .iter()
+            .filter(|layer| {
+                layer.iter().any(|idx| {
+                    !journal.is_completed(*idx)
+                        && matches!(plan.execution_steps[*idx], ExecutionStep::Push(_))
+                })
+            })
+            .count();
+        if push_batches > 1 {
+            let msg = format!(
+                "Pushes must be split into {push_batches} batches to keep PR bases valid; \
+                 each batch is atomic, but the submission as a whole is not"
+            );
+            progress.on_message(&format!("Warning: {msg}")).await;
+            result.warnings.push(msg);
+        }
     }
 
+    // Carry over work done by an earlier, interrupted run
+    for (idx, step) in plan.execution_steps.iter().enumerate() {
+        if journal.is_completed(idx) {
+            record_prior_step(&mut result, step, &journal.prs);
+        }
+    }
+
+    journal.last_error = None;
+    result.journal = persist_journal(&workspace_root, &journal);
+
     // Track all PRs (existing + created) for comment generation
     let mut bookmark_to_pr: HashMap<String, PullRequest> = plan.existing_prs.clone();
+    bookmark_to_pr.extend(journal.prs.clone());
 
     // Phase: Executing all steps
     progress.on_phase(Phase::Executing).await;
 
-    for step in &plan.execution_steps {
-        let outcome = execute_step(step, workspace, platform, &plan.remote, progress).await;
+    for layer in layers {
+        let (pushes, platform_steps): (Vec<usize>, Vec<usize>) = layer
+            .into_iter()
+            .filter(|idx| !journal.is_completed(*idx))
+            .partition(|idx| matches!(plan.execution_steps[*idx], ExecutionStep::Push(_)));
+
+        // All pushes of the layer go out in one `git push`
+        let (mut outcomes, applied_atomically) = push_bookmarks(
+            &plan.execution_steps,
+            &pushes,
+            workspace,
+            &plan.remote,
+            options.atomic,
+            progress,
+        )
+        .await;
+        if options.atomic && !pushes.is_empty() && !applied_atomically {
+            let msg = format!(
+                "Remote '{}' does not support atomic pushes; pushed bookmarks non-atomically",
+                plan.remote
+            );
+            progress.on_message(&format!("Warning: {msg}")).await;
+            result.warnings.push(msg);
+        }
 
-        match outcome {
-            StepOutcome::Success(Some((bookmark, pr))) => {
-                // Track the PR for comment generation
-                match step {
-                    ExecutionStep::CreatePr(_) => result.created_prs.push(pr.clone()),
-                    ExecutionStep::UpdateBase(_) | ExecutionStep::PublishPr(_) => {
-                        result.updated_prs.push(pr.clone());
-                    }
-                    ExecutionStep::Push(_) => {}
-                }
-                bookmark_to_pr.insert(bookmark, pr);
-            }
-            StepOutcome::Success(None) => {
-                // Push succeeded - track it
-                if let ExecutionStep::Push(bm) = step {
-                    result.pushed_bookmarks.push(bm.name.clone());
+        // Platform calls within a layer are independent of each other
+        let steps = &plan.execution_steps;
+        outcomes.extend(
+            stream::iter(platform_steps)
+                .map(|idx| async move {
+                    let outcome = execute_platform_step(&steps[idx], platform, progress).await;
+                    (idx, outcome)
+                })
+                .buffer_unordered(concurrency)
+                .collect::<Vec<_>>()
+                .await,
+        );
+        outcomes.sort_by_key(|(idx, _)| *idx);
+
+        // Let every in-flight step of the layer finish, then stop on failure
+        let mut fatal = false;
+        for (idx, outcome) in outcomes {
+            let step = &plan.execution_steps[idx];
+            fatal |= record_outcome(
+                &mut result,
+                &mut journal,
+                &mut bookmark_to_pr,
+                (idx, step),
+                outcome,
+                progress,
+            )
+            .await;
+        }
+
+        result.journal = persist_journal(&workspace_root, &journal);
+
+        if fatal {
+            return Ok(result);
+        }
+    }
+
+    // Phase: Adding stack comments
+    progress.on_phase(Phase::AddingComments).await;
+
+    if !bookmark_to_pr.is_empty() {
+        let outcomes = update_stack_comments(&plan, &bookmark_to_pr, platform, &options).await;
+        record_comment_outcomes(&mut result, outcomes, progress).await;
+    }
+
+    // Submission finished - nothing left to resume
+    if let Err(e) = clear_journal(&workspace_root) {
+        tracing::warn!("Failed to remove submission journal: {e}");
+    }
+    result.journal = JournalStatus::Cleared;
+
+    progress.on_phase(Phase::Complete).await;
+
+    Ok(result)
+}
+
+/// Record a step outcome in the result and journal
+///
+/// Returns `true` if the step failed fatally.
+async fn record_outcome(
+    result: &mut SubmissionResult,
+    journal: &mut SubmissionJournal,
+    bookmark_to_pr: &mut HashMap<String, PullRequest>,
+    (idx, step): (usize, &ExecutionStep),
+    outcome: StepOutcome,
+    progress: &dyn ProgressCallback,
+) -> bool {
+    match outcome {
+        StepOutcome::Success(Some((bookmark, pr))) => {
+            // Track the PR for comment generation
+            match step {
+                ExecutionStep::CreatePr(_) => result.created_prs.push(pr.clone()),
+                ExecutionStep::UpdateBase(update) => {
+                    result.record_base_change(update);
+                    result.updated_prs.push(pr.clone());
                 }
+                ExecutionStep::PublishPr(_) => result.updated_prs.push(pr.clone()),
+                ExecutionStep::Push(_) => {}
             }
-            StepOutcome::FatalError(msg) => {
-                progress.on_error(&Error::Platform(msg.clone())).await;
-                result.fail(msg);
-                return Ok(result);
+            // A new PR has no comments, and retargeting or publishing one
+            // leaves them alone, so the stack comment phase needn't list them
+            let mut tracked = pr.clone();
+            if tracked.comments.is_none() {
+                tracked.comments = match step {
+                    ExecutionStep::CreatePr(_) => Some(Vec::new()),
+                    _ => bookmark_to_pr
+                        .get(&bookmark)
+                        .and_then(|old| old.comments.clone()),
+                };
             }
-            StepOutcome::SoftError(msg) => {
-                progress.on_error(&Error::Platform(msg.clone())).await;
-                result.soft_fail(msg);
+            bookmark_to_pr.insert(bookmark.clone(), tracked);
+            journal.mark_completed(idx, Some((bookmark, pr)));
+            false
+        }
+        StepOutcome::Success(None) => {
+            // Push succeeded - track it
+            if let ExecutionStep::Push(bm) = step {
+                result.record_push(bm);
             }
+            journal.mark_completed(idx, None);
+            false
+        }
+        StepOutcome::FatalError(msg) => {
+            progress.on_error(&Error::Platform(msg.clone())).await;
+            journal.last_error = Some(msg.clone());
+            result.fail(msg);
+            true
+        }
+        StepOutcome::SoftError(msg) => {
+            progress.on_error(&Error::Platform(msg.clone())).await;
+            result.soft_fail(msg);
+            journal.mark_completed(idx, None);
+            false
         }
     }
+}
 
-    // Phase: Adding stack comments
-    progress.on_phase(Phase::AddingComments).await;
+/// Outcome of writing one stack comment: the PR number if a comment was
+/// created, `None` if one was updated, or the failure message
+type CommentOutcome = std::result::Result<Option<u64>, String>;
 
-    if !bookmark_to_pr.is_empty() {
-        let stack_data = build_stack_comment_data(plan, &bookmark_to_pr);
-
-        for (idx, item) in stack_data.stack.iter().enumerate() {
-            if let Err(e) =
-                create_or_update_stack_comment(platform, &stack_data, idx, item.pr_number).await
-            {
-                let msg = format!(
-                    "Failed to update stack comment for {}: {e}",
-                    item.bookmark_name
-                );
+/// Create or update the stack navigation on every PR, concurrently, then
+/// clean up navigation left on PRs that are no longer in the stack
+///
+/// If `options.comment_policy` doesn't want navigation for this stack, any
+/// existing navigation is removed instead. Returns one outcome per PR: the PR
+/// number if a new comment was created, `None` if an existing one was updated
+/// or removed, or a message if writing failed.
+async fn update_stack_comments(
+    plan: &SubmissionPlan,
+    bookmark_to_pr: &HashMap<String, PullRequest>,
+    platform: &dyn PlatformService,
+    options: &ExecutionOptions,
+) -> Vec<CommentOutcome> {
+    let stack_data = build_stack_comment_data(plan, bookmark_to_pr);
+    let stack_data = &stack_data;
+    let wanted = options
+        .comment_policy
+        .wants_navigation(stack_data.stack.len());
+
+    let updates: Vec<Result<NavigationUpdate>> = stream::iter(stack_data.stack.iter().enumerate())
+        .map(|(idx, item)| async move {
+            // Reuse comments the planning lookup already fetched
+            let comments = bookmark_to_pr
+                .get(&item.bookmark_name)
+                .and_then(|pr| pr.comments.as_deref());
+            if wanted {
+                create_or_update_stack_comment(
+                    platform,
+                    stack_data,
+                    options,
+                    idx,
+                    item.pr_number,
+                    comments,
+                )
+                .await
+            } else {
+                remove_stack_navigation(platform, item.pr_number, options.placement, None, comments)
+                    .await
+            }
+        })
+        .buffered(options.concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut outcomes = Vec::with_capacity(updates.len());
+    let mut departed = BTreeSet::new();
+    for (item, update) in stack_data.stack.iter().zip(updates) {
+        match update {
+            Ok(update) => {
+                departed.extend(update.previous_stack);
+                outcomes.push(Ok(update.created_comment.then_some(item.pr_number)));
+            }
+            Err(e) => outcomes.push(Err(format!(
+                "Failed to update stack comment for {}: {e}",
+                item.bookmark_name
+            ))),
+        }
+    }
+    let stack_prs: HashSet<u64> = stack_data.stack.iter().map(|item| item.pr_number).collect();
+    let stack_prs = &stack_prs;
+    departed.retain(|pr_number| !stack_prs.contains(pr_number));
+
+    // A PR that left the stack, whether merged, closed, untracked or moved to
+    // another stack, keeps navigation listing this stack until removed here
+    let cleanups: Vec<CommentOutcome> = stream::iter(departed)
+        .map(|pr_number| async move {
+            remove_stack_navigation(
+                platform,
+                pr_number,
+                options.placement,
+                Some(stack_prs),
+                None,
+            )
+            .await
+            .map(|_| None)
+            .map_err(|e| format!("Failed to remove stale stack comment from PR #{pr_number}: {e}"))
+        })
+        .buffer_unordered(options.concurrency.max(1))
+        .collect()
+        .await;
+    outcomes.extend(cleanups);
+    outcomes
+}
+
+/// Record which PRs got a new stack comment and which comments failed
+async fn record_comment_outcomes(
+    result: &mut SubmissionResult,
+    outcomes: Vec<CommentOutcome>,
+    progress: &dyn ProgressCallback,
+) {
+    for outcome in outcomes {
+        match outcome {
+            Ok(Some(pr_number)) => result.created_comments.push(pr_number),
+            Ok(None) => {}
+            Err(msg) => {
                 progress.on_error(&Error::Platform(msg.clone())).await;
                 result.soft_fail(msg);
             }
         }
     }
-
-    progress.on_phase(Phase::Complete).await;
-
-    Ok(result)
 }
 
-/// Execute a single step with progress reporting
-async fn execute_step(
+/// Fold a step completed by an earlier run into the result
+fn record_prior_step(
+    result: &mut SubmissionResult,
     step: &ExecutionStep,
+    prs: &HashMap<String, PullRequest>,
+) {
+    match step {
+        ExecutionStep::Push(bm) => result.record_push(bm),
+        ExecutionStep::CreatePr(create) => {
+            if let Some(pr) = prs.get(&create.bookmark.name) {
+                result.created_prs.push(pr.clone());
+            }
+        }
+        ExecutionStep::UpdateBase(update) => {
+            if let Some(pr) = prs.get(&update.bookmark.name) {
+                result.record_base_change(update);
+                result.updated_prs.push(pr.clone());
+            }
+        }
+        ExecutionStep::PublishPr(pr) => {
+            if let Some(pr) = prs.get(&pr.head_ref) {
+                result.updated_prs.push(pr.clone());
+            }
+        }
+    }
+}
+
+/// Push the bookmarks of the given push steps together, with progress reporting
+///
+/// Also returns whether the push was applied atomically.
+async fn push_bookmarks(
+    steps: &[ExecutionStep],
+    indices: &[usize],
     workspace: &mut JjWorkspace,
-    platform: &dyn PlatformService,
     remote: &str,
+    atomic: bool,
     progress: &dyn ProgressCallback,
-) -> StepOutcome {
-    match step {
-        ExecutionStep::Push(bookmark) => {
-            progress
-                .on_bookmark_push(&bookmark.name, PushStatus::Started)
-                .await;
+) -> (Vec<(usize, StepOutcome)>, bool) {
+    let bookmarks: Vec<(usize, &Bookmark)> = indices
+        .iter()
+        .filter_map(|&idx| match &steps[idx] {
+            ExecutionStep::Push(bookmark) => Some((idx, bookmark)),
+            _ => None,
+        })
+        .collect();
+    if bookmarks.is_empty() {
+        return (Vec::new(), false);
+    }
+
+    for (_, bookmark) in &bookmarks {
+        progress
+            .on_bookmark_push(&bookmark.name, PushStatus::Started)
+            .await;
+    }
 
-            let outcome = execute_push(workspace, bookmark, remote);
+    let refs: Vec<&Bookmark> = bookmarks.iter().map(|(_, bm)| *bm).collect();
+    let (outcomes, applied_atomically) = execute_pushes(workspace, &refs, remote, atomic);
 
-            match &outcome {
-                StepOutcome::Success(_) => {
-                    progress
-                        .on_bookmark_push(&bookmark.name, PushStatus::Success)
-                        .await;
-                }
-                StepOutcome::FatalError(msg) | StepOutcome::SoftError(msg) => {
-                    progress
-                        .on_bookmark_push(&bookmark.name, PushStatus::Failed(msg.clone()))
-                        .await;
-                }
+    for ((_, bookmark), outcome) in bookmarks.iter().zip(&outcomes) {
+        let status = match outcome {
+            StepOutcome::Success(_) => PushStatus::Success,
+            StepOutcome::FatalError(msg) | StepOutcome::SoftError(msg) => {
+                PushStatus::Failed(msg.clone())
             }
+        };
+        progress.on_bookmark_push(&bookmark.name, status).await;
+    }
 
-            outcome
-        }
+    let outcomes = bookmarks
+        .into_iter()
+        .map(|(idx, _)| idx)
+        .zip(outcomes)
+        .collect();
+    (outcomes, applied_atomically)
+}
+
+/// Execute a step that only talks to the platform (safe to run concurrently)
+async fn execute_platform_step(
+    step: &ExecutionStep,
+    platform: &dyn PlatformService,
+    progress: &dyn ProgressCallback,
+) -> StepOutcome {
+    match step {
+        ExecutionStep::Push(bookmark) => StepOutcome::FatalError(format!(
+            "Push of {} cannot run as a platform step",
+            bookmark.name
+        )),
 
         ExecutionStep::UpdateBase(update) => {
             progress
@@ -365,6 +933,7 @@ pub fn build_stack_comment_data(
                 pr_url: pr.html_url.clone(),
                 pr_number: pr.number,
                 pr_title: pr.title.clone(),
+                pr_state: pr.state,
             })
         })
         .collect();
@@ -378,63 +947,325 @@ pub fn build_stack_comment_data(
 
 /// Format the stack comment body for a PR
 pub fn format_stack_comment(data: &StackCommentData, current_idx: usize) -> Result<String> {
+    render_stack_comment(data, current_idx, None, false)
+}
+
+/// Format the stack comment body for a PR, using `template` for the visible
+/// part if given
+///
+/// Without a template, `diagram` draws the stack as a Mermaid diagram instead
+/// of a bullet list. The embedded stack data ryu uses to recognise its
+/// comments is always included, whatever the template renders.
+pub fn render_stack_comment(
+    data: &StackCommentData,
+    current_idx: usize,
+    template: Option<&StackCommentTemplate>,
+    diagram: bool,
+) -> Result<String> {
     let encoded_data = BASE64.encode(
         serde_json::to_string(data)
             .map_err(|e| Error::Internal(format!("Failed to serialize stack data: {e}")))?,
     );
 
-    let mut body = format!("{COMMENT_DATA_PREFIX}{encoded_data}{COMMENT_DATA_POSTFIX}\n");
-
-    // Reverse order: newest/leaf at top, oldest at bottom
-    // Format: "* PR title #N" with current PR marked with 👈 and bold
-    let reversed_idx = data.stack.len() - 1 - current_idx;
-    for (i, item) in data.stack.iter().rev().enumerate() {
-        if i == reversed_idx {
-            let _ = writeln!(
-                body,
-                "* **{} #{} {STACK_COMMENT_THIS_PR}**",
-                item.pr_title, item.pr_number
-            );
+    let visible = template.map_or_else(
+        || Ok(default_stack_info(data, current_idx, diagram)),
+        |template| template.render(data, current_idx),
+    )?;
+    Ok(format!(
+        "{COMMENT_DATA_PREFIX}{encoded_data}{COMMENT_DATA_POSTFIX}\n{visible}"
+    ))
+}
+
+/// Draw the stack as a Mermaid `graph BT` flowchart
+///
+/// The base branch is at the bottom and each PR points up to the PR stacked
+/// on it. Nodes show the PR number, title and state; merged and closed PRs
+/// are shaded and the PR at `current_idx` is outlined and marked.
+///
+/// The diagram is always a single chain in stack order, like the stack data
+/// it is drawn from: it doesn't show PRs' actual base branches, so a PR
+/// retargeted outside ryu still appears on the one below it.
+pub fn format_stack_diagram(data: &StackCommentData, current_idx: usize) -> String {
+    let mut out = String::from("graph BT\n");
+    let _ = writeln!(out, "    base[\"{}\"]", mermaid_escape(&data.base_branch));
+    for (idx, item) in data.stack.iter().enumerate() {
+        let marker = if idx == current_idx {
+            format!(" {STACK_COMMENT_THIS_PR}")
         } else {
-            let _ = writeln!(body, "* {} #{}", item.pr_title, item.pr_number);
+            String::new()
+        };
+        let _ = writeln!(
+            out,
+            "    pr{}[\"#{} {}{marker}<br/>{}\"]",
+            item.pr_number,
+            item.pr_number,
+            mermaid_escape(&item.pr_title),
+            item.pr_state
+        );
+    }
+
+    let mut previous = "base".to_string();
+    for item in &data.stack {
+        let node = format!("pr{}", item.pr_number);
+        let _ = writeln!(out, "    {previous} --> {node}");
+        previous = node;
+    }
+
+    let _ = writeln!(out, "    classDef merged fill:#8250df,color:#fff");
+    let _ = writeln!(out, "    classDef closed fill:#cf222e,color:#fff");
+    let _ = writeln!(out, "    classDef current stroke-width:3px");
+    for item in &data.stack {
+        if item.pr_state != PrState::Open {
+            let _ = writeln!(out, "    class pr{} {}", item.pr_number, item.pr_state);
         }
     }
+    if let Some(item) = data.stack.get(current_idx) {
+        let _ = writeln!(out, "    class pr{} current", item.pr_number);
+    }
+    out
+}
+
+/// Escape text for a quoted Mermaid node label
+///
+/// `#` starts Mermaid's entity codes, so it is escaped before the entities
+/// the other replacements insert.
+fn mermaid_escape(text: &str) -> String {
+    text.replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+/// Replace ryu's managed section of a PR description with `section`
+///
+/// Text outside the section markers is left untouched. If the description has
+/// no section yet, one is appended.
+pub fn replace_stack_section(body: &str, section: &str) -> String {
+    let fenced = format!(
+        "{STACK_SECTION_START}\n{}\n{STACK_SECTION_END}",
+        section.trim_end()
+    );
+    if let Some((before, after)) = split_stack_section(body) {
+        return format!("{before}{fenced}{after}");
+    }
+    let body = body.trim_end();
+    if body.is_empty() {
+        fenced
+    } else {
+        format!("{body}\n\n{fenced}")
+    }
+}
+
+/// Remove ryu's managed section from a PR description, if present
+pub fn remove_stack_section(body: &str) -> Option<String> {
+    let (before, after) = split_stack_section(body)?;
+    let before = before.trim_end();
+    let after = after.trim_start();
+    Some(match (before.is_empty(), after.is_empty()) {
+        (true, _) => after.to_string(),
+        (false, true) => before.to_string(),
+        (false, false) => format!("{before}\n\n{after}"),
+    })
+}
+
+/// Text before and after the managed section (markers excluded)
+fn split_stack_section(body: &str) -> Option<(&str, &str)> {
+    let start = body.find(STACK_SECTION_START)?;
+    let end = start + body[start..].find(STACK_SECTION_END)? + STACK_SECTION_END.len();
+    Some((&body[..start], &body[end..]))
+}
 
-    // Add base branch at bottom
-    let _ = writeln!(body, "* `{}`", data.base_branch);
+/// Built-in stack comment layout
+fn default_stack_info(data: &StackCommentData, current_idx: usize, diagram: bool) -> String {
+    let mut body = String::new();
+
+    if diagram {
+        let _ = writeln!(
+            body,
+            "```mermaid\n{}```",
+            format_stack_diagram(data, current_idx)
+        );
+    } else {
+        // Reverse order: newest/leaf at top, oldest at bottom
+        // Format: "* PR title #N" with current PR marked with 👈 and bold
+        let reversed_idx = data.stack.len() - 1 - current_idx;
+        for (i, item) in data.stack.iter().rev().enumerate() {
+            if i == reversed_idx {
+                let _ = writeln!(
+                    body,
+                    "* **{} #{} {STACK_COMMENT_THIS_PR}**",
+                    item.pr_title, item.pr_number
+                );
+            } else {
+                let _ = writeln!(body, "* {} #{}", item.pr_title, item.pr_number);
+            }
+        }
+
+        // Add base branch at bottom
+        let _ = writeln!(body, "* `{}`", data.base_branch);
+    }
 
     let _ = write!(
         body,
         "\n---\nThis stack of pull requests is managed by [jj-ryu](https://github.com/dmmulroy/jj-ryu)."
     );
 
-    Ok(body)
+    body
+}
+
+/// Whether a comment is a ryu stack comment
+fn is_stack_comment(body: &str) -> bool {
+    body.contains(COMMENT_DATA_PREFIX) || body.contains(COMMENT_DATA_PREFIX_OLD)
+}
+
+/// Remove ryu's stack comment from a PR
+///
+/// Returns `false` if the PR has no stack comment.
+pub async fn delete_stack_comment(platform: &dyn PlatformService, pr_number: u64) -> Result<bool> {
+    let comments = platform.list_pr_comments(pr_number).await?;
+    let Some(comment) = comments.iter().find(|c| is_stack_comment(&c.body)) else {
+        return Ok(false);
+    };
+    platform.delete_pr_comment(pr_number, comment.id).await?;
+    Ok(true)
+}
+
+/// What writing or removing stack navigation on one PR did
+#[derive(Debug, Default)]
+struct NavigationUpdate {
+    /// A new stack comment was created
+    created_comment: bool,
+    /// PRs listed by the navigation the PR had before
+    previous_stack: Vec<u64>,
 }
 
-/// Create or update the stack comment on a PR
+/// A PR's comments: `prefetched` if the PR lookup returned them, otherwise
+/// listed from the platform
+async fn pr_comments(
+    platform: &dyn PlatformService,
+    pr_number: u64,
+    prefetched: Option<&[PrComment]>,
+) -> Result<Vec<PrComment>> {
+    match prefetched {
+        Some(comments) => Ok(comments.to_vec()),
+        None => platform.list_pr_comments(pr_number).await,
+    }
+}
+
+/// Create or update the stack navigation on a PR
+///
+/// Depending on `options.placement`, writes the stack comment, the managed
+/// section of the PR description, or both. `comments` are the PR's comments
+/// if already known.
 async fn create_or_update_stack_comment(
     platform: &dyn PlatformService,
     data: &StackCommentData,
+    options: &ExecutionOptions,
     current_idx: usize,
     pr_number: u64,
-) -> Result<()> {
-    let body = format_stack_comment(data, current_idx)?;
+    comments: Option<&[PrComment]>,
+) -> Result<NavigationUpdate> {
+    let body = render_stack_comment(
+        data,
+        current_idx,
+        options.comment_template.as_ref(),
+        options.comment_diagram,
+    )?;
+    let mut update = NavigationUpdate::default();
+
+    if options.placement.in_body() {
+        let current = platform.get_pr_body(pr_number).await?;
+        update.previous_stack.extend(stack_pr_numbers(&current));
+        let updated = replace_stack_section(&current, &body);
+        // Unchanged descriptions aren't rewritten, which would notify reviewers
+        if updated != current {
+            platform.update_pr_body(pr_number, &updated).await?;
+        }
+    }
+    if !options.placement.in_comment() {
+        return Ok(update);
+    }
 
     // Find existing comment by looking for our data prefix (check both old and new)
-    let comments = platform.list_pr_comments(pr_number).await?;
-    let existing = comments
-        .iter()
-        .find(|c| c.body.contains(COMMENT_DATA_PREFIX) || c.body.contains(COMMENT_DATA_PREFIX_OLD));
+    let comments = pr_comments(platform, pr_number, comments).await?;
+    let existing = comments.iter().find(|c| is_stack_comment(&c.body));
 
     if let Some(comment) = existing {
+        update
+            .previous_stack
+            .extend(stack_pr_numbers(&comment.body));
         platform
             .update_pr_comment(pr_number, comment.id, &body)
             .await?;
     } else {
         platform.create_pr_comment(pr_number, &body).await?;
+        update.created_comment = true;
+    }
+    Ok(update)
+}
+
+/// Remove ryu's stack comment and description section from a PR
+///
+/// Only the places `placement` writes navigation to are touched. With
+/// `stack`, navigation is only removed if it lists one of those PRs, so
+/// navigation another stack has since written is kept. `comments` are the
+/// PR's comments if already known.
+async fn remove_stack_navigation(
+    platform: &dyn PlatformService,
+    pr_number: u64,
+    placement: StackPlacement,
+    stack: Option<&HashSet<u64>>,
+    comments: Option<&[PrComment]>,
+) -> Result<NavigationUpdate> {
+    let lists_stack = |text: &str| {
+        let listed = stack_pr_numbers(text);
+        stack.is_none_or(|stack| listed.iter().any(|n| stack.contains(n)))
+    };
+    let mut update = NavigationUpdate::default();
+
+    if placement.in_comment() {
+        let comments = pr_comments(platform, pr_number, comments).await?;
+        if let Some(comment) = comments
+            .iter()
+            .find(|c| is_stack_comment(&c.body) && lists_stack(&c.body))
+        {
+            update
+                .previous_stack
+                .extend(stack_pr_numbers(&comment.body));
+            platform.delete_pr_comment(pr_number, comment.id).await?;
+        }
     }
 
-    Ok(())
+    if placement.in_body() {
+        let body = platform.get_pr_body(pr_number).await?;
+        if let Some(stripped) = remove_stack_section(&body).filter(|_| lists_stack(&body)) {
+            update.previous_stack.extend(stack_pr_numbers(&body));
+            platform.update_pr_body(pr_number, &stripped).await?;
+        }
+    }
+
+    Ok(update)
+}
+
+/// Stack data embedded in a ryu comment or description section, if any
+pub fn parse_stack_comment_data(text: &str) -> Option<StackCommentData> {
+    let start = text
+        .find(COMMENT_DATA_PREFIX)
+        .map(|i| i + COMMENT_DATA_PREFIX.len())
+        .or_else(|| {
+            text.find(COMMENT_DATA_PREFIX_OLD)
+                .map(|i| i + COMMENT_DATA_PREFIX_OLD.len())
+        })?;
+    let end = start + text[start..].find(COMMENT_DATA_POSTFIX)?;
+    let json = BASE64.decode(text[start..end].trim()).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+/// PR numbers listed by the stack data embedded in `text`
+fn stack_pr_numbers(text: &str) -> Vec<u64> {
+    parse_stack_comment_data(text)
+        .map(|data| data.stack.iter().map(|item| item.pr_number).collect())
+        .unwrap_or_default()
 }
 
 // =============================================================================
@@ -444,7 +1275,7 @@ async fn create_or_update_stack_comment(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::NarrowedBookmarkSegment;
+    use crate::types::{NarrowedBookmarkSegment, PrState};
 
     fn make_pr(number: u64, bookmark: &str) -> PullRequest {
         PullRequest {
@@ -455,6 +1286,9 @@ mod tests {
             title: format!("PR for {bookmark}"),
             node_id: Some(format!("PR_node_{number}")),
             is_draft: false,
+            state: PrState::Open,
+            comments: None,
+            checks: None,
         }
     }
 
@@ -465,6 +1299,9 @@ mod tests {
             change_id: format!("{name}_change"),
             has_remote: false,
             is_synced: false,
+            is_conflicted: false,
+            has_conflicts: false,
+            is_divergent: false,
         }
     }
 
@@ -497,6 +1334,27 @@ mod tests {
         assert_eq!(result.errors.len(), 1);
     }
 
+    #[test]
+    fn test_submission_result_records_revertible_changes() {
+        let mut result = SubmissionResult::new();
+        let mut existing = make_bookmark("feat-a");
+        existing.has_remote = true;
+        result.record_push(&existing);
+        result.record_push(&make_bookmark("feat-b"));
+        result.record_base_change(&PrBaseUpdate {
+            bookmark: existing,
+            current_base: "main".to_string(),
+            expected_base: "feat-0".to_string(),
+            pr: make_pr(4, "feat-a"),
+        });
+
+        assert_eq!(result.pushed_bookmarks, vec!["feat-a", "feat-b"]);
+        assert_eq!(result.new_branches, vec!["feat-b"]);
+        assert_eq!(result.base_changes[0].pr_number, 4);
+        assert_eq!(result.base_changes[0].previous_base, "main");
+        assert_eq!(result.base_changes[0].new_base, "feat-0");
+    }
+
     // === StepOutcome tests ===
 
     #[test]
@@ -541,6 +1399,7 @@ mod tests {
             bookmark: bm,
             base_branch: "main".to_string(),
             title: "Add feature".to_string(),
+            body: String::new(),
             draft: false,
         };
         let step = ExecutionStep::CreatePr(create);
@@ -555,6 +1414,7 @@ mod tests {
             bookmark: bm,
             base_branch: "main".to_string(),
             title: "Add feature".to_string(),
+            body: String::new(),
             draft: true,
         };
         let step = ExecutionStep::CreatePr(create);
@@ -604,6 +1464,7 @@ mod tests {
             existing_prs: HashMap::new(),
             remote: "origin".to_string(),
             default_branch: "main".to_string(),
+            drift: vec![],
         };
 
         let mut bookmark_to_pr = HashMap::new();
@@ -640,6 +1501,7 @@ mod tests {
             existing_prs: HashMap::new(),
             remote: "origin".to_string(),
             default_branch: "main".to_string(),
+            drift: vec![],
         };
 
         // Only feat-a has a PR
@@ -662,12 +1524,14 @@ mod tests {
                     pr_url: "https://example.com/1".to_string(),
                     pr_number: 1,
                     pr_title: "feat: add auth".to_string(),
+                    pr_state: PrState::Open,
                 },
                 StackItem {
                     bookmark_name: "feat-b".to_string(),
                     pr_url: "https://example.com/2".to_string(),
                     pr_number: 2,
                     pr_title: "feat: add sessions".to_string(),
+                    pr_state: PrState::Open,
                 },
             ],
             base_branch: "main".to_string(),
@@ -688,6 +1552,7 @@ mod tests {
                 pr_url: "https://example.com/1".to_string(),
                 pr_number: 1,
                 pr_title: "feat: add auth".to_string(),
+                pr_state: PrState::Open,
             }],
             base_branch: "main".to_string(),
         };
@@ -697,6 +1562,101 @@ mod tests {
         assert!(body.contains(COMMENT_DATA_POSTFIX));
     }
 
+    #[test]
+    fn test_format_stack_diagram() {
+        let mut data = StackCommentData {
+            version: 1,
+            stack: vec![
+                StackItem {
+                    bookmark_name: "feat-a".to_string(),
+                    pr_url: "https://example.com/1".to_string(),
+                    pr_number: 1,
+                    pr_title: "feat: add \"auth\" (#12)".to_string(),
+                    pr_state: PrState::Merged,
+                },
+                StackItem {
+                    bookmark_name: "feat-b".to_string(),
+                    pr_url: "https://example.com/2".to_string(),
+                    pr_number: 2,
+                    pr_title: "feat: add sessions".to_string(),
+                    pr_state: PrState::Open,
+                },
+            ],
+            base_branch: "main".to_string(),
+        };
+
+        let diagram = format_stack_diagram(&data, 1);
+        assert_eq!(
+            diagram,
+            "graph BT\n\
+             \x20   base[\"main\"]\n\
+             \x20   pr1[\"#1 feat: add #quot;auth#quot; (#35;12)<br/>merged\"]\n\
+             \x20   pr2[\"#2 feat: add sessions 👈<br/>open\"]\n\
+             \x20   base --> pr1\n\
+             \x20   pr1 --> pr2\n\
+             \x20   classDef merged fill:#8250df,color:#fff\n\
+             \x20   classDef closed fill:#cf222e,color:#fff\n\
+             \x20   classDef current stroke-width:3px\n\
+             \x20   class pr1 merged\n\
+             \x20   class pr2 current\n"
+        );
+
+        data.stack.truncate(1);
+        let body = render_stack_comment(&data, 0, None, true).unwrap();
+        assert!(body.contains("```mermaid\ngraph BT\n"));
+        assert!(!body.contains("* `main`"));
+    }
+
+    #[test]
+    fn test_replace_stack_section_keeps_user_text() {
+        let body = replace_stack_section("Fixes the login bug.\n", "* #1");
+        assert_eq!(
+            body,
+            format!("Fixes the login bug.\n\n{STACK_SECTION_START}\n* #1\n{STACK_SECTION_END}")
+        );
+
+        let edited = format!("Intro\n\n{STACK_SECTION_START}\nold\n{STACK_SECTION_END}\n\nFooter");
+        assert_eq!(
+            replace_stack_section(&edited, "* #2\n"),
+            format!("Intro\n\n{STACK_SECTION_START}\n* #2\n{STACK_SECTION_END}\n\nFooter")
+        );
+        assert_eq!(
+            replace_stack_section("", "* #1"),
+            format!("{STACK_SECTION_START}\n* #1\n{STACK_SECTION_END}")
+        );
+    }
+
+    #[test]
+    fn test_remove_stack_section() {
+        let body = format!("Intro\n\n{STACK_SECTION_START}\nold\n{STACK_SECTION_END}\n\nFooter");
+        assert_eq!(
+            remove_stack_section(&body).as_deref(),
+            Some("Intro\n\nFooter")
+        );
+        assert_eq!(remove_stack_section("no section"), None);
+    }
+
+    #[test]
+    fn test_comment_policy() {
+        assert_eq!(
+            CommentPolicy::parse("multi-only").unwrap(),
+            CommentPolicy::MultiOnly
+        );
+        assert!(CommentPolicy::parse("sometimes").is_err());
+        assert!(CommentPolicy::Always.wants_navigation(1));
+        assert!(!CommentPolicy::MultiOnly.wants_navigation(1));
+        assert!(CommentPolicy::MultiOnly.wants_navigation(2));
+        assert!(!CommentPolicy::Never.wants_navigation(3));
+    }
+
+    #[test]
+    fn test_stack_placement_parse() {
+        assert_eq!(StackPlacement::parse("body").unwrap(), StackPlacement::Body);
+        assert!(StackPlacement::Both.in_comment() && StackPlacement::Both.in_body());
+        assert!(!StackPlacement::Comment.in_body());
+        assert!(StackPlacement::parse("description").is_err());
+    }
+
     // === Plan helper tests ===
 
     #[test]
@@ -708,6 +1668,7 @@ mod tests {
             existing_prs: HashMap::new(),
             remote: "origin".to_string(),
             default_branch: "main".to_string(),
+            drift: vec![],
         };
 
         assert!(plan.is_empty());
@@ -728,12 +1689,14 @@ mod tests {
                     bookmark: bm,
                     base_branch: "main".to_string(),
                     title: "Add feat-a".to_string(),
+                    body: String::new(),
                     draft: false,
                 }),
             ],
             existing_prs: HashMap::new(),
             remote: "origin".to_string(),
             default_branch: "main".to_string(),
+            drift: vec![],
         };
 
         assert!(!plan.is_empty());
@@ -0,0 +1,228 @@
+//! Execution journal for resumable submissions.
+//!
+//! Before executing a plan, the full plan is written to
+//! `.jj/repo/ryu/journal.toml`. Each completed step is recorded as it
+//! finishes, so an interrupted run (network drop, rate limit, Ctrl-C) can be
+//! resumed from the first incomplete step instead of re-planning from scratch.
+//! The journal is removed once a submission completes.
+
+use crate::error::{Error, Result};
+use crate::submit::SubmissionPlan;
+use crate::tracking::{read_toml, remove_with_backup, ryu_dir, write_toml_atomic};
+use crate::types::PullRequest;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Current version of the journal file format.
+pub const JOURNAL_VERSION: u32 = 1;
+
+/// Filename for the execution journal.
+const JOURNAL_FILE: &str = "journal.toml";
+
+/// On-disk record of an in-flight submission.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionJournal {
+    /// File format version.
+    pub version: u32,
+    /// When the submission started.
+    pub started_at: DateTime<Utc>,
+    /// The plan being executed.
+    pub plan: SubmissionPlan,
+    /// Indices into `plan.execution_steps` that have completed.
+    #[serde(default)]
+    pub completed_steps: BTreeSet<usize>,
+    /// PRs created or updated so far, by bookmark name.
+    #[serde(default)]
+    pub prs: HashMap<String, PullRequest>,
+    /// Error that interrupted the run, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl SubmissionJournal {
+    /// Start a new journal for a plan.
+    pub fn new(plan: SubmissionPlan) -> Self {
+        Self {
+            version: JOURNAL_VERSION,
+            started_at: Utc::now(),
+            plan,
+            completed_steps: BTreeSet::new(),
+            prs: HashMap::new(),
+            last_error: None,
+        }
+    }
+
+    /// Check whether a step has completed.
+    pub fn is_completed(&self, step_idx: usize) -> bool {
+        self.completed_steps.contains(&step_idx)
+    }
+
+    /// Record a completed step, along with the PR it produced (if any).
+    pub fn mark_completed(&mut self, step_idx: usize, pr: Option<(String, PullRequest)>) {
+        self.completed_steps.insert(step_idx);
+        if let Some((bookmark, pr)) = pr {
+            self.prs.insert(bookmark, pr);
+        }
+    }
+
+    /// Number of execution steps that have completed.
+    pub fn completed_count(&self) -> usize {
+        self.completed_steps.len()
+    }
+
+    /// Total number of execution steps in the plan.
+    pub fn total_count(&self) -> usize {
+        self.plan.execution_steps.len()
+    }
+
+    /// Index of the first step that has not completed.
+    pub fn first_incomplete_step(&self) -> Option<usize> {
+        (0..self.total_count()).find(|idx| !self.is_completed(*idx))
+    }
+}
+
+/// Get path to the journal file.
+pub fn journal_path(workspace_root: &Path) -> PathBuf {
+    ryu_dir(workspace_root).join(JOURNAL_FILE)
+}
+
+/// Load the journal of an interrupted submission.
+///
+/// Returns `None` if no submission is in progress. Falls back to
+/// `journal.toml.bak` if the file is corrupted.
+pub fn load_journal(workspace_root: &Path) -> Result<Option<SubmissionJournal>> {
+    read_toml(&journal_path(workspace_root))
+}
+
+/// Save the journal to disk.
+///
+/// Creates the `.jj/repo/ryu/` directory if it doesn't exist.
+pub fn save_journal(workspace_root: &Path, journal: &SubmissionJournal) -> Result<()> {
+    let path = journal_path(workspace_root);
+
+    let mut journal_to_save = journal.clone();
+    journal_to_save.version = JOURNAL_VERSION;
+
+    let content = toml::to_string_pretty(&journal_to_save)
+        .map_err(|e| Error::Tracking(format!("failed to serialize journal: {e}")))?;
+
+    let content_with_header = format!(
+        "# ryu submission journal - present only while a submission is incomplete\n\
+         # Resume with 'ryu submit --resume'; delete to discard\n\n{content}"
+    );
+
+    write_toml_atomic::<SubmissionJournal>(&path, &content_with_header)
+}
+
+/// Remove the journal after a submission completes.
+pub fn clear_journal(workspace_root: &Path) -> Result<()> {
+    remove_with_backup(&journal_path(workspace_root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submit::plan::{CreateRef, PushRef};
+    use crate::submit::{ExecutionConstraint, ExecutionStep, PrToCreate};
+    use crate::types::{Bookmark, NarrowedBookmarkSegment};
+    use tempfile::TempDir;
+
+    fn setup_fake_jj_workspace() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join(".jj").join("repo")).unwrap();
+        temp
+    }
+
+    fn make_bookmark(name: &str) -> Bookmark {
+        Bookmark {
+            name: name.to_string(),
+            commit_id: format!("{name}_commit"),
+            change_id: format!("{name}_change"),
+            has_remote: false,
+            is_synced: false,
+            is_conflicted: false,
+            has_conflicts: false,
+            is_divergent: false,
+        }
+    }
+
+    fn make_plan() -> SubmissionPlan {
+        let bm = make_bookmark("feat-a");
+        SubmissionPlan {
+            segments: vec![NarrowedBookmarkSegment {
+                bookmark: bm.clone(),
+                changes: vec![],
+            }],
+            constraints: vec![ExecutionConstraint::PushBeforeCreate {
+                push: PushRef("feat-a".to_string()),
+                create: CreateRef("feat-a".to_string()),
+            }],
+            execution_steps: vec![
+                ExecutionStep::Push(bm.clone()),
+                ExecutionStep::CreatePr(PrToCreate {
+                    bookmark: bm,
+                    base_branch: "main".to_string(),
+                    title: "Add feat-a".to_string(),
+                    body: String::new(),
+                    draft: false,
+                }),
+            ],
+            existing_prs: HashMap::new(),
+            remote: "origin".to_string(),
+            default_branch: "main".to_string(),
+            drift: vec![],
+        }
+    }
+
+    #[test]
+    fn test_load_missing_journal_returns_none() {
+        let temp = setup_fake_jj_workspace();
+        assert!(load_journal(temp.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_roundtrip_preserves_progress() {
+        let temp = setup_fake_jj_workspace();
+
+        let mut journal = SubmissionJournal::new(make_plan());
+        journal.mark_completed(0, None);
+        journal.last_error = Some("connection reset".to_string());
+        save_journal(temp.path(), &journal).unwrap();
+
+        let loaded = load_journal(temp.path()).unwrap().unwrap();
+        assert_eq!(loaded.total_count(), 2);
+        assert_eq!(loaded.completed_count(), 1);
+        assert_eq!(loaded.first_incomplete_step(), Some(1));
+        assert_eq!(loaded.last_error.as_deref(), Some("connection reset"));
+        assert!(matches!(
+            loaded.plan.execution_steps[1],
+            ExecutionStep::CreatePr(_)
+        ));
+        assert_eq!(loaded.plan.constraints.len(), 1);
+    }
+
+    #[test]
+    fn test_clear_removes_journal() {
+        let temp = setup_fake_jj_workspace();
+        save_journal(temp.path(), &SubmissionJournal::new(make_plan())).unwrap();
+        assert!(journal_path(temp.path()).exists());
+
+        clear_journal(temp.path()).unwrap();
+        assert!(!journal_path(temp.path()).exists());
+
+        // Clearing again is a no-op
+        clear_journal(temp.path()).unwrap();
+    }
+
+    #[test]
+    fn test_first_incomplete_step_skips_completed() {
+        let mut journal = SubmissionJournal::new(make_plan());
+        assert_eq!(journal.first_incomplete_step(), Some(0));
+
+        journal.mark_completed(0, None);
+        journal.mark_completed(1, None);
+        assert_eq!(journal.first_incomplete_step(), None);
+    }
+}
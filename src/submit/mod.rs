@@ -5,17 +5,31 @@
 //! 2. Planning - determine what PRs to create/update
 //! 3. Execution - perform the actual operations
 
+mod adopt;
 mod analysis;
+mod edit;
 mod execute;
+mod journal;
 mod plan;
+mod pr_body;
 mod progress;
+mod template;
 
+pub use adopt::{AdoptedStack, StackSource, discover_stack, discover_stack_below};
 pub use analysis::{
-    SubmissionAnalysis, analyze_submission, create_narrowed_segments, generate_pr_title,
-    get_base_branch, select_bookmark_for_segment,
+    SubmissionAnalysis, analyze_submission, check_submittable, create_narrowed_segments,
+    generate_pr_title, get_base_branch, select_bookmark_for_segment,
 };
+pub use edit::{EDIT_COMMENT_PREFIX, apply_pr_edits, format_pr_edits, has_prs_to_create};
 pub use execute::{
-    STACK_COMMENT_THIS_PR, SubmissionResult, execute_submission, format_stack_comment,
+    CommentPolicy, DEFAULT_CONCURRENCY, DIAGRAM_CONFIG_KEY, ExecutionOptions, JournalStatus,
+    PLACEMENT_CONFIG_KEY, POLICY_CONFIG_KEY, STACK_COMMENT_THIS_PR, STACK_SECTION_END,
+    STACK_SECTION_START, StackPlacement, SubmissionResult, delete_stack_comment,
+    execute_submission, format_stack_comment, parse_stack_comment_data, remove_stack_section,
+    render_stack_comment, replace_stack_section, resume_submission,
+};
+pub use journal::{
+    JOURNAL_VERSION, SubmissionJournal, clear_journal, journal_path, load_journal, save_journal,
 };
 
 // Exports for testing stack comment formatting (used by integration tests)
@@ -24,7 +38,10 @@ pub use execute::{
     build_stack_comment_data,
 };
 pub use plan::{
-    ExecutionConstraint, ExecutionStep, PrBaseUpdate, PrToCreate, SubmissionPlan,
-    create_submission_plan,
+    ExecutionConstraint, ExecutionStep, PrBaseUpdate, PrDrift, PrDriftKind, PrToCreate,
+    SubmissionPlan, batched_execution_layers, create_submission_plan,
+    create_submission_plan_with_cache, execution_layers,
 };
+pub use pr_body::{build_pr_body, fill_pr_bodies, load_pr_template};
 pub use progress::{NoopProgress, Phase, ProgressCallback, PushStatus};
+pub use template::{StackCommentTemplate, TEMPLATE_CONFIG_KEY, load_stack_comment_template};
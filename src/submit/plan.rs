@@ -6,12 +6,14 @@ use crate::error::{Error, Result};
 use crate::platform::PlatformService;
 use crate::submit::SubmissionAnalysis;
 use crate::submit::analysis::{generate_pr_title, get_base_branch};
-use crate::types::{Bookmark, NarrowedBookmarkSegment, PullRequest};
+use crate::tracking::{CachedPr, PrCache};
+use crate::types::{Bookmark, NarrowedBookmarkSegment, PrState, PullRequest};
+use serde::{Deserialize, Serialize};
 use std::cmp::Reverse;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 
 /// Information about a PR that needs to be created
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrToCreate {
     /// Bookmark for this PR
     pub bookmark: Bookmark,
@@ -19,12 +21,15 @@ pub struct PrToCreate {
     pub base_branch: String,
     /// Generated PR title
     pub title: String,
+    /// PR description (commit descriptions merged into the PR template)
+    #[serde(default)]
+    pub body: String,
     /// Whether to create as draft
     pub draft: bool,
 }
 
 /// Information about a PR that needs its base updated
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrBaseUpdate {
     /// Bookmark for this PR
     pub bookmark: Bookmark,
@@ -37,7 +42,7 @@ pub struct PrBaseUpdate {
 }
 
 /// Ordered execution step for a submission plan
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExecutionStep {
     /// Push bookmark to remote
     Push(Bookmark),
@@ -86,21 +91,70 @@ impl std::fmt::Display for ExecutionStep {
     }
 }
 
+/// How a cached PR association differs from what the platform reports
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrDriftKind {
+    /// The PR was closed without merging
+    Closed,
+    /// The PR was merged
+    Merged,
+    /// The PR no longer exists (or is not visible to the current token)
+    Missing,
+    /// The PR's head branch is no longer the bookmark
+    HeadChanged {
+        /// Head branch the PR now reports
+        head: String,
+    },
+    /// The PR's base was changed outside of ryu
+    Retargeted {
+        /// Base recorded in the cache
+        from: String,
+        /// Base the platform now reports
+        to: String,
+    },
+}
+
+/// A cached PR that no longer matches the platform
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrDrift {
+    /// Bookmark the PR was cached under
+    pub bookmark: String,
+    /// Cached PR number
+    pub pr_number: u64,
+    /// What changed
+    pub kind: PrDriftKind,
+}
+
+impl std::fmt::Display for PrDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: PR #{} ", self.bookmark, self.pr_number)?;
+        match &self.kind {
+            PrDriftKind::Closed => write!(f, "was closed"),
+            PrDriftKind::Merged => write!(f, "was merged"),
+            PrDriftKind::Missing => write!(f, "no longer exists"),
+            PrDriftKind::HeadChanged { head } => write!(f, "now belongs to branch {head}"),
+            PrDriftKind::Retargeted { from, to } => {
+                write!(f, "was retargeted from {from} to {to} outside ryu")
+            }
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Typed constraint system for dependency-aware scheduling
 // ═══════════════════════════════════════════════════════════════════════════
 
 /// Typed reference to a Push operation by bookmark name.
 /// Distinct from [`UpdateRef`]/[`CreateRef`] to prevent mixing constraint endpoints.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PushRef(pub String);
 
 /// Typed reference to an `UpdateBase` operation by bookmark name.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct UpdateRef(pub String);
 
 /// Typed reference to a `CreatePr` operation by bookmark name.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct CreateRef(pub String);
 
 /// Dependency constraint between execution operations.
@@ -111,7 +165,7 @@ pub struct CreateRef(pub String);
 /// Constraints may reference operations that don't exist in the current plan
 /// (e.g., a bookmark that's already synced has no `Push` node). Resolution
 /// returns `None` for such constraints, which is expected behavior.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExecutionConstraint {
     /// Push parent branch before child branch.
     /// Ensures commits are pushed in stack order (ancestors before descendants).
@@ -216,6 +270,24 @@ impl NodeRegistry {
     fn len(&self) -> usize {
         self.push.len() + self.update.len() + self.create.len() + self.publish.len()
     }
+
+    /// Register already-ordered steps, using their positions as node indices.
+    fn from_steps(steps: &[ExecutionStep]) -> Self {
+        let mut registry = Self::default();
+        for (idx, step) in steps.iter().enumerate() {
+            match step {
+                ExecutionStep::Push(bm) => registry.register_push(&bm.name, idx),
+                ExecutionStep::UpdateBase(update) => {
+                    registry.register_update(&update.bookmark.name, idx);
+                }
+                ExecutionStep::CreatePr(create) => {
+                    registry.register_create(&create.bookmark.name, idx);
+                }
+                ExecutionStep::PublishPr(pr) => registry.register_publish(&pr.head_ref, idx),
+            }
+        }
+        registry
+    }
 }
 
 impl ExecutionConstraint {
@@ -262,7 +334,7 @@ struct ExecutionNode {
 }
 
 /// Submission plan
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubmissionPlan {
     /// Segments to submit (used for stack comment generation)
     pub segments: Vec<NarrowedBookmarkSegment>,
@@ -276,6 +348,9 @@ pub struct SubmissionPlan {
     pub remote: String,
     /// Default branch name (main/master)
     pub default_branch: String,
+    /// Cached PR associations that no longer match the platform
+    #[serde(default)]
+    pub drift: Vec<PrDrift>,
 }
 
 impl SubmissionPlan {
@@ -328,17 +403,31 @@ pub async fn create_submission_plan(
     platform: &dyn PlatformService,
     remote: &str,
     default_branch: &str,
+) -> Result<SubmissionPlan> {
+    create_submission_plan_with_cache(analysis, platform, remote, default_branch, &PrCache::new())
+        .await
+}
+
+/// Create a submission plan, using cached PR numbers where available
+///
+/// Bookmarks with a cached PR for `remote` are fetched by number, which is
+/// cheaper than searching by head branch. Cached PRs that are no longer open,
+/// belong to another branch, or were retargeted are reported in
+/// [`SubmissionPlan::drift`]; bookmarks whose cached PR can't be used fall
+/// back to the head-branch search.
+pub async fn create_submission_plan_with_cache(
+    analysis: &SubmissionAnalysis,
+    platform: &dyn PlatformService,
+    remote: &str,
+    default_branch: &str,
+    pr_cache: &PrCache,
 ) -> Result<SubmissionPlan> {
     let segments = &analysis.segments;
     let bookmarks: Vec<&Bookmark> = segments.iter().map(|s| &s.bookmark).collect();
 
-    // Check for existing PRs
-    let mut existing_prs = HashMap::new();
-    for bookmark in &bookmarks {
-        if let Some(pr) = platform.find_existing_pr(&bookmark.name).await? {
-            existing_prs.insert(bookmark.name.clone(), pr);
-        }
-    }
+    let names: Vec<&str> = bookmarks.iter().map(|b| b.name.as_str()).collect();
+    let (existing_prs, drift) =
+        find_existing_prs_cached(&names, platform, remote, pr_cache).await?;
 
     // Collect raw operations (unordered)
     let mut bookmarks_needing_push = Vec::new();
@@ -373,6 +462,7 @@ pub async fn create_submission_plan(
                 bookmark: (*bookmark).clone(),
                 base_branch,
                 title,
+                body: String::new(),
                 draft: false,
             });
         }
@@ -394,9 +484,81 @@ pub async fn create_submission_plan(
         existing_prs,
         remote: remote.to_string(),
         default_branch: default_branch.to_string(),
+        drift,
     })
 }
 
+/// Look up existing PRs, fetching cached PR numbers directly
+///
+/// Bookmarks without a usable cached PR are looked up with one batched
+/// head-branch search.
+async fn find_existing_prs_cached(
+    names: &[&str],
+    platform: &dyn PlatformService,
+    remote: &str,
+    pr_cache: &PrCache,
+) -> Result<(HashMap<String, PullRequest>, Vec<PrDrift>)> {
+    let cached: Vec<(&str, &CachedPr)> = names
+        .iter()
+        .filter_map(|name| {
+            pr_cache
+                .get(name)
+                .filter(|entry| entry.remote == remote)
+                .map(|entry| (*name, entry))
+        })
+        .collect();
+
+    let fetched = futures::future::try_join_all(
+        cached
+            .iter()
+            .map(|(_, entry)| platform.get_pr(entry.number)),
+    )
+    .await?;
+
+    let mut existing_prs = HashMap::new();
+    let mut drift = Vec::new();
+
+    for ((name, entry), pr) in cached.into_iter().zip(fetched) {
+        let kind = match pr {
+            None => PrDriftKind::Missing,
+            Some(pr) if pr.head_ref != name => PrDriftKind::HeadChanged { head: pr.head_ref },
+            Some(pr) if pr.state == PrState::Merged => PrDriftKind::Merged,
+            Some(pr) if pr.state == PrState::Closed => PrDriftKind::Closed,
+            Some(pr) => {
+                if let Some(base) = entry.base.as_ref().filter(|base| **base != pr.base_ref) {
+                    drift.push(PrDrift {
+                        bookmark: name.to_string(),
+                        pr_number: pr.number,
+                        kind: PrDriftKind::Retargeted {
+                            from: base.clone(),
+                            to: pr.base_ref.clone(),
+                        },
+                    });
+                }
+                existing_prs.insert(name.to_string(), pr);
+                continue;
+            }
+        };
+        drift.push(PrDrift {
+            bookmark: name.to_string(),
+            pr_number: entry.number,
+            kind,
+        });
+    }
+
+    // Anything not resolved from the cache goes through the head-branch search
+    let uncached: Vec<&str> = names
+        .iter()
+        .filter(|name| !existing_prs.contains_key(**name))
+        .copied()
+        .collect();
+    if !uncached.is_empty() {
+        existing_prs.extend(platform.find_existing_prs(&uncached).await?);
+    }
+
+    Ok((existing_prs, drift))
+}
+
 /// Build dependency-ordered execution steps.
 ///
 /// Returns both the constraints (for debugging/display) and the sorted execution steps.
@@ -428,7 +590,7 @@ fn build_execution_steps(
     );
 
     // Phase 3: Resolve constraints to edges
-    let edges = resolve_constraints(&constraints, &registry);
+    let edges = resolve_constraints(&constraints, &registry, registry.len());
 
     // Phase 4: Topological sort
     let steps = topo_sort_steps(&nodes, &edges)?;
@@ -602,8 +764,9 @@ fn build_execution_nodes(
 fn resolve_constraints(
     constraints: &[ExecutionConstraint],
     registry: &NodeRegistry,
+    node_count: usize,
 ) -> Vec<Vec<usize>> {
-    let mut edges = vec![Vec::new(); registry.len()];
+    let mut edges = vec![Vec::new(); node_count];
 
     for constraint in constraints {
         if let Some((from, to)) = constraint.resolve(registry) {
@@ -650,24 +813,13 @@ fn topo_sort_steps(nodes: &[ExecutionNode], edges: &[Vec<usize>]) -> Result<Vec<
 
     if sorted.len() != nodes.len() {
         // Collect nodes stuck in the cycle (indegree > 0 means couldn't be scheduled)
-        let cycle_nodes: Vec<String> = nodes
-            .iter()
-            .enumerate()
-            .filter(|(idx, _)| indegree[*idx] > 0)
-            .map(|(_, node)| format!("{}", node.step))
-            .collect();
-
-        tracing::error!(
-            cycle_nodes = ?cycle_nodes,
-            "Scheduler cycle detected - this is a bug in jj-ryu"
-        );
-
-        return Err(Error::SchedulerCycle {
-            message:
-                "Dependency cycle in execution plan - this is a bug in jj-ryu, please report it"
-                    .to_string(),
-            cycle_nodes,
-        });
+        return Err(cycle_error(
+            nodes
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| indegree[*idx] > 0)
+                .map(|(_, node)| &node.step),
+        ));
     }
 
     Ok(sorted
@@ -676,6 +828,133 @@ fn topo_sort_steps(nodes: &[ExecutionNode], edges: &[Vec<usize>]) -> Result<Vec<
         .collect())
 }
 
+/// Build the error reported when constraints form a cycle
+fn cycle_error<'a>(stuck: impl Iterator<Item = &'a ExecutionStep>) -> Error {
+    let cycle_nodes: Vec<String> = stuck.map(ToString::to_string).collect();
+
+    tracing::error!(
+        cycle_nodes = ?cycle_nodes,
+        "Scheduler cycle detected - this is a bug in jj-ryu"
+    );
+
+    Error::SchedulerCycle {
+        message: "Dependency cycle in execution plan - this is a bug in jj-ryu, please report it"
+            .to_string(),
+        cycle_nodes,
+    }
+}
+
+/// Group ordered execution steps into dependency layers.
+///
+/// Every step in a layer depends only on steps in earlier layers, so the steps
+/// of one layer may run concurrently. Layers hold indices into `steps`, in plan
+/// order. Constraints are re-resolved against `steps` so this stays correct
+/// after the CLI filters steps or appends publish steps to a plan.
+pub fn execution_layers(
+    steps: &[ExecutionStep],
+    constraints: &[ExecutionConstraint],
+) -> Result<Vec<Vec<usize>>> {
+    let registry = NodeRegistry::from_steps(steps);
+    let edges = resolve_constraints(constraints, &registry, steps.len());
+    let groups: Vec<usize> = (0..steps.len()).collect();
+
+    layer_groups(&groups, &edges)
+        .map_err(|stuck| cycle_error(stuck.into_iter().map(|idx| &steps[idx])))
+}
+
+/// Group steps into dependency layers, with all pushes as a single node
+///
+/// A batched push sends every bookmark in one `git push`, so ordering between
+/// individual pushes no longer matters and they share one layer. If merging
+/// the pushes would create a cycle (a retarget that must happen both after
+/// one push and before another), this falls back to [`execution_layers`].
+pub fn batched_execution_layers(
+    steps: &[ExecutionStep],
+    constraints: &[ExecutionConstraint],
+) -> Result<Vec<Vec<usize>>> {
+    let Some(first_push) = steps
+        .iter()
+        .position(|step| matches!(step, ExecutionStep::Push(_)))
+    else {
+        return execution_layers(steps, constraints);
+    };
+
+    let registry = NodeRegistry::from_steps(steps);
+    let edges = resolve_constraints(constraints, &registry, steps.len());
+    let groups: Vec<usize> = steps
+        .iter()
+        .enumerate()
+        .map(|(idx, step)| match step {
+            ExecutionStep::Push(_) => first_push,
+            _ => idx,
+        })
+        .collect();
+
+    layer_groups(&groups, &edges).or_else(|_| execution_layers(steps, constraints))
+}
+
+/// Level-by-level Kahn's algorithm over groups of steps
+///
+/// `groups[i]` is the representative step of step `i`'s group; a group is
+/// scheduled as a unit. Returns step indices per layer (sorted), or the steps
+/// stuck in a cycle.
+fn layer_groups(
+    groups: &[usize],
+    edges: &[Vec<usize>],
+) -> std::result::Result<Vec<Vec<usize>>, Vec<usize>> {
+    let n = groups.len();
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (idx, &group) in groups.iter().enumerate() {
+        members[group].push(idx);
+    }
+
+    let mut group_edges: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut indegree = vec![0usize; n];
+    for (from, edge_list) in edges.iter().enumerate() {
+        for &to in edge_list {
+            let (from_group, to_group) = (groups[from], groups[to]);
+            if from_group != to_group {
+                group_edges[from_group].push(to_group);
+                indegree[to_group] += 1;
+            }
+        }
+    }
+
+    let mut layers = Vec::new();
+    let mut scheduled = 0;
+    let mut current: Vec<usize> = (0..n)
+        .filter(|&g| !members[g].is_empty() && indegree[g] == 0)
+        .collect();
+
+    while !current.is_empty() {
+        let mut next = Vec::new();
+        for &group in &current {
+            for &to in &group_edges[group] {
+                indegree[to] -= 1;
+                if indegree[to] == 0 {
+                    next.push(to);
+                }
+            }
+        }
+        next.sort_unstable();
+
+        let mut layer: Vec<usize> = current
+            .iter()
+            .flat_map(|&group| members[group].iter().copied())
+            .collect();
+        layer.sort_unstable();
+        scheduled += layer.len();
+        layers.push(layer);
+        current = next;
+    }
+
+    if scheduled != n {
+        return Err((0..n).filter(|&idx| indegree[groups[idx]] > 0).collect());
+    }
+
+    Ok(layers)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -687,6 +966,9 @@ mod tests {
             change_id: format!("{name}_change"),
             has_remote,
             is_synced,
+            is_conflicted: false,
+            has_conflicts: false,
+            is_divergent: false,
         }
     }
 
@@ -706,6 +988,9 @@ mod tests {
             title: format!("PR for {bookmark}"),
             node_id: Some(format!("PR_node_{number}")),
             is_draft: false,
+            state: PrState::Open,
+            comments: None,
+            checks: None,
         }
     }
 
@@ -728,6 +1013,7 @@ mod tests {
             bookmark: bookmark.clone(),
             base_branch: base_branch.to_string(),
             title: format!("Add {}", bookmark.name),
+            body: String::new(),
             draft: false,
         }
     }
@@ -757,6 +1043,7 @@ mod tests {
             bookmark: make_bookmark("feat-a", false, false),
             base_branch: "main".to_string(),
             title: "Add feature A".to_string(),
+            body: String::new(),
             draft: false,
         };
 
@@ -881,6 +1168,130 @@ mod tests {
         );
     }
 
+    fn layer_of(layers: &[Vec<usize>], step_idx: usize) -> usize {
+        layers
+            .iter()
+            .position(|layer| layer.contains(&step_idx))
+            .expect("every step is scheduled in some layer")
+    }
+
+    #[test]
+    fn test_execution_layers_respect_constraints() {
+        let bm_a = make_bookmark("a", false, false);
+        let bm_b = make_bookmark("b", false, false);
+        let segments = vec![make_segment("a"), make_segment("b")];
+        let pushes = vec![bm_a.clone(), bm_b.clone()];
+        let creates = vec![make_create(&bm_a, "main"), make_create(&bm_b, "a")];
+
+        let (constraints, steps) =
+            build_execution_steps(&segments, &pushes, &[], &creates, &[]).unwrap();
+        let layers = execution_layers(&steps, &constraints).unwrap();
+
+        let idx = |pred: &dyn Fn(&ExecutionStep) -> bool| {
+            layer_of(&layers, find_step_index(&steps, pred).unwrap())
+        };
+        let push_a = idx(&|s| matches!(s, ExecutionStep::Push(b) if b.name == "a"));
+        let push_b = idx(&|s| matches!(s, ExecutionStep::Push(b) if b.name == "b"));
+        let create_a = idx(&|s| matches!(s, ExecutionStep::CreatePr(c) if c.bookmark.name == "a"));
+        let create_b = idx(&|s| matches!(s, ExecutionStep::CreatePr(c) if c.bookmark.name == "b"));
+
+        assert!(push_a < push_b);
+        assert!(push_a < create_a);
+        assert!(push_b < create_b);
+        assert!(create_a < create_b);
+        // Independent steps share a layer: push b runs alongside create a
+        assert_eq!(push_b, create_a);
+        assert_eq!(layers.iter().map(Vec::len).sum::<usize>(), steps.len());
+    }
+
+    #[test]
+    fn test_execution_layers_independent_steps_share_layer() {
+        let steps = vec![
+            ExecutionStep::PublishPr(make_pr(1, "a", "main")),
+            ExecutionStep::PublishPr(make_pr(2, "b", "a")),
+        ];
+
+        let layers = execution_layers(&steps, &[]).unwrap();
+        assert_eq!(layers, vec![vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_execution_layers_detects_cycle() {
+        let bm_a = make_bookmark("a", false, false);
+        let bm_b = make_bookmark("b", false, false);
+        let steps = vec![ExecutionStep::Push(bm_a), ExecutionStep::Push(bm_b)];
+        let constraints = vec![
+            ExecutionConstraint::PushOrder {
+                parent: PushRef("a".to_string()),
+                child: PushRef("b".to_string()),
+            },
+            ExecutionConstraint::PushOrder {
+                parent: PushRef("b".to_string()),
+                child: PushRef("a".to_string()),
+            },
+        ];
+
+        let err = execution_layers(&steps, &constraints).unwrap_err();
+        assert!(matches!(err, Error::SchedulerCycle { .. }));
+    }
+
+    #[test]
+    fn test_batched_execution_layers_push_together() {
+        let bm_a = make_bookmark("a", false, false);
+        let bm_b = make_bookmark("b", false, false);
+        let segments = vec![make_segment("a"), make_segment("b")];
+        let pushes = vec![bm_a.clone(), bm_b.clone()];
+        let creates = vec![make_create(&bm_a, "main"), make_create(&bm_b, "a")];
+
+        let (constraints, steps) =
+            build_execution_steps(&segments, &pushes, &[], &creates, &[]).unwrap();
+        let layers = batched_execution_layers(&steps, &constraints).unwrap();
+
+        let idx = |pred: &dyn Fn(&ExecutionStep) -> bool| {
+            layer_of(&layers, find_step_index(&steps, pred).unwrap())
+        };
+        let push_a = idx(&|s| matches!(s, ExecutionStep::Push(b) if b.name == "a"));
+        let push_b = idx(&|s| matches!(s, ExecutionStep::Push(b) if b.name == "b"));
+        let create_a = idx(&|s| matches!(s, ExecutionStep::CreatePr(c) if c.bookmark.name == "a"));
+        let create_b = idx(&|s| matches!(s, ExecutionStep::CreatePr(c) if c.bookmark.name == "b"));
+
+        assert_eq!(push_a, 0);
+        assert_eq!(push_b, 0);
+        assert_eq!(create_a, 1);
+        assert_eq!(create_b, 2);
+        assert_eq!(layers.iter().map(Vec::len).sum::<usize>(), steps.len());
+    }
+
+    #[test]
+    fn test_batched_execution_layers_falls_back_when_batching_cycles() {
+        let bm_a = make_bookmark("a", true, false);
+        let bm_b = make_bookmark("b", true, false);
+        // The update must land after pushing a but before pushing b
+        let steps = vec![
+            ExecutionStep::Push(bm_a),
+            ExecutionStep::UpdateBase(PrBaseUpdate {
+                bookmark: bm_b.clone(),
+                current_base: "a".to_string(),
+                expected_base: "main".to_string(),
+                pr: make_pr(2, "b", "a"),
+            }),
+            ExecutionStep::Push(bm_b),
+        ];
+        let constraints = vec![
+            ExecutionConstraint::PushBeforeRetarget {
+                base: PushRef("a".to_string()),
+                pr: UpdateRef("b".to_string()),
+            },
+            ExecutionConstraint::RetargetBeforePush {
+                pr: UpdateRef("b".to_string()),
+                old_base: PushRef("b".to_string()),
+            },
+        ];
+
+        let layers = batched_execution_layers(&steps, &constraints).unwrap();
+        assert_eq!(layers, vec![vec![0], vec![1], vec![2]]);
+    }
+
     #[test]
     fn test_plan_is_empty() {
         let plan = SubmissionPlan {
@@ -890,6 +1301,7 @@ mod tests {
             existing_prs: HashMap::new(),
             remote: "origin".to_string(),
             default_branch: "main".to_string(),
+            drift: vec![],
         };
 
         assert!(plan.is_empty());
@@ -910,6 +1322,7 @@ mod tests {
             existing_prs: HashMap::new(),
             remote: "origin".to_string(),
             default_branch: "main".to_string(),
+            drift: vec![],
         };
 
         assert!(!plan.is_empty());
@@ -0,0 +1,405 @@
+//! Descriptions for newly created PRs
+//!
+//! The body is built from the descriptions of the commits in the PR's
+//! segment. If the repo has a PR template for its platform, the commit
+//! content goes into the template's description section and any section
+//! whose heading matches a commit trailer (e.g. `## Testing` and
+//! `Testing: ran the e2e suite`) is filled with the trailer's value.
+
+use crate::error::Result;
+use crate::repo::JjWorkspace;
+use crate::submit::plan::{ExecutionStep, SubmissionPlan};
+use crate::types::Platform;
+
+/// Revision templates are read from, so unpushed edits don't leak into PRs
+const TEMPLATE_REVISION: &str = "trunk()";
+
+/// Single-file templates, in the order the platform looks for them
+const GITHUB_TEMPLATE_FILES: &[&str] = &[
+    ".github/pull_request_template.md",
+    ".github/PULL_REQUEST_TEMPLATE.md",
+    "pull_request_template.md",
+    "PULL_REQUEST_TEMPLATE.md",
+    "docs/pull_request_template.md",
+    "docs/PULL_REQUEST_TEMPLATE.md",
+];
+const GITLAB_TEMPLATE_FILES: &[&str] = &[
+    ".gitlab/merge_request_templates/Default.md",
+    ".gitlab/merge_request_templates/default.md",
+];
+const AZURE_TEMPLATE_FILES: &[&str] = &[
+    ".azuredevops/pull_request_template.md",
+    ".vsts/pull_request_template.md",
+    "docs/pull_request_template.md",
+    "pull_request_template.md",
+];
+
+/// Directories holding several templates; `default.md` wins, else the first
+const GITHUB_TEMPLATE_DIRS: &[&str] = &[
+    ".github/PULL_REQUEST_TEMPLATE",
+    ".github/pull_request_template",
+    "docs/PULL_REQUEST_TEMPLATE",
+    "PULL_REQUEST_TEMPLATE",
+];
+
+/// Template headings (normalized) that receive the commit descriptions
+const DESCRIPTION_HEADINGS: &[&str] = &[
+    "description",
+    "summary",
+    "changes",
+    "whatchanged",
+    "what",
+    "overview",
+];
+
+/// Read the platform's PR template from trunk, if the repo has one
+pub async fn load_pr_template(
+    workspace: &mut JjWorkspace,
+    platform: Platform,
+) -> Result<Option<String>> {
+    let (files, dirs) = match platform {
+        Platform::GitHub => (GITHUB_TEMPLATE_FILES, GITHUB_TEMPLATE_DIRS),
+        Platform::GitLab => (GITLAB_TEMPLATE_FILES, &[][..]),
+        Platform::AzureDevOps => (AZURE_TEMPLATE_FILES, &[][..]),
+    };
+
+    for path in files {
+        if let Some(template) = workspace.read_file_at(TEMPLATE_REVISION, path).await? {
+            return Ok(Some(template));
+        }
+    }
+
+    for dir in dirs {
+        let candidates: Vec<String> = workspace
+            .list_files_at(TEMPLATE_REVISION, dir)?
+            .into_iter()
+            .filter(|path| path.to_ascii_lowercase().ends_with(".md"))
+            .collect();
+        let chosen = candidates
+            .iter()
+            .find(|path| path.to_ascii_lowercase().ends_with("/default.md"))
+            .or_else(|| candidates.first());
+        if let Some(path) = chosen {
+            return workspace.read_file_at(TEMPLATE_REVISION, path).await;
+        }
+    }
+
+    Ok(None)
+}
+
+/// Set the body of every PR the plan creates
+pub async fn fill_pr_bodies(
+    plan: &mut SubmissionPlan,
+    workspace: &mut JjWorkspace,
+    platform: Platform,
+) -> Result<()> {
+    if !plan
+        .execution_steps
+        .iter()
+        .any(|step| matches!(step, ExecutionStep::CreatePr(_)))
+    {
+        return Ok(());
+    }
+
+    let template = load_pr_template(workspace, platform).await?;
+    for step in &mut plan.execution_steps {
+        let ExecutionStep::CreatePr(create) = step else {
+            continue;
+        };
+        let Some(segment) = plan
+            .segments
+            .iter()
+            .find(|segment| segment.bookmark.name == create.bookmark.name)
+        else {
+            continue;
+        };
+        // Segment changes are newest first
+        let descriptions = segment
+            .changes
+            .iter()
+            .rev()
+            .map(|change| workspace.commit_description(&change.commit_id))
+            .collect::<Result<Vec<_>>>()?;
+        create.body = build_pr_body(&descriptions, template.as_deref());
+    }
+
+    Ok(())
+}
+
+/// Build a PR body from commit descriptions (root to leaf) and a template
+///
+/// Without a template the body is just the commit content. With one, the
+/// content fills the first description-like section (or goes above the
+/// template if it has none) and trailers fill sections with matching
+/// headings. HTML comments in filled sections are kept.
+pub fn build_pr_body(descriptions: &[String], template: Option<&str>) -> String {
+    let parsed: Vec<ParsedDescription<'_>> = descriptions
+        .iter()
+        .map(|description| ParsedDescription::parse(description))
+        .collect();
+    let content = description_content(&parsed);
+
+    let Some(template) = template.filter(|t| !t.trim().is_empty()) else {
+        return content;
+    };
+
+    let trailers: Vec<(&str, &str)> = parsed
+        .iter()
+        .flat_map(|p| p.trailers.iter().copied())
+        .collect();
+    let mut content_placed = content.is_empty();
+    let mut out = String::new();
+
+    for section in split_sections(template) {
+        let Some((heading_line, heading)) = section.heading else {
+            push_lines(&mut out, &section.body);
+            continue;
+        };
+        out.push_str(heading_line);
+        out.push('\n');
+
+        let key = normalize(heading);
+        let values: Vec<&str> = trailers
+            .iter()
+            .filter(|(name, _)| normalize(name) == key)
+            .map(|(_, value)| *value)
+            .collect();
+        if !values.is_empty() {
+            push_filled(&mut out, &section.body, &values.join("\n"));
+        } else if !content_placed && DESCRIPTION_HEADINGS.contains(&key.as_str()) {
+            push_filled(&mut out, &section.body, &content);
+            content_placed = true;
+        } else {
+            push_lines(&mut out, &section.body);
+        }
+    }
+
+    let out = out.trim_end();
+    if content_placed {
+        out.to_string()
+    } else {
+        format!("{content}\n\n{out}")
+    }
+}
+
+/// A commit description split into subject, body and trailers
+struct ParsedDescription<'a> {
+    subject: &'a str,
+    body: &'a str,
+    trailers: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> ParsedDescription<'a> {
+    fn parse(description: &'a str) -> Self {
+        let description = description.trim();
+        let (subject, rest) = description
+            .split_once('\n')
+            .map_or((description, ""), |(subject, rest)| (subject, rest.trim()));
+
+        // The last paragraph holds trailers if every line is one, as git does
+        let (body, last_paragraph) = rest
+            .rfind("\n\n")
+            .map_or(("", rest), |i| (rest[..i].trim_end(), &rest[i + 2..]));
+        let trailers: Option<Vec<_>> = last_paragraph.lines().map(parse_trailer).collect();
+        match trailers {
+            Some(trailers) if !trailers.is_empty() => Self {
+                subject: subject.trim(),
+                body,
+                trailers,
+            },
+            _ => Self {
+                subject: subject.trim(),
+                body: rest,
+                trailers: Vec::new(),
+            },
+        }
+    }
+}
+
+/// `Key: value` with a git-style key (letters, digits and dashes)
+fn parse_trailer(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+    let value = value.trim();
+    let valid_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    (valid_key && !value.is_empty()).then_some((key, value))
+}
+
+/// Commit bodies for the PR; with several commits each gets its subject as a
+/// heading, since the PR title only covers the first
+fn description_content(parsed: &[ParsedDescription<'_>]) -> String {
+    if let [single] = parsed {
+        return single.body.to_string();
+    }
+    parsed
+        .iter()
+        .map(|p| {
+            if p.body.is_empty() {
+                format!("### {}", p.subject)
+            } else {
+                format!("### {}\n\n{}", p.subject, p.body)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// A template section: an optional heading (line and text) and the lines
+/// up to the next heading
+struct Section<'a> {
+    heading: Option<(&'a str, &'a str)>,
+    body: Vec<&'a str>,
+}
+
+fn split_sections(template: &str) -> Vec<Section<'_>> {
+    let mut sections = vec![Section {
+        heading: None,
+        body: Vec::new(),
+    }];
+    let mut in_fence = false;
+    for line in template.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        match heading_text(line).filter(|_| !in_fence) {
+            Some(text) => sections.push(Section {
+                heading: Some((line, text)),
+                body: Vec::new(),
+            }),
+            None => sections
+                .last_mut()
+                .expect("sections starts non-empty")
+                .body
+                .push(line),
+        }
+    }
+    sections
+}
+
+/// Text of a Markdown ATX heading (`## Testing`)
+fn heading_text(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let rest = trimmed.trim_start_matches('#');
+    let level = trimmed.len() - rest.len();
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim_end())
+}
+
+/// Lowercased alphanumerics, so `How to test` matches `How-To-Test`
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn push_lines(out: &mut String, lines: &[&str]) {
+    for line in lines {
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+/// Replace a section's body with `value`, keeping its HTML comments
+fn push_filled(out: &mut String, body: &[&str], value: &str) {
+    let body = body.join("\n");
+    let mut rest = body.as_str();
+    while let Some(start) = rest.find("<!--") {
+        let Some(len) = rest[start..].find("-->") else {
+            break;
+        };
+        out.push_str(&rest[start..start + len + 3]);
+        out.push('\n');
+        rest = &rest[start + len + 3..];
+    }
+    out.push_str(value);
+    out.push_str("\n\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "## Description\n\n<!-- What does this change? -->\n\n## Testing\n\n<!-- How was it tested? -->\n- [ ] Unit tests\n\n## Checklist\n\n- [ ] Docs updated\n";
+
+    #[test]
+    fn test_body_without_template_is_commit_body() {
+        let descriptions = vec!["Add login\n\nUses the new session store.\n".to_string()];
+        assert_eq!(
+            build_pr_body(&descriptions, None),
+            "Uses the new session store."
+        );
+    }
+
+    #[test]
+    fn test_body_strips_trailers() {
+        let descriptions = vec![
+            "Add login\n\nUses the new session store.\n\nSigned-off-by: A <a@b.c>".to_string(),
+        ];
+        assert_eq!(
+            build_pr_body(&descriptions, None),
+            "Uses the new session store."
+        );
+    }
+
+    #[test]
+    fn test_multiple_commits_get_subject_headings() {
+        let descriptions = vec![
+            "Add session store\n\nBacked by redis.".to_string(),
+            "Add login".to_string(),
+        ];
+        assert_eq!(
+            build_pr_body(&descriptions, None),
+            "### Add session store\n\nBacked by redis.\n\n### Add login"
+        );
+    }
+
+    #[test]
+    fn test_template_description_and_trailer_sections_filled() {
+        let descriptions = vec![
+            "Add login\n\nUses the new session store.\n\nTesting: ran the e2e suite".to_string(),
+        ];
+        let body = build_pr_body(&descriptions, Some(TEMPLATE));
+        assert_eq!(
+            body,
+            "## Description\n<!-- What does this change? -->\nUses the new session store.\n\n\
+             ## Testing\n<!-- How was it tested? -->\nran the e2e suite\n\n\
+             ## Checklist\n\n- [ ] Docs updated"
+        );
+    }
+
+    #[test]
+    fn test_template_without_description_section_gets_content_above() {
+        let descriptions = vec!["Add login\n\nUses the new session store.".to_string()];
+        let body = build_pr_body(&descriptions, Some("## Checklist\n\n- [ ] Docs updated\n"));
+        assert_eq!(
+            body,
+            "Uses the new session store.\n\n## Checklist\n\n- [ ] Docs updated"
+        );
+    }
+
+    #[test]
+    fn test_trailer_keys_match_headings_loosely() {
+        let descriptions = vec!["Fix crash\n\nHow-To-Test: open the app twice".to_string()];
+        let body = build_pr_body(&descriptions, Some("### How to test?\n\nTBD\n"));
+        assert_eq!(body, "### How to test?\nopen the app twice");
+    }
+
+    #[test]
+    fn test_headings_in_code_fences_ignored() {
+        let template = "## Notes\n\n```sh\n# Testing\n```\n";
+        let descriptions = vec!["Fix crash\n\nTesting: manual".to_string()];
+        assert_eq!(
+            build_pr_body(&descriptions, Some(template)),
+            template.trim_end()
+        );
+    }
+
+    #[test]
+    fn test_trailing_prose_with_colon_not_a_trailer() {
+        let parsed = ParsedDescription::parse("Fix crash\n\nNote: this is prose, really\nand more");
+        assert!(parsed.trailers.is_empty());
+        assert_eq!(parsed.body, "Note: this is prose, really\nand more");
+    }
+}
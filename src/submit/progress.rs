@@ -6,6 +6,7 @@
 use crate::error::Error;
 use crate::types::PullRequest;
 use async_trait::async_trait;
+use std::time::Duration;
 
 /// Submission phase
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -82,6 +83,11 @@ pub trait ProgressCallback: Send + Sync {
 
     /// Called with a general status message
     async fn on_message(&self, message: &str);
+
+    /// Called before a failed platform request is retried
+    ///
+    /// `attempt` counts retries from 1; `delay` is how long ryu will wait first.
+    async fn on_retry(&self, _operation: &str, _attempt: u32, _delay: Duration, _error: &Error) {}
 }
 
 /// No-op progress callback for testing or when progress isn't needed
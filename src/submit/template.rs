@@ -0,0 +1,233 @@
+//! User-defined stack comment templates.
+//!
+//! Templates are rendered with [minijinja], so they use Jinja syntax: see the
+//! minijinja documentation for the supported tags, filters and tests.
+//! Undefined variables are errors rather than empty strings, so typos in a
+//! template show up on the first submit.
+//!
+//! The template only renders the visible part of the comment; ryu always
+//! prepends the hidden `COMMENT_DATA_PREFIX` payload it uses to find its
+//! comments.
+
+use super::execute::{STACK_COMMENT_THIS_PR, StackCommentData, format_stack_diagram};
+use crate::error::{Error, Result};
+use crate::repo::JjWorkspace;
+use minijinja::{Environment, UndefinedBehavior, context};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// jj config key naming the stack comment template file
+pub const TEMPLATE_CONFIG_KEY: &str = "ryu.stack-comment.template";
+
+/// A parsed stack comment template
+#[derive(Debug, Clone)]
+pub struct StackCommentTemplate {
+    source: String,
+}
+
+impl StackCommentTemplate {
+    /// Parse a template from source
+    pub fn parse(source: &str) -> Result<Self> {
+        environment()
+            .template_from_str(source)
+            .map_err(template_error)?;
+        Ok(Self {
+            source: source.to_string(),
+        })
+    }
+
+    /// Load and parse a template file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            Error::Config(format!(
+                "Failed to read stack comment template {}: {e}",
+                path.display()
+            ))
+        })?;
+        Self::parse(&source).map_err(|e| match e {
+            Error::Config(msg) => Error::Config(format!("{}: {msg}", path.display())),
+            other => other,
+        })
+    }
+
+    /// Render the visible body of the comment for the PR at `current_idx`
+    pub fn render(&self, data: &StackCommentData, current_idx: usize) -> Result<String> {
+        let stack: Vec<TemplateItem> = data
+            .stack
+            .iter()
+            .enumerate()
+            .map(|(index, item)| TemplateItem {
+                bookmark: &item.bookmark_name,
+                title: &item.pr_title,
+                number: item.pr_number,
+                url: &item.pr_url,
+                state: item.pr_state.to_string(),
+                index,
+                is_current: index == current_idx,
+            })
+            .collect();
+
+        environment()
+            .template_from_str(&self.source)
+            .and_then(|template| {
+                template.render(context! {
+                    current => stack.get(current_idx),
+                    current_index => current_idx,
+                    stack => stack,
+                    base_branch => data.base_branch,
+                    marker => STACK_COMMENT_THIS_PR,
+                    mermaid => format_stack_diagram(data, current_idx),
+                })
+            })
+            .map_err(template_error)
+    }
+}
+
+/// A PR in the `stack` template variable
+#[derive(Serialize)]
+struct TemplateItem<'a> {
+    bookmark: &'a str,
+    title: &'a str,
+    number: u64,
+    url: &'a str,
+    state: String,
+    index: usize,
+    is_current: bool,
+}
+
+fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_keep_trailing_newline(true);
+    env
+}
+
+/// Load the template selected by `ryu.stack-comment.template`, if any
+///
+/// A relative path is resolved against the workspace root, and `~/` against
+/// the home directory.
+pub fn load_stack_comment_template(
+    workspace: &JjWorkspace,
+) -> Result<Option<StackCommentTemplate>> {
+    let Some(path) = workspace.config_string(TEMPLATE_CONFIG_KEY)? else {
+        return Ok(None);
+    };
+    let path = resolve_template_path(&path, workspace.workspace_root());
+    StackCommentTemplate::from_file(&path).map(Some)
+}
+
+fn resolve_template_path(path: &str, workspace_root: &Path) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    workspace_root.join(path)
+}
+
+fn template_error(msg: impl std::fmt::Display) -> Error {
+    Error::Config(format!("Invalid stack comment template: {msg}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::submit::execute::StackItem;
+    use crate::types::PrState;
+
+    fn data() -> StackCommentData {
+        let item = |n: u64, name: &str, state| StackItem {
+            bookmark_name: name.to_string(),
+            pr_url: format!("https://example.com/pr/{n}"),
+            pr_number: n,
+            pr_title: format!("Add {name}"),
+            pr_state: state,
+        };
+        StackCommentData {
+            version: 1,
+            stack: vec![
+                item(1, "feat-a", PrState::Merged),
+                item(2, "feat-b", PrState::Open),
+                item(3, "feat-c", PrState::Open),
+            ],
+            base_branch: "main".to_string(),
+        }
+    }
+
+    fn render(source: &str, current: usize) -> String {
+        StackCommentTemplate::parse(source)
+            .unwrap()
+            .render(&data(), current)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_renders_loop_with_current_marker() {
+        let source = "{% for pr in stack | reverse -%}\n\
+                      - [#{{ pr.number }}]({{ pr.url }}) {{ pr.title }}\
+                      {% if pr.is_current %} {{ marker }}{% endif %}\n\
+                      {% endfor -%}\n\
+                      base: `{{ base_branch }}`";
+        assert_eq!(
+            render(source, 1),
+            "- [#3](https://example.com/pr/3) Add feat-c\n\
+             - [#2](https://example.com/pr/2) Add feat-b 👈\n\
+             - [#1](https://example.com/pr/1) Add feat-a\n\
+             base: `main`"
+        );
+    }
+
+    #[test]
+    fn test_conditions_and_filters() {
+        let source = "{% for pr in stack %}{% if pr.state == \"merged\" %}~{{ pr.bookmark }}~\
+                      {% elif not pr.is_current and pr.number != 3 %}{{ pr.bookmark | upper }}\
+                      {% else %}{{ pr.bookmark }}{% endif %}{% if not loop.last %}, {% endif %}\
+                      {% endfor %} ({{ stack | length }} PRs, at {{ current.number }})";
+        assert_eq!(render(source, 1), "~feat-a~, feat-b, feat-c (3 PRs, at 2)");
+    }
+
+    #[test]
+    fn test_comments_and_lone_braces_pass_through() {
+        assert_eq!(
+            render("{# hidden #}a { b } {{ current_index }}", 2),
+            "a { b } 2"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for source in [
+            "{% for pr in stack %}",
+            "{% endif %}",
+            "{{ stack | }}",
+            "{{ pr.",
+            "{% while x %}",
+        ] {
+            assert!(
+                StackCommentTemplate::parse(source).is_err(),
+                "{source} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_errors_on_undefined_variable() {
+        let template = StackCommentTemplate::parse("{{ nope }}").unwrap();
+        assert!(template.render(&data(), 0).is_err());
+        let template = StackCommentTemplate::parse("{{ current.nope }}").unwrap();
+        assert!(template.render(&data(), 0).is_err());
+    }
+
+    #[test]
+    fn test_resolve_template_path() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            resolve_template_path(".github/stack.md.j2", root),
+            PathBuf::from("/repo/.github/stack.md.j2")
+        );
+        assert_eq!(
+            resolve_template_path("/etc/stack.j2", root),
+            PathBuf::from("/etc/stack.j2")
+        );
+    }
+}
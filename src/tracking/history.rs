@@ -0,0 +1,333 @@
+//! History of ryu metadata changes, keyed by jj operation.
+//!
+//! Tracking state and the PR cache live outside jj's operation log, so
+//! `jj undo` and `jj op restore` don't touch them. To keep the two aligned,
+//! every ryu command that changes metadata appends an entry to
+//! `.jj/repo/ryu/history/` recording the jj operation the repo was at, the
+//! metadata before and after the change, and (for submit and sync) what the
+//! run did on the remote. The entries let ryu show its state as of any jj
+//! operation and roll back its latest change, remote side effects included.
+
+use super::pr_cache::{PrCache, load_pr_cache, save_pr_cache};
+use super::storage::{read_toml, ryu_dir, write_toml_atomic};
+use super::{TrackingState, load_tracking, save_tracking};
+use crate::error::{Error, Result};
+use crate::types::{BaseChange, PullRequest};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Current version of the history entry format.
+pub const HISTORY_VERSION: u32 = 1;
+
+/// Directory (under the ryu dir) holding history entries.
+const HISTORY_DIR: &str = "history";
+
+/// Tracking state and PR cache at one point in time.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetadataSnapshot {
+    /// Tracked bookmarks.
+    #[serde(default)]
+    pub tracking: TrackingState,
+    /// Bookmark → PR associations.
+    #[serde(default)]
+    pub pr_cache: PrCache,
+}
+
+impl MetadataSnapshot {
+    /// Read the current tracking state and PR cache from disk.
+    pub fn load(workspace_root: &Path) -> Result<Self> {
+        Ok(Self {
+            tracking: load_tracking(workspace_root)?,
+            pr_cache: load_pr_cache(workspace_root)?,
+        })
+    }
+
+    /// Write this snapshot back as the current tracking state and PR cache.
+    ///
+    /// Callers should hold [`lock_state`](super::lock_state).
+    pub fn restore(&self, workspace_root: &Path) -> Result<()> {
+        save_tracking(workspace_root, &self.tracking)?;
+        save_pr_cache(workspace_root, &self.pr_cache)
+    }
+}
+
+/// What a submission did on the remote, as needed to revert it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubmissionRecord {
+    /// Remote the submission pushed to.
+    pub remote: String,
+    /// PRs that were opened.
+    #[serde(default)]
+    pub created_prs: Vec<PullRequest>,
+    /// Existing PRs that were changed (base updated or published).
+    #[serde(default)]
+    pub updated_prs: Vec<PullRequest>,
+    /// Bookmarks that were pushed.
+    #[serde(default)]
+    pub pushed_bookmarks: Vec<String>,
+    /// Pushed bookmarks that didn't exist on the remote before.
+    #[serde(default)]
+    pub new_branches: Vec<String>,
+    /// PR bases that were changed, with their previous value.
+    #[serde(default)]
+    pub base_changes: Vec<BaseChange>,
+    /// PRs that got a new stack comment.
+    #[serde(default)]
+    pub created_comments: Vec<u64>,
+}
+
+impl SubmissionRecord {
+    /// Whether the submission changed anything on the remote.
+    pub fn is_empty(&self) -> bool {
+        self.created_prs.is_empty()
+            && self.updated_prs.is_empty()
+            && self.pushed_bookmarks.is_empty()
+            && self.created_comments.is_empty()
+    }
+}
+
+/// One ryu command's change to the metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// File format version.
+    pub version: u32,
+    /// jj operation (hex id) the repo was at when the change was made.
+    pub operation: String,
+    /// When the change was made.
+    pub recorded_at: DateTime<Utc>,
+    /// The ryu command that made the change (e.g. `track`, `submit`).
+    pub command: String,
+    /// Remote side effects, for commands that submitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submission: Option<SubmissionRecord>,
+    /// Metadata before the command ran.
+    pub before: MetadataSnapshot,
+    /// Metadata after the command ran.
+    pub after: MetadataSnapshot,
+    /// File stem the entry is stored under (set on load/record).
+    #[serde(skip)]
+    pub id: String,
+}
+
+impl HistoryEntry {
+    /// Create an entry for a change made at jj operation `operation`.
+    pub fn new(
+        operation: impl Into<String>,
+        command: impl Into<String>,
+        before: MetadataSnapshot,
+        after: MetadataSnapshot,
+    ) -> Self {
+        Self {
+            version: HISTORY_VERSION,
+            operation: operation.into(),
+            recorded_at: Utc::now(),
+            command: command.into(),
+            submission: None,
+            before,
+            after,
+            id: String::new(),
+        }
+    }
+
+    /// PRs the command opened.
+    pub fn created_prs(&self) -> &[PullRequest] {
+        self.submission
+            .as_ref()
+            .map_or(&[], |submission| submission.created_prs.as_slice())
+    }
+
+    /// Short form of the jj operation id, as `jj op log` shows it.
+    pub fn short_operation(&self) -> &str {
+        &self.operation[..12.min(self.operation.len())]
+    }
+}
+
+/// Get path to the history directory.
+pub fn history_dir(workspace_root: &Path) -> PathBuf {
+    ryu_dir(workspace_root).join(HISTORY_DIR)
+}
+
+/// Append an entry to the history.
+///
+/// Entries are named by timestamp so directory order is recording order.
+pub fn record_history(workspace_root: &Path, entry: &mut HistoryEntry) -> Result<()> {
+    let dir = history_dir(workspace_root);
+    let stem = format!(
+        "{}-{}",
+        entry.recorded_at.format("%Y%m%dT%H%M%S%6f"),
+        entry.short_operation()
+    );
+    let mut id = stem.clone();
+    let mut n = 1;
+    while dir.join(format!("{id}.toml")).exists() {
+        id = format!("{stem}-{n}");
+        n += 1;
+    }
+
+    let content = toml::to_string_pretty(entry)
+        .map_err(|e| Error::Tracking(format!("failed to serialize history entry: {e}")))?;
+    let content_with_header = format!(
+        "# ryu metadata history - one entry per ryu command that changed metadata\n\
+         # Used by 'ryu history' and 'ryu undo'\n\n{content}"
+    );
+    write_toml_atomic::<HistoryEntry>(&dir.join(format!("{id}.toml")), &content_with_header)?;
+    entry.id = id;
+    Ok(())
+}
+
+/// Load all history entries, oldest first.
+pub fn load_history(workspace_root: &Path) -> Result<Vec<HistoryEntry>> {
+    let dir = history_dir(workspace_root);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let read_err =
+        |e: std::io::Error| Error::Tracking(format!("failed to read {}: {e}", dir.display()));
+    let mut stems: Vec<String> = Vec::new();
+    for dir_entry in fs::read_dir(&dir).map_err(read_err)? {
+        let path = dir_entry.map_err(read_err)?.path();
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            if let Some(stem) = path.file_stem() {
+                stems.push(stem.to_string_lossy().into_owned());
+            }
+        }
+    }
+    stems.sort();
+
+    let mut entries = Vec::with_capacity(stems.len());
+    for stem in stems {
+        if let Some(mut entry) = read_toml::<HistoryEntry>(&dir.join(format!("{stem}.toml")))? {
+            entry.id = stem;
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Remove an entry from the history (after it has been undone).
+pub fn remove_history_entry(workspace_root: &Path, entry: &HistoryEntry) -> Result<()> {
+    let path = history_dir(workspace_root).join(format!("{}.toml", entry.id));
+    fs::remove_file(&path)
+        .map_err(|e| Error::Tracking(format!("failed to remove {}: {e}", path.display())))
+}
+
+/// Latest entry recorded at jj operation `operation`, if any.
+pub fn latest_entry_at<'a>(
+    entries: &'a [HistoryEntry],
+    operation: &str,
+) -> Option<&'a HistoryEntry> {
+    entries.iter().rev().find(|e| e.operation == operation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracking::TrackedBookmark;
+    use crate::types::PrState;
+    use tempfile::TempDir;
+
+    fn setup_fake_jj_workspace() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join(".jj").join("repo")).unwrap();
+        temp
+    }
+
+    fn snapshot(names: &[&str]) -> MetadataSnapshot {
+        let mut tracking = TrackingState::new();
+        for name in names {
+            tracking.track(TrackedBookmark::new(
+                (*name).to_string(),
+                format!("change-{name}"),
+            ));
+        }
+        MetadataSnapshot {
+            tracking,
+            pr_cache: PrCache::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_and_load_roundtrip() {
+        let temp = setup_fake_jj_workspace();
+        let mut first = HistoryEntry::new("aaaa", "track", snapshot(&[]), snapshot(&["feat-a"]));
+        record_history(temp.path(), &mut first).unwrap();
+
+        let mut second = HistoryEntry::new(
+            "bbbb",
+            "submit",
+            snapshot(&["feat-a"]),
+            snapshot(&["feat-a"]),
+        );
+        let mut submission = SubmissionRecord {
+            remote: "origin".to_string(),
+            new_branches: vec!["feat-a".to_string()],
+            ..Default::default()
+        };
+        submission.created_prs.push(PullRequest {
+            number: 7,
+            html_url: "https://github.com/o/r/pull/7".to_string(),
+            base_ref: "main".to_string(),
+            head_ref: "feat-a".to_string(),
+            title: "Feat A".to_string(),
+            node_id: None,
+            is_draft: false,
+            state: PrState::Open,
+            comments: None,
+            checks: None,
+        });
+        submission.base_changes.push(BaseChange {
+            pr_number: 3,
+            bookmark: "feat-b".to_string(),
+            previous_base: "main".to_string(),
+            new_base: "feat-a".to_string(),
+        });
+        second.submission = Some(submission);
+        record_history(temp.path(), &mut second).unwrap();
+
+        let entries = load_history(temp.path()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, "track");
+        assert_eq!(entries[0].after.tracking.bookmarks[0].name, "feat-a");
+        assert_eq!(entries[1].command, "submit");
+        assert_eq!(entries[1].created_prs()[0].number, 7);
+        let submission = entries[1].submission.as_ref().unwrap();
+        assert_eq!(submission.remote, "origin");
+        assert_eq!(submission.new_branches, vec!["feat-a".to_string()]);
+        assert_eq!(submission.base_changes[0].previous_base, "main");
+        assert!(entries[0].submission.is_none());
+        assert!(entries[0].created_prs().is_empty());
+        assert_eq!(entries[1].id, second.id);
+    }
+
+    #[test]
+    fn test_remove_entry_and_latest_at() {
+        let temp = setup_fake_jj_workspace();
+        let mut a = HistoryEntry::new("op1", "track", snapshot(&[]), snapshot(&["a"]));
+        let mut b = HistoryEntry::new("op1", "track", snapshot(&["a"]), snapshot(&["a", "b"]));
+        record_history(temp.path(), &mut a).unwrap();
+        record_history(temp.path(), &mut b).unwrap();
+
+        let entries = load_history(temp.path()).unwrap();
+        let latest = latest_entry_at(&entries, "op1").unwrap();
+        assert_eq!(latest.after.tracking.bookmarks.len(), 2);
+        assert!(latest_entry_at(&entries, "op2").is_none());
+
+        remove_history_entry(temp.path(), latest).unwrap();
+        let entries = load_history(temp.path()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].after.tracking.bookmarks.len(), 1);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let temp = setup_fake_jj_workspace();
+        snapshot(&["feat-x"]).restore(temp.path()).unwrap();
+
+        let loaded = MetadataSnapshot::load(temp.path()).unwrap();
+        assert!(loaded.tracking.is_tracked("feat-x"));
+        assert!(loaded.pr_cache.prs.is_empty());
+    }
+}
@@ -3,13 +3,19 @@
 //! This module provides persistence for tracking which bookmarks should be
 //! submitted to the remote platform. It stores metadata in `.jj/repo/ryu/`.
 
+mod history;
 mod pr_cache;
 mod storage;
 
+pub use history::{
+    HISTORY_VERSION, HistoryEntry, MetadataSnapshot, SubmissionRecord, history_dir,
+    latest_entry_at, load_history, record_history, remove_history_entry,
+};
 pub use pr_cache::{
     CachedPr, PR_CACHE_VERSION, PrCache, load_pr_cache, pr_cache_path, save_pr_cache,
 };
-pub use storage::{load_tracking, save_tracking, tracking_path};
+pub use storage::{StateLock, load_tracking, lock_state, save_tracking, tracking_path};
+pub(crate) use storage::{read_toml, remove_with_backup, ryu_dir, write_toml_atomic};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -27,6 +33,9 @@ pub struct TrackedBookmark {
     /// Optional remote to submit to (defaults to auto-detect).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub remote: Option<String>,
+    /// Workspace this bookmark is tracked in (`None` = every workspace).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
     /// When this bookmark was tracked.
     pub tracked_at: DateTime<Utc>,
 }
@@ -38,16 +47,23 @@ impl TrackedBookmark {
             name,
             change_id,
             remote: None,
+            workspace: None,
             tracked_at: Utc::now(),
         }
     }
 
+    /// Whether this bookmark is tracked in the given workspace.
+    pub fn applies_to(&self, workspace: &str) -> bool {
+        self.workspace.as_deref().is_none_or(|ws| ws == workspace)
+    }
+
     /// Create a new tracked bookmark with a specific remote.
     pub fn with_remote(name: String, change_id: String, remote: String) -> Self {
         Self {
             name,
             change_id,
             remote: Some(remote),
+            workspace: None,
             tracked_at: Utc::now(),
         }
     }
@@ -100,6 +116,22 @@ impl TrackingState {
     pub fn tracked_names(&self) -> Vec<&str> {
         self.bookmarks.iter().map(|b| b.name.as_str()).collect()
     }
+
+    /// Check if a bookmark is tracked in a workspace (repo-wide or scoped to it).
+    pub fn is_tracked_in(&self, name: &str, workspace: &str) -> bool {
+        self.bookmarks
+            .iter()
+            .any(|b| b.name == name && b.applies_to(workspace))
+    }
+
+    /// Get the names of bookmarks tracked in a workspace (repo-wide or scoped to it).
+    pub fn tracked_names_in(&self, workspace: &str) -> Vec<&str> {
+        self.bookmarks
+            .iter()
+            .filter(|b| b.applies_to(workspace))
+            .map(|b| b.name.as_str())
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -148,6 +180,26 @@ mod tests {
         assert!(!state.untrack("feat-auth")); // Already removed
     }
 
+    #[test]
+    fn test_workspace_scoped_tracking() {
+        let mut state = TrackingState::new();
+        state.track(TrackedBookmark::new(
+            "shared".to_string(),
+            "abc123".to_string(),
+        ));
+        let mut scoped = TrackedBookmark::new("scoped".to_string(), "def456".to_string());
+        scoped.workspace = Some("second".to_string());
+        state.track(scoped);
+
+        assert_eq!(state.tracked_names_in("default"), vec!["shared"]);
+        assert_eq!(state.tracked_names_in("second"), vec!["shared", "scoped"]);
+        assert!(state.is_tracked_in("shared", "default"));
+        assert!(!state.is_tracked_in("scoped", "default"));
+        assert!(state.is_tracked_in("scoped", "second"));
+        // Repo-wide checks still see every entry
+        assert!(state.is_tracked("scoped"));
+    }
+
     #[test]
     fn test_tracking_state_serialization() {
         let mut state = TrackingState::new();
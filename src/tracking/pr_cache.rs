@@ -3,11 +3,11 @@
 //! The cache is stored in `.jj/repo/ryu/pr_cache.toml` and can be safely
 //! deleted - it will be rebuilt on the next submit.
 
+use super::storage::{read_toml, ryu_dir, write_toml_atomic};
 use crate::error::{Error, Result};
 use crate::types::PullRequest;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::fs;
 use std::path::{Path, PathBuf};
 
 /// Current version of the PR cache file format.
@@ -27,6 +27,9 @@ pub struct CachedPr {
     pub url: String,
     /// Remote this PR was pushed to.
     pub remote: String,
+    /// Base branch the PR targeted when last seen, used to detect retargeting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
     /// When this cache entry was last updated.
     pub updated_at: DateTime<Utc>,
 }
@@ -62,6 +65,7 @@ impl PrCache {
             number: pr.number,
             url: pr.html_url.clone(),
             remote: remote.to_string(),
+            base: Some(pr.base_ref.clone()),
             updated_at: Utc::now(),
         };
 
@@ -88,44 +92,26 @@ impl PrCache {
 
 /// Get path to the PR cache file.
 pub fn pr_cache_path(workspace_root: &Path) -> PathBuf {
-    workspace_root
-        .join(".jj")
-        .join("repo")
-        .join("ryu")
-        .join(PR_CACHE_FILE)
+    ryu_dir(workspace_root).join(PR_CACHE_FILE)
 }
 
 /// Load PR cache from disk.
 ///
-/// Returns an empty `PrCache` if the file doesn't exist.
+/// Returns an empty `PrCache` if the file doesn't exist. Falls back to
+/// `pr_cache.toml.bak` if the file is corrupted.
 pub fn load_pr_cache(workspace_root: &Path) -> Result<PrCache> {
     let path = pr_cache_path(workspace_root);
-
-    if !path.exists() {
-        return Ok(PrCache::new());
-    }
-
-    let content = fs::read_to_string(&path)
-        .map_err(|e| Error::Tracking(format!("failed to read {}: {e}", path.display())))?;
-
-    let cache: PrCache = toml::from_str(&content)
-        .map_err(|e| Error::Tracking(format!("failed to parse {}: {e}", path.display())))?;
-
-    Ok(cache)
+    Ok(read_toml(&path)?.unwrap_or_else(PrCache::new))
 }
 
 /// Save PR cache to disk.
 ///
-/// Creates the `.jj/repo/ryu/` directory if it doesn't exist.
+/// Creates the `.jj/repo/ryu/` directory if it doesn't exist. The write is
+/// atomic; callers doing read-modify-write should hold [`lock_state`].
+///
+/// [`lock_state`]: super::lock_state
 pub fn save_pr_cache(workspace_root: &Path, cache: &PrCache) -> Result<()> {
     let path = pr_cache_path(workspace_root);
-    let dir = path.parent().expect("path has parent");
-
-    // Ensure directory exists
-    if !dir.exists() {
-        fs::create_dir_all(dir)
-            .map_err(|e| Error::Tracking(format!("failed to create {}: {e}", dir.display())))?;
-    }
 
     // Serialize with version
     let mut cache_to_save = cache.clone();
@@ -140,15 +126,13 @@ pub fn save_pr_cache(workspace_root: &Path, cache: &PrCache) -> Result<()> {
          # Safe to delete; will be rebuilt on next submit\n\n{content}"
     );
 
-    fs::write(&path, content_with_header)
-        .map_err(|e| Error::Tracking(format!("failed to write {}: {e}", path.display())))?;
-
-    Ok(())
+    write_toml_atomic::<PrCache>(&path, &content_with_header)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::PrState;
     use tempfile::TempDir;
 
     fn setup_fake_jj_workspace() -> TempDir {
@@ -166,6 +150,9 @@ mod tests {
             title: "Test PR".to_string(),
             node_id: None,
             is_draft: false,
+            state: PrState::Open,
+            comments: None,
+            checks: None,
         }
     }
 
@@ -195,6 +182,7 @@ mod tests {
         assert_eq!(cached.number, 123);
         assert_eq!(cached.remote, "origin");
         assert!(cached.url.contains("123"));
+        assert_eq!(cached.base.as_deref(), Some("main"));
 
         // Update existing
         let pr2 = make_test_pr(456);
@@ -260,7 +248,7 @@ mod tests {
         let cache = PrCache::new();
         save_pr_cache(temp.path(), &cache).unwrap();
 
-        let content = fs::read_to_string(pr_cache_path(temp.path())).unwrap();
+        let content = std::fs::read_to_string(pr_cache_path(temp.path())).unwrap();
         assert!(content.contains("PR association cache"));
         assert!(content.contains("Safe to delete"));
     }
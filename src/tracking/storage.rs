@@ -1,9 +1,20 @@
 //! Persistence for tracking state in `.jj/repo/ryu/`.
+//!
+//! The directory lives in the repo shared by every jj workspace, so all
+//! workspaces see the same tracking state and PR cache.
+//!
+//! Files are written to a temporary sibling and renamed into place, so a crash
+//! mid-write never leaves a truncated file. The previous good copy is kept as
+//! `<file>.bak` and used if the primary file fails to parse.
 
 use super::{TRACKING_VERSION, TrackingState};
 use crate::error::{Error, Result};
+use jj_lib::lock::FileLock;
+use serde::de::DeserializeOwned;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use tracing::warn;
 
 /// Directory name for ryu metadata within `.jj/repo/`.
 const RYU_DIR: &str = "ryu";
@@ -11,48 +22,160 @@ const RYU_DIR: &str = "ryu";
 /// Filename for tracking state.
 const TRACKING_FILE: &str = "tracked.toml";
 
+/// Filename for the lock guarding read-modify-write of ryu state.
+const LOCK_FILE: &str = "lock";
+
 /// Get path to the ryu metadata directory.
-fn ryu_dir(workspace_root: &Path) -> PathBuf {
-    workspace_root.join(".jj").join("repo").join(RYU_DIR)
+pub fn ryu_dir(workspace_root: &Path) -> PathBuf {
+    repo_dir(workspace_root).join(RYU_DIR)
 }
 
-/// Get path to the tracking state file.
-pub fn tracking_path(workspace_root: &Path) -> PathBuf {
-    ryu_dir(workspace_root).join(TRACKING_FILE)
+/// Get path to the repo directory shared by all workspaces.
+///
+/// In secondary workspaces (`jj workspace add`), `.jj/repo` is a file holding
+/// the path of the main workspace's repo directory, relative to `.jj/`.
+fn repo_dir(workspace_root: &Path) -> PathBuf {
+    let jj_dir = workspace_root.join(".jj");
+    let repo = jj_dir.join("repo");
+    if repo.is_file() {
+        if let Ok(target) = fs::read_to_string(&repo) {
+            return jj_dir.join(target.trim());
+        }
+    }
+    repo
 }
 
-/// Load tracking state from disk.
+/// Exclusive lock on ryu state, released when dropped.
 ///
-/// Returns an empty `TrackingState` if the file doesn't exist.
-pub fn load_tracking(workspace_root: &Path) -> Result<TrackingState> {
-    let path = tracking_path(workspace_root);
+/// Hold it across load-modify-save of `tracked.toml` or `pr_cache.toml` so
+/// concurrent ryu runs (e.g. in two workspaces) don't lose each other's updates.
+pub struct StateLock {
+    _lock: FileLock,
+}
+
+/// Lock ryu state, waiting for any other ryu process to release it.
+pub fn lock_state(workspace_root: &Path) -> Result<StateLock> {
+    let dir = ryu_dir(workspace_root);
+    if !dir.exists() {
+        fs::create_dir_all(&dir)
+            .map_err(|e| Error::Tracking(format!("failed to create {}: {e}", dir.display())))?;
+    }
+
+    let lock = FileLock::lock(dir.join(LOCK_FILE))
+        .map_err(|e| Error::Tracking(format!("failed to lock ryu state: {e}")))?;
+    Ok(StateLock { _lock: lock })
+}
 
+/// Path of the backup copy kept next to a metadata file.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Read and parse a TOML metadata file.
+///
+/// Returns `None` if the file doesn't exist. If it exists but can't be read or
+/// parsed, the `.bak` copy is used instead and restored over the damaged file.
+/// Fails only when neither copy is usable.
+pub fn read_toml<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
     if !path.exists() {
-        return Ok(TrackingState::new());
+        return Ok(None);
     }
 
-    let content = fs::read_to_string(&path)
-        .map_err(|e| Error::Tracking(format!("failed to read {}: {e}", path.display())))?;
+    let err = match parse_file::<T>(path) {
+        Ok(value) => return Ok(Some(value)),
+        Err(e) => e,
+    };
 
-    let state: TrackingState = toml::from_str(&content)
-        .map_err(|e| Error::Tracking(format!("failed to parse {}: {e}", path.display())))?;
+    let backup = backup_path(path);
+    let Ok(value) = parse_file::<T>(&backup) else {
+        return Err(err);
+    };
+    warn!(
+        "{err}; recovered from {}",
+        backup.file_name().unwrap_or_default().to_string_lossy()
+    );
+    // Best effort - the next save rewrites the file anyway
+    let _ = fs::copy(&backup, path);
+    Ok(Some(value))
+}
 
-    Ok(state)
+fn parse_file<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| Error::Tracking(format!("failed to read {}: {e}", path.display())))?;
+    toml::from_str(&content)
+        .map_err(|e| Error::Tracking(format!("failed to parse {}: {e}", path.display())))
 }
 
-/// Save tracking state to disk.
+/// Atomically replace a TOML metadata file with `content`.
 ///
-/// Creates the `.jj/repo/ryu/` directory if it doesn't exist.
-pub fn save_tracking(workspace_root: &Path, state: &TrackingState) -> Result<()> {
-    let dir = ryu_dir(workspace_root);
-    let path = dir.join(TRACKING_FILE);
-
-    // Ensure directory exists
+/// Creates the parent directory if needed. If the current file parses as `T`
+/// it is first copied to `<file>.bak`, then `content` is written to a temporary
+/// file, synced, and renamed over the original.
+pub fn write_toml_atomic<T: DeserializeOwned>(path: &Path, content: &str) -> Result<()> {
+    let dir = path.parent().expect("path has parent");
     if !dir.exists() {
-        fs::create_dir_all(&dir)
+        fs::create_dir_all(dir)
             .map_err(|e| Error::Tracking(format!("failed to create {}: {e}", dir.display())))?;
     }
 
+    if parse_file::<T>(path).is_ok() {
+        let backup = backup_path(path);
+        fs::copy(path, &backup)
+            .map_err(|e| Error::Tracking(format!("failed to write {}: {e}", backup.display())))?;
+    }
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".tmp{}", std::process::id()));
+    let tmp = path.with_file_name(tmp_name);
+    let write_err = |e: std::io::Error| {
+        let _ = fs::remove_file(&tmp);
+        Error::Tracking(format!("failed to write {}: {e}", path.display()))
+    };
+
+    let mut file = fs::File::create(&tmp).map_err(write_err)?;
+    file.write_all(content.as_bytes()).map_err(write_err)?;
+    file.sync_all().map_err(write_err)?;
+    drop(file);
+    fs::rename(&tmp, path).map_err(write_err)?;
+
+    Ok(())
+}
+
+/// Remove a metadata file together with its `.bak` copy.
+pub fn remove_with_backup(path: &Path) -> Result<()> {
+    for file in [path.to_path_buf(), backup_path(path)] {
+        if file.exists() {
+            fs::remove_file(&file).map_err(|e| {
+                Error::Tracking(format!("failed to remove {}: {e}", file.display()))
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Get path to the tracking state file.
+pub fn tracking_path(workspace_root: &Path) -> PathBuf {
+    ryu_dir(workspace_root).join(TRACKING_FILE)
+}
+
+/// Load tracking state from disk.
+///
+/// Returns an empty `TrackingState` if the file doesn't exist. Falls back to
+/// `tracked.toml.bak` if the file is corrupted.
+pub fn load_tracking(workspace_root: &Path) -> Result<TrackingState> {
+    let path = tracking_path(workspace_root);
+    Ok(read_toml(&path)?.unwrap_or_else(TrackingState::new))
+}
+
+/// Save tracking state to disk.
+///
+/// Creates the `.jj/repo/ryu/` directory if it doesn't exist. The write is
+/// atomic; callers doing read-modify-write should hold [`lock_state`].
+pub fn save_tracking(workspace_root: &Path, state: &TrackingState) -> Result<()> {
+    let path = tracking_path(workspace_root);
+
     // Serialize with version
     let mut state_to_save = state.clone();
     state_to_save.version = TRACKING_VERSION;
@@ -65,10 +188,7 @@ pub fn save_tracking(workspace_root: &Path, state: &TrackingState) -> Result<()>
         "# ryu tracking metadata\n# Auto-generated - manual edits may be overwritten\n\n{content}"
     );
 
-    fs::write(&path, content_with_header)
-        .map_err(|e| Error::Tracking(format!("failed to write {}: {e}", path.display())))?;
-
-    Ok(())
+    write_toml_atomic::<TrackingState>(&path, &content_with_header)
 }
 
 #[cfg(test)]
@@ -91,6 +211,31 @@ mod tests {
         assert!(path.ends_with(".jj/repo/ryu/tracked.toml"));
     }
 
+    #[test]
+    fn test_ryu_dir_follows_secondary_workspace_pointer() {
+        let main = setup_fake_jj_workspace();
+        let secondary = TempDir::new().unwrap();
+        fs::create_dir_all(secondary.path().join(".jj")).unwrap();
+        let main_repo = main.path().join(".jj").join("repo");
+        fs::write(
+            secondary.path().join(".jj").join("repo"),
+            main_repo.to_str().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(ryu_dir(secondary.path()), main_repo.join("ryu"));
+        assert_eq!(ryu_dir(main.path()), main_repo.join("ryu"));
+    }
+
+    #[test]
+    fn test_lock_state_is_reentrant_after_drop() {
+        let temp = setup_fake_jj_workspace();
+        let lock = lock_state(temp.path()).unwrap();
+        drop(lock);
+        let _lock = lock_state(temp.path()).unwrap();
+        assert!(ryu_dir(temp.path()).join(LOCK_FILE).exists());
+    }
+
     #[test]
     fn test_load_missing_file_returns_empty() {
         let temp = setup_fake_jj_workspace();
@@ -138,6 +283,85 @@ mod tests {
         assert_eq!(loaded.bookmarks[1].remote, Some("upstream".to_string()));
     }
 
+    #[test]
+    fn test_save_is_atomic_and_keeps_backup() {
+        let temp = setup_fake_jj_workspace();
+        let mut state = TrackingState::new();
+        state.track(TrackedBookmark::new(
+            "feat-a".to_string(),
+            "abc".to_string(),
+        ));
+        save_tracking(temp.path(), &state).unwrap();
+        state.track(TrackedBookmark::new(
+            "feat-b".to_string(),
+            "def".to_string(),
+        ));
+        save_tracking(temp.path(), &state).unwrap();
+
+        let path = tracking_path(temp.path());
+        let backup: TrackingState = parse_file(&backup_path(&path)).unwrap();
+        assert_eq!(backup.bookmarks.len(), 1);
+
+        // No temporary files left behind
+        let leftovers: Vec<_> = fs::read_dir(ryu_dir(temp.path()))
+            .unwrap()
+            .filter_map(std::result::Result::ok)
+            .filter(|e| e.file_name().to_string_lossy().contains(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_load_recovers_from_backup_when_corrupted() {
+        let temp = setup_fake_jj_workspace();
+        let mut state = TrackingState::new();
+        state.track(TrackedBookmark::new(
+            "feat-a".to_string(),
+            "abc".to_string(),
+        ));
+        save_tracking(temp.path(), &state).unwrap();
+        save_tracking(temp.path(), &state).unwrap();
+
+        // Simulate a truncated write
+        let path = tracking_path(temp.path());
+        fs::write(&path, "version = 1\n[[bookmarks]]\nname = \"fe").unwrap();
+
+        let loaded = load_tracking(temp.path()).unwrap();
+        assert_eq!(loaded.bookmarks.len(), 1);
+        assert_eq!(loaded.bookmarks[0].name, "feat-a");
+        // The damaged file was restored from the backup
+        assert!(parse_file::<TrackingState>(&path).is_ok());
+    }
+
+    #[test]
+    fn test_corrupted_file_does_not_overwrite_backup() {
+        let temp = setup_fake_jj_workspace();
+        let mut state = TrackingState::new();
+        state.track(TrackedBookmark::new(
+            "feat-a".to_string(),
+            "abc".to_string(),
+        ));
+        save_tracking(temp.path(), &state).unwrap();
+        save_tracking(temp.path(), &state).unwrap();
+
+        let path = tracking_path(temp.path());
+        fs::write(&path, "garbage [[").unwrap();
+        save_tracking(temp.path(), &TrackingState::new()).unwrap();
+
+        let backup: TrackingState = parse_file(&backup_path(&path)).unwrap();
+        assert_eq!(backup.bookmarks.len(), 1);
+    }
+
+    #[test]
+    fn test_load_fails_when_backup_also_unusable() {
+        let temp = setup_fake_jj_workspace();
+        let path = tracking_path(temp.path());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "garbage [[").unwrap();
+
+        assert!(load_tracking(temp.path()).is_err());
+    }
+
     #[test]
     fn test_file_contains_header_comment() {
         let temp = setup_fake_jj_workspace();
@@ -6,6 +6,7 @@ use std::collections::HashMap;
 
 /// A jj bookmark (branch reference)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Bookmark {
     /// Bookmark name
     pub name: String,
@@ -17,6 +18,15 @@ pub struct Bookmark {
     pub has_remote: bool,
     /// Whether local and remote are in sync
     pub is_synced: bool,
+    /// Whether the bookmark has several targets (e.g. after concurrent edits)
+    #[serde(default)]
+    pub is_conflicted: bool,
+    /// Whether the target commit has unresolved conflicts
+    #[serde(default)]
+    pub has_conflicts: bool,
+    /// Whether the target's change has more than one visible commit
+    #[serde(default)]
+    pub is_divergent: bool,
 }
 
 /// A commit/change entry from jj log
@@ -44,6 +54,94 @@ pub struct LogEntry {
     pub authored_at: DateTime<Utc>,
     /// When the commit was committed
     pub committed_at: DateTime<Utc>,
+    /// Whether the commit has unresolved conflicts
+    #[serde(default)]
+    pub has_conflicts: bool,
+    /// Whether the change has more than one visible commit
+    #[serde(default)]
+    pub is_divergent: bool,
+}
+
+/// A remote bookmark that moved to commits the local repo didn't have
+#[derive(Debug, Clone)]
+pub struct RemoteDivergence {
+    /// Bookmark name
+    pub bookmark: String,
+    /// Remote the bookmark was fetched from
+    pub remote: String,
+    /// Commits on the remote bookmark that weren't known locally (newest first)
+    pub commits: Vec<LogEntry>,
+}
+
+/// Why a commit can't be pushed (mirrors the checks of `jj git push`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnpushableReason {
+    /// The commit has unresolved conflicts
+    Conflicted,
+    /// The commit has no description
+    NoDescription,
+    /// The commit matches jj's `git.private-commits` revset
+    Private,
+}
+
+impl std::fmt::Display for UnpushableReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Conflicted => write!(f, "has conflicts"),
+            Self::NoDescription => write!(f, "has no description"),
+            Self::Private => write!(f, "is private (matches git.private-commits)"),
+        }
+    }
+}
+
+/// A commit a bookmark would push that jj would refuse
+#[derive(Debug, Clone)]
+pub struct UnpushableCommit {
+    /// Bookmark whose push includes the commit
+    pub bookmark: String,
+    /// The offending commit
+    pub commit: LogEntry,
+    /// What's wrong with it
+    pub reason: UnpushableReason,
+}
+
+impl std::fmt::Display for UnpushableCommit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: commit {} {}",
+            self.bookmark,
+            self.commit
+                .commit_id
+                .get(..8)
+                .unwrap_or(&self.commit.commit_id),
+            self.reason
+        )
+    }
+}
+
+/// Outcome of pushing several bookmarks at once
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PushReport {
+    /// Bookmarks the remote accepted
+    pub pushed: Vec<String>,
+    /// Bookmarks the remote rejected, with the reason
+    pub rejected: Vec<(String, String)>,
+    /// Whether the push was applied atomically (all or nothing)
+    pub atomic: bool,
+}
+
+/// A PR base change made by a submission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseChange {
+    /// PR number
+    pub pr_number: u64,
+    /// Bookmark (head branch) of the PR
+    pub bookmark: String,
+    /// Base before the change
+    pub previous_base: String,
+    /// Base after the change
+    pub new_base: String,
 }
 
 /// A segment of changes belonging to one or more bookmarks
@@ -56,7 +154,7 @@ pub struct BookmarkSegment {
 }
 
 /// A segment narrowed to a single bookmark (after user selection)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NarrowedBookmarkSegment {
     /// The selected bookmark for this segment
     pub bookmark: Bookmark,
@@ -102,6 +200,51 @@ pub struct PullRequest {
     pub node_id: Option<String>,
     /// Whether PR is a draft
     pub is_draft: bool,
+    /// Open/closed/merged state
+    #[serde(default)]
+    pub state: PrState,
+    /// Comments, if fetched together with the PR (GitHub's batched lookup);
+    /// `None` means they have to be listed separately
+    #[serde(skip)]
+    pub comments: Option<Vec<PrComment>>,
+    /// Combined status of the head commit's checks, if fetched with the PR
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checks: Option<ChecksState>,
+}
+
+/// Lifecycle state of a pull request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrState {
+    /// Open (including drafts)
+    #[default]
+    Open,
+    /// Closed without merging
+    Closed,
+    /// Merged into its base
+    Merged,
+}
+
+impl std::fmt::Display for PrState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "open"),
+            Self::Closed => write!(f, "closed"),
+            Self::Merged => write!(f, "merged"),
+        }
+    }
+}
+
+/// Combined status of the checks on a PR's head commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksState {
+    /// Some checks haven't finished (or haven't started)
+    Pending,
+    /// Every check passed
+    Success,
+    /// At least one check failed or errored
+    Failure,
 }
 
 /// A comment on a pull request
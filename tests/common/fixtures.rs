@@ -8,7 +8,7 @@
 use chrono::Utc;
 use jj_ryu::types::{
     Bookmark, BookmarkSegment, BranchStack, ChangeGraph, LogEntry, Platform, PlatformConfig,
-    PrComment, PullRequest,
+    PrComment, PrState, PullRequest,
 };
 use std::collections::HashMap;
 
@@ -20,6 +20,9 @@ pub fn make_bookmark(name: &str) -> Bookmark {
         change_id: format!("{name}_change_xyz789"),
         has_remote: false,
         is_synced: false,
+        is_conflicted: false,
+        has_conflicts: false,
+        is_divergent: false,
     }
 }
 
@@ -40,6 +43,9 @@ pub fn make_bookmark_with_ids(name: &str, commit_id: &str, change_id: &str) -> B
         change_id: change_id.to_string(),
         has_remote: false,
         is_synced: false,
+        is_conflicted: false,
+        has_conflicts: false,
+        is_divergent: false,
     }
 }
 
@@ -62,6 +68,8 @@ pub fn make_log_entry_with_ids(
         is_working_copy: false,
         authored_at: Utc::now(),
         committed_at: Utc::now(),
+        has_conflicts: false,
+        is_divergent: false,
     }
 }
 
@@ -75,6 +83,9 @@ pub fn make_pr(number: u64, head: &str, base: &str) -> PullRequest {
         title: format!("PR for {head}"),
         node_id: Some(format!("PR_node_{number}")),
         is_draft: false,
+        state: PrState::Open,
+        comments: None,
+        checks: None,
     }
 }
 
@@ -88,6 +99,9 @@ pub fn make_pr_draft(number: u64, head: &str, base: &str) -> PullRequest {
         title: format!("PR for {head}"),
         node_id: Some(format!("PR_node_{number}")),
         is_draft: true,
+        state: PrState::Open,
+        comments: None,
+        checks: None,
     }
 }
 
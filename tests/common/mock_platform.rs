@@ -8,7 +8,7 @@
 use async_trait::async_trait;
 use jj_ryu::error::{Error, Result};
 use jj_ryu::platform::PlatformService;
-use jj_ryu::types::{PlatformConfig, PrComment, PullRequest};
+use jj_ryu::types::{PlatformConfig, PrComment, PrState, PullRequest};
 use std::collections::HashMap;
 use std::sync::Mutex;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -19,6 +19,7 @@ pub struct CreatePrCall {
     pub head: String,
     pub base: String,
     pub title: String,
+    pub body: String,
 }
 
 /// Call record for `update_pr_base`
@@ -49,12 +50,18 @@ pub struct MockPlatformService {
     config: PlatformConfig,
     next_pr_number: AtomicU64,
     find_pr_responses: Mutex<HashMap<String, Option<PullRequest>>>,
+    get_pr_responses: Mutex<HashMap<u64, PullRequest>>,
     list_comments_responses: Mutex<HashMap<u64, Vec<PrComment>>>,
+    pr_bodies: Mutex<HashMap<u64, String>>,
     // Call tracking
     find_pr_calls: Mutex<Vec<String>>,
+    get_pr_calls: Mutex<Vec<u64>>,
     create_pr_calls: Mutex<Vec<CreatePrCall>>,
     update_base_calls: Mutex<Vec<UpdateBaseCall>>,
     create_comment_calls: Mutex<Vec<CreateCommentCall>>,
+    close_pr_calls: Mutex<Vec<u64>>,
+    delete_comment_calls: Mutex<Vec<(u64, u64)>>,
+    update_body_calls: Mutex<Vec<(u64, String)>>,
     list_comments_calls: Mutex<Vec<u64>>,
     // Error injection
     error_on_find_pr: Mutex<Option<String>>,
@@ -69,11 +76,17 @@ impl MockPlatformService {
             config,
             next_pr_number: AtomicU64::new(1),
             find_pr_responses: Mutex::new(HashMap::new()),
+            get_pr_responses: Mutex::new(HashMap::new()),
             list_comments_responses: Mutex::new(HashMap::new()),
+            pr_bodies: Mutex::new(HashMap::new()),
             find_pr_calls: Mutex::new(Vec::new()),
+            get_pr_calls: Mutex::new(Vec::new()),
             create_pr_calls: Mutex::new(Vec::new()),
             update_base_calls: Mutex::new(Vec::new()),
             create_comment_calls: Mutex::new(Vec::new()),
+            close_pr_calls: Mutex::new(Vec::new()),
+            delete_comment_calls: Mutex::new(Vec::new()),
+            update_body_calls: Mutex::new(Vec::new()),
             list_comments_calls: Mutex::new(Vec::new()),
             error_on_find_pr: Mutex::new(None),
             error_on_create_pr: Mutex::new(None),
@@ -106,6 +119,11 @@ impl MockPlatformService {
             .insert(branch.to_string(), pr);
     }
 
+    /// Set the response for `get_pr` (PRs not set are reported as missing)
+    pub fn set_get_pr_response(&self, pr: PullRequest) {
+        self.get_pr_responses.lock().unwrap().insert(pr.number, pr);
+    }
+
     /// Set the response for `list_pr_comments` for a specific PR
     pub fn set_list_comments_response(&self, pr_number: u64, comments: Vec<PrComment>) {
         self.list_comments_responses
@@ -114,6 +132,14 @@ impl MockPlatformService {
             .insert(pr_number, comments);
     }
 
+    /// Set the description returned by `get_pr_body` for a specific PR
+    pub fn set_pr_body(&self, pr_number: u64, body: &str) {
+        self.pr_bodies
+            .lock()
+            .unwrap()
+            .insert(pr_number, body.to_string());
+    }
+
     // === Call verification methods ===
 
     /// Get all branches that `find_existing_pr` was called with
@@ -121,6 +147,11 @@ impl MockPlatformService {
         self.find_pr_calls.lock().unwrap().clone()
     }
 
+    /// Get all PR numbers that `get_pr` was called with
+    pub fn get_get_pr_calls(&self) -> Vec<u64> {
+        self.get_pr_calls.lock().unwrap().clone()
+    }
+
     /// Get all `create_pr` calls
     pub fn get_create_pr_calls(&self) -> Vec<CreatePrCall> {
         self.create_pr_calls.lock().unwrap().clone()
@@ -136,6 +167,21 @@ impl MockPlatformService {
         self.create_comment_calls.lock().unwrap().clone()
     }
 
+    /// Get all PR numbers that `close_pr` was called with
+    pub fn get_close_pr_calls(&self) -> Vec<u64> {
+        self.close_pr_calls.lock().unwrap().clone()
+    }
+
+    /// Get all `(pr_number, comment_id)` pairs `delete_pr_comment` was called with
+    pub fn get_delete_comment_calls(&self) -> Vec<(u64, u64)> {
+        self.delete_comment_calls.lock().unwrap().clone()
+    }
+
+    /// Get all `(pr_number, body)` pairs `update_pr_body` was called with
+    pub fn get_update_body_calls(&self) -> Vec<(u64, String)> {
+        self.update_body_calls.lock().unwrap().clone()
+    }
+
     /// Get all `list_pr_comments` calls
     pub fn get_list_comments_calls(&self) -> Vec<u64> {
         self.list_comments_calls.lock().unwrap().clone()
@@ -190,17 +236,35 @@ impl PlatformService for MockPlatformService {
         Ok(responses.get(head_branch).cloned().flatten())
     }
 
+    async fn get_pr(&self, pr_number: u64) -> Result<Option<PullRequest>> {
+        self.get_pr_calls.lock().unwrap().push(pr_number);
+        let responses = self.get_pr_responses.lock().unwrap();
+        Ok(responses.get(&pr_number).cloned())
+    }
+
     async fn create_pr_with_options(
         &self,
         head: &str,
         base: &str,
         title: &str,
         draft: bool,
+    ) -> Result<PullRequest> {
+        self.create_pr_with_body(head, base, title, "", draft).await
+    }
+
+    async fn create_pr_with_body(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
     ) -> Result<PullRequest> {
         self.create_pr_calls.lock().unwrap().push(CreatePrCall {
             head: head.to_string(),
             base: base.to_string(),
             title: title.to_string(),
+            body: body.to_string(),
         });
 
         // Check for injected error
@@ -217,6 +281,9 @@ impl PlatformService for MockPlatformService {
             title: title.to_string(),
             node_id: Some(format!("PR_node_{number}")),
             is_draft: draft,
+            state: PrState::Open,
+            comments: None,
+            checks: None,
         };
         Ok(pr)
     }
@@ -240,6 +307,9 @@ impl PlatformService for MockPlatformService {
             title: "Updated PR".to_string(),
             node_id: Some(format!("PR_node_{pr_number}")),
             is_draft: false,
+            state: PrState::Open,
+            comments: None,
+            checks: None,
         })
     }
 
@@ -278,9 +348,39 @@ impl PlatformService for MockPlatformService {
             title: "Published PR".to_string(),
             node_id: Some(format!("PR_node_{pr_number}")),
             is_draft: false, // After publishing, is_draft is false
+            state: PrState::Open,
+            comments: None,
+            checks: None,
         })
     }
 
+    async fn delete_pr_comment(&self, pr_number: u64, comment_id: u64) -> Result<()> {
+        self.delete_comment_calls
+            .lock()
+            .unwrap()
+            .push((pr_number, comment_id));
+        Ok(())
+    }
+
+    async fn close_pr(&self, pr_number: u64) -> Result<()> {
+        self.close_pr_calls.lock().unwrap().push(pr_number);
+        Ok(())
+    }
+
+    async fn get_pr_body(&self, pr_number: u64) -> Result<String> {
+        let bodies = self.pr_bodies.lock().unwrap();
+        Ok(bodies.get(&pr_number).cloned().unwrap_or_default())
+    }
+
+    async fn update_pr_body(&self, pr_number: u64, body: &str) -> Result<()> {
+        self.update_body_calls
+            .lock()
+            .unwrap()
+            .push((pr_number, body.to_string()));
+        self.set_pr_body(pr_number, body);
+        Ok(())
+    }
+
     fn config(&self) -> &PlatformConfig {
         &self.config
     }
@@ -166,7 +166,7 @@ mod analysis_test {
 
 mod detection_test {
     use jj_ryu::error::Error;
-    use jj_ryu::platform::{detect_platform, parse_repo_info};
+    use jj_ryu::platform::{detect_platform, parse_pr_reference, parse_repo_info};
     use jj_ryu::types::Platform;
 
     #[test]
@@ -258,11 +258,56 @@ mod detection_test {
         assert_eq!(config.platform, Platform::GitHub);
         assert_eq!(config.repo, "repo"); // .git should be stripped
     }
+
+    #[test]
+    fn test_parse_pr_reference_numbers() {
+        assert_eq!(parse_pr_reference("42").unwrap(), 42);
+        assert_eq!(parse_pr_reference("#42").unwrap(), 42);
+        assert_eq!(parse_pr_reference("!42").unwrap(), 42);
+        assert_eq!(parse_pr_reference(" 42 ").unwrap(), 42);
+    }
+
+    #[test]
+    fn test_parse_pr_reference_urls() {
+        assert_eq!(
+            parse_pr_reference("https://github.com/owner/repo/pull/17").unwrap(),
+            17
+        );
+        assert_eq!(
+            parse_pr_reference("https://github.com/owner/repo/pull/17/files#diff-1").unwrap(),
+            17
+        );
+        assert_eq!(
+            parse_pr_reference("https://gitlab.com/group/sub/repo/-/merge_requests/8?tab=notes")
+                .unwrap(),
+            8
+        );
+        assert_eq!(
+            parse_pr_reference("https://dev.azure.com/org/project/_git/repo/pullrequest/99")
+                .unwrap(),
+            99
+        );
+    }
+
+    #[test]
+    fn test_parse_pr_reference_rejects_garbage() {
+        for input in ["", "0", "feat-auth", "https://github.com/owner/repo", "#-1"] {
+            assert!(
+                matches!(parse_pr_reference(input), Err(Error::Parse(_))),
+                "{input:?} should not parse"
+            );
+        }
+    }
 }
 
 mod plan_test {
     use crate::common::{MockPlatformService, github_config, make_linear_stack, make_pr};
-    use jj_ryu::submit::{ExecutionStep, analyze_submission, create_submission_plan};
+    use jj_ryu::submit::{
+        ExecutionStep, PrDrift, PrDriftKind, analyze_submission, create_submission_plan,
+        create_submission_plan_with_cache,
+    };
+    use jj_ryu::tracking::PrCache;
+    use jj_ryu::types::{PrState, PullRequest};
 
     #[tokio::test]
     async fn test_plan_new_stack_no_existing_prs() {
@@ -539,6 +584,306 @@ mod plan_test {
             "Should fail fast, not retry all bookmarks"
         );
     }
+
+    fn cache_with(entries: &[(&str, PullRequest)]) -> PrCache {
+        let mut cache = PrCache::new();
+        for (bookmark, pr) in entries {
+            cache.upsert(bookmark, pr, "origin");
+        }
+        cache
+    }
+
+    #[tokio::test]
+    async fn test_plan_fetches_cached_pr_by_number() {
+        let graph = make_linear_stack(&["feat-a", "feat-b"]);
+        let analysis = analyze_submission(&graph, Some("feat-b")).unwrap();
+
+        let mock = MockPlatformService::with_config(github_config());
+        mock.set_get_pr_response(make_pr(7, "feat-a", "main"));
+        let cache = cache_with(&[("feat-a", make_pr(7, "feat-a", "main"))]);
+
+        let plan = create_submission_plan_with_cache(&analysis, &mock, "origin", "main", &cache)
+            .await
+            .unwrap();
+
+        assert_eq!(mock.get_get_pr_calls(), vec![7]);
+        // Only the uncached bookmark goes through the head-branch search
+        assert_eq!(mock.get_find_pr_calls(), vec!["feat-b".to_string()]);
+        assert_eq!(plan.existing_prs["feat-a"].number, 7);
+        assert_eq!(plan.count_creates(), 1);
+        assert!(plan.drift.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_plan_reports_closed_cached_pr_and_falls_back() {
+        let graph = make_linear_stack(&["feat-a"]);
+        let analysis = analyze_submission(&graph, Some("feat-a")).unwrap();
+
+        let mock = MockPlatformService::with_config(github_config());
+        let mut closed = make_pr(7, "feat-a", "main");
+        closed.state = PrState::Closed;
+        mock.set_get_pr_response(closed);
+        mock.set_find_pr_response("feat-a", Some(make_pr(9, "feat-a", "main")));
+        let cache = cache_with(&[("feat-a", make_pr(7, "feat-a", "main"))]);
+
+        let plan = create_submission_plan_with_cache(&analysis, &mock, "origin", "main", &cache)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            plan.drift,
+            vec![PrDrift {
+                bookmark: "feat-a".to_string(),
+                pr_number: 7,
+                kind: PrDriftKind::Closed,
+            }]
+        );
+        mock.assert_find_pr_called_for(&["feat-a"]);
+        assert_eq!(plan.existing_prs["feat-a"].number, 9);
+    }
+
+    #[tokio::test]
+    async fn test_plan_reports_retargeted_and_missing_cached_prs() {
+        let graph = make_linear_stack(&["feat-a", "feat-b"]);
+        let analysis = analyze_submission(&graph, Some("feat-b")).unwrap();
+
+        let mock = MockPlatformService::with_config(github_config());
+        // Someone else moved feat-b's PR onto main; feat-a's PR was deleted
+        mock.set_get_pr_response(make_pr(8, "feat-b", "main"));
+        let cache = cache_with(&[
+            ("feat-a", make_pr(7, "feat-a", "main")),
+            ("feat-b", make_pr(8, "feat-b", "feat-a")),
+        ]);
+
+        let plan = create_submission_plan_with_cache(&analysis, &mock, "origin", "main", &cache)
+            .await
+            .unwrap();
+
+        let kinds: Vec<_> = plan.drift.iter().map(|d| d.kind.clone()).collect();
+        assert!(kinds.contains(&PrDriftKind::Missing));
+        assert!(kinds.contains(&PrDriftKind::Retargeted {
+            from: "feat-a".to_string(),
+            to: "main".to_string(),
+        }));
+        // The retargeted PR is still used, and its base gets restored
+        assert_eq!(plan.existing_prs["feat-b"].number, 8);
+        assert_eq!(plan.count_updates(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_plan_ignores_cache_for_other_remote() {
+        let graph = make_linear_stack(&["feat-a"]);
+        let analysis = analyze_submission(&graph, Some("feat-a")).unwrap();
+
+        let mock = MockPlatformService::with_config(github_config());
+        let cache = cache_with(&[("feat-a", make_pr(7, "feat-a", "main"))]);
+
+        create_submission_plan_with_cache(&analysis, &mock, "upstream", "main", &cache)
+            .await
+            .unwrap();
+
+        assert!(mock.get_get_pr_calls().is_empty());
+        mock.assert_find_pr_called_for(&["feat-a"]);
+    }
+}
+
+mod adopt_test {
+    use crate::common::{MockPlatformService, github_config, make_pr, make_pr_comment};
+    use jj_ryu::error::Error;
+    use jj_ryu::submit::{
+        StackCommentData, StackItem, StackSource, discover_stack, discover_stack_below,
+        format_stack_comment,
+    };
+    use jj_ryu::types::{PrState, PullRequest};
+
+    fn stack_data(prs: &[&PullRequest]) -> StackCommentData {
+        StackCommentData {
+            version: 1,
+            stack: prs
+                .iter()
+                .map(|pr| StackItem {
+                    bookmark_name: pr.head_ref.clone(),
+                    pr_url: pr.html_url.clone(),
+                    pr_number: pr.number,
+                    pr_title: pr.title.clone(),
+                    pr_state: pr.state,
+                })
+                .collect(),
+            base_branch: "main".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_discover_from_stack_comment() {
+        let a = make_pr(1, "feat-a", "main");
+        let b = make_pr(2, "feat-b", "feat-a");
+        let c = make_pr(3, "feat-c", "feat-b");
+        let mock = MockPlatformService::with_config(github_config());
+        for pr in [&a, &b, &c] {
+            mock.set_get_pr_response(pr.clone());
+        }
+        let data = stack_data(&[&a, &b, &c]);
+        mock.set_list_comments_response(
+            2,
+            vec![make_pr_comment(
+                10,
+                &format_stack_comment(&data, 1).unwrap(),
+            )],
+        );
+
+        // Adopting from the middle PR still finds the whole stack
+        let stack = discover_stack(&mock, 2, "main").await.unwrap();
+
+        assert_eq!(stack.source, StackSource::StackComment);
+        assert_eq!(stack.base_branch, "main");
+        let heads: Vec<_> = stack.prs.iter().map(|pr| pr.head_ref.as_str()).collect();
+        assert_eq!(heads, vec!["feat-a", "feat-b", "feat-c"]);
+        assert!(stack.skipped.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_discover_skips_merged_prs_from_comment() {
+        let mut a = make_pr(1, "feat-a", "main");
+        let b = make_pr(2, "feat-b", "main");
+        let mock = MockPlatformService::with_config(github_config());
+        let data = stack_data(&[&a, &b]);
+        a.state = PrState::Merged;
+        mock.set_get_pr_response(a);
+        mock.set_get_pr_response(b);
+        mock.set_pr_body(
+            2,
+            &format!("Description\n\n{}", format_stack_comment(&data, 1).unwrap()),
+        );
+
+        let stack = discover_stack(&mock, 2, "main").await.unwrap();
+
+        assert_eq!(stack.source, StackSource::StackComment);
+        assert_eq!(stack.prs.len(), 1);
+        assert_eq!(stack.prs[0].head_ref, "feat-b");
+        assert_eq!(stack.skipped.len(), 1);
+        assert_eq!(stack.skipped[0].number, 1);
+    }
+
+    #[tokio::test]
+    async fn test_discover_from_base_refs() {
+        let mock = MockPlatformService::with_config(github_config());
+        mock.set_get_pr_response(make_pr(3, "feat-c", "feat-b"));
+        mock.set_find_pr_response("feat-b", Some(make_pr(2, "feat-b", "feat-a")));
+        mock.set_find_pr_response("feat-a", Some(make_pr(1, "feat-a", "main")));
+
+        let stack = discover_stack(&mock, 3, "main").await.unwrap();
+
+        assert_eq!(stack.source, StackSource::BaseRefs);
+        assert_eq!(stack.base_branch, "main");
+        let numbers: Vec<_> = stack.prs.iter().map(|pr| pr.number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        mock.assert_find_pr_called_for(&["feat-b", "feat-a"]);
+    }
+
+    #[tokio::test]
+    async fn test_discover_stops_at_branch_without_pr() {
+        let mock = MockPlatformService::with_config(github_config());
+        mock.set_get_pr_response(make_pr(3, "feat-c", "feat-b"));
+
+        let stack = discover_stack(&mock, 3, "main").await.unwrap();
+
+        assert_eq!(stack.prs.len(), 1);
+        assert_eq!(stack.base_branch, "feat-b");
+    }
+
+    #[tokio::test]
+    async fn test_discover_below_ignores_prs_on_top() {
+        let a = make_pr(1, "feat-a", "main");
+        let b = make_pr(2, "feat-b", "feat-a");
+        let c = make_pr(3, "feat-c", "feat-b");
+        let mock = MockPlatformService::with_config(github_config());
+        mock.set_get_pr_response(b.clone());
+        mock.set_find_pr_response("feat-a", Some(a.clone()));
+        let data = stack_data(&[&a, &b, &c]);
+        mock.set_list_comments_response(
+            2,
+            vec![make_pr_comment(
+                10,
+                &format_stack_comment(&data, 1).unwrap(),
+            )],
+        );
+
+        let stack = discover_stack_below(&mock, 2, "main").await.unwrap();
+
+        assert_eq!(stack.source, StackSource::BaseRefs);
+        let numbers: Vec<_> = stack.prs.iter().map(|pr| pr.number).collect();
+        assert_eq!(numbers, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_discover_missing_pr() {
+        let mock = MockPlatformService::with_config(github_config());
+
+        let result = discover_stack(&mock, 42, "main").await;
+
+        assert!(matches!(result, Err(Error::Platform(_))));
+    }
+}
+
+mod import_test {
+    use crate::common::{MockPlatformService, github_config, make_pr};
+    use jj_ryu::import::{ImportCandidate, PrLocator, resolve_import_prs};
+    use jj_ryu::types::PrState;
+
+    fn candidate(commit: &str, pr: PrLocator) -> ImportCandidate {
+        ImportCandidate {
+            commit_id: commit.to_string(),
+            pr,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_by_number_and_branch() {
+        let mock = MockPlatformService::with_config(github_config());
+        mock.set_get_pr_response(make_pr(12, "gt-login", "main"));
+        mock.set_find_pr_response(
+            "spr/main/1a2b3c4d",
+            Some(make_pr(13, "spr/main/1a2b3c4d", "gt-login")),
+        );
+
+        let (imported, unmatched) = resolve_import_prs(
+            &mock,
+            vec![
+                candidate("aaaa", PrLocator::Number(12)),
+                candidate("bbbb", PrLocator::Branch("spr/main/1a2b3c4d".to_string())),
+            ],
+        )
+        .await
+        .unwrap();
+
+        assert!(unmatched.is_empty());
+        let pairs: Vec<_> = imported
+            .iter()
+            .map(|i| (i.commit_id.as_str(), i.pr.number))
+            .collect();
+        assert_eq!(pairs, vec![("aaaa", 12), ("bbbb", 13)]);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_skips_missing_and_closed_prs() {
+        let mock = MockPlatformService::with_config(github_config());
+        let mut merged = make_pr(12, "gt-login", "main");
+        merged.state = PrState::Merged;
+        mock.set_get_pr_response(merged);
+
+        let (imported, unmatched) = resolve_import_prs(
+            &mock,
+            vec![
+                candidate("aaaa", PrLocator::Number(12)),
+                candidate("bbbb", PrLocator::Branch("gh/alice/3/head".to_string())),
+            ],
+        )
+        .await
+        .unwrap();
+
+        assert!(imported.is_empty());
+        assert_eq!(unmatched.len(), 2);
+    }
 }
 
 mod stack_comment_test {
@@ -546,7 +891,7 @@ mod stack_comment_test {
         COMMENT_DATA_PREFIX, STACK_COMMENT_THIS_PR, StackCommentData, StackItem, SubmissionPlan,
         build_stack_comment_data, format_stack_comment,
     };
-    use jj_ryu::types::{Bookmark, NarrowedBookmarkSegment, PullRequest};
+    use jj_ryu::types::{Bookmark, NarrowedBookmarkSegment, PrState, PullRequest};
     use std::collections::HashMap;
 
     fn make_bookmark(name: &str) -> Bookmark {
@@ -556,6 +901,9 @@ mod stack_comment_test {
             change_id: format!("{name}_change"),
             has_remote: false,
             is_synced: false,
+            is_conflicted: false,
+            has_conflicts: false,
+            is_divergent: false,
         }
     }
 
@@ -568,6 +916,9 @@ mod stack_comment_test {
             title: format!("PR for {bookmark}"),
             node_id: Some(format!("PR_node_{number}")),
             is_draft: false,
+            state: PrState::Open,
+            comments: None,
+            checks: None,
         }
     }
 
@@ -577,6 +928,7 @@ mod stack_comment_test {
             pr_url: format!("https://github.com/test/test/pull/{number}"),
             pr_number: number,
             pr_title: format!("feat: {name}"),
+            pr_state: PrState::Open,
         }
     }
 
@@ -592,6 +944,7 @@ mod stack_comment_test {
             existing_prs: HashMap::new(),
             remote: "origin".to_string(),
             default_branch: "main".to_string(),
+            drift: vec![],
         };
 
         let mut bookmark_to_pr = HashMap::new();
@@ -628,6 +981,7 @@ mod stack_comment_test {
             existing_prs: HashMap::new(),
             remote: "origin".to_string(),
             default_branch: "main".to_string(),
+            drift: vec![],
         };
 
         let mut bookmark_to_pr = HashMap::new();
@@ -738,11 +1092,85 @@ mod stack_comment_test {
             "body should contain PR title: {body}"
         );
     }
+
+    #[test]
+    fn test_custom_template_keeps_comment_data() {
+        use jj_ryu::submit::{StackCommentTemplate, render_stack_comment};
+
+        let data = StackCommentData {
+            version: 1,
+            stack: vec![make_stack_item("feat-a", 1), make_stack_item("feat-b", 2)],
+            base_branch: "main".to_string(),
+        };
+        let template = StackCommentTemplate::parse(
+            "Stack ({{ stack | length }}):{% for pr in stack %} {{ pr.bookmark }}\
+             {% if pr.is_current %}*{% endif %}{% endfor %}",
+        )
+        .unwrap();
+
+        let body = render_stack_comment(&data, 0, Some(&template), false).unwrap();
+
+        assert!(body.starts_with(COMMENT_DATA_PREFIX), "{body}");
+        assert!(body.ends_with("\nStack (2): feat-a* feat-b"), "{body}");
+        assert!(!body.contains("managed by"), "{body}");
+    }
+
+    #[test]
+    fn test_parse_stack_comment_data_roundtrip() {
+        use jj_ryu::submit::{
+            STACK_SECTION_START, parse_stack_comment_data, replace_stack_section,
+        };
+
+        let data = StackCommentData {
+            version: 1,
+            stack: vec![make_stack_item("feat-a", 1), make_stack_item("feat-b", 2)],
+            base_branch: "main".to_string(),
+        };
+        let comment = format_stack_comment(&data, 1).unwrap();
+        assert_eq!(parse_stack_comment_data(&comment), Some(data.clone()));
+
+        // The description section embeds the same data
+        let body = replace_stack_section("My description", &comment);
+        assert!(body.contains(STACK_SECTION_START));
+        assert_eq!(parse_stack_comment_data(&body), Some(data));
+
+        assert_eq!(parse_stack_comment_data("LGTM"), None);
+        assert_eq!(
+            parse_stack_comment_data(&format!("{COMMENT_DATA_PREFIX}not base64 --->")),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_stack_comment_removes_only_ryu_comment() {
+        use crate::common::{MockPlatformService, github_config};
+        use jj_ryu::submit::delete_stack_comment;
+        use jj_ryu::types::PrComment;
+
+        let mock = MockPlatformService::with_config(github_config());
+        mock.set_list_comments_response(
+            1,
+            vec![
+                PrComment {
+                    id: 10,
+                    body: "LGTM".to_string(),
+                },
+                PrComment {
+                    id: 11,
+                    body: format!("Stack\n{COMMENT_DATA_PREFIX}abc"),
+                },
+            ],
+        );
+
+        assert!(delete_stack_comment(&mock, 1).await.unwrap());
+        assert!(!delete_stack_comment(&mock, 2).await.unwrap());
+        assert_eq!(mock.get_delete_comment_calls(), vec![(1, 11)]);
+    }
 }
 
 mod sync_test {
     use jj_ryu::error::Error;
-    use jj_ryu::repo::select_remote;
+    use jj_ryu::repo::{select_remote, select_remote_with_default};
     use jj_ryu::types::GitRemote;
 
     fn make_remote(name: &str) -> GitRemote {
@@ -803,4 +1231,28 @@ mod sync_test {
             other => panic!("Expected NoSupportedRemotes error, got: {other:?}"),
         }
     }
+
+    #[test]
+    fn test_select_remote_uses_configured_default() {
+        let remotes = vec![make_remote("origin"), make_remote("fork")];
+        let result = select_remote_with_default(&remotes, None, Some("fork")).unwrap();
+        assert_eq!(result, "fork");
+    }
+
+    #[test]
+    fn test_select_remote_specified_overrides_configured() {
+        let remotes = vec![make_remote("origin"), make_remote("fork")];
+        let result = select_remote_with_default(&remotes, Some("origin"), Some("fork")).unwrap();
+        assert_eq!(result, "origin");
+    }
+
+    #[test]
+    fn test_select_remote_configured_not_found() {
+        let remotes = vec![make_remote("origin")];
+        let result = select_remote_with_default(&remotes, None, Some("fork"));
+        match result {
+            Err(Error::RemoteNotFound(name)) => assert_eq!(name, "fork"),
+            other => panic!("Expected RemoteNotFound error, got: {other:?}"),
+        }
+    }
 }